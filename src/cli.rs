@@ -3,6 +3,8 @@ use gumdrop::Options;
 /// `frust [--config config.yaml]`
 /// `frust import OUTPUT OPML_FILE [OPML_FILE…]`
 /// `frust export OUTPUT [CONFIG_FILE]`
+/// `frust check [CONFIG_FILE]`
+/// `frust discover URL`
 #[derive(Debug, Options)]
 pub struct CliOptions {
     #[options(help = "print help message")]
@@ -31,6 +33,10 @@ pub enum Command {
     /// With CONFIG_FILE    → OPML generated from the given YAML configuration.
     #[options(help = "export OPML from a config, or a zip archive of feeds+media from the DB")]
     Export(ExportOpts),
+    #[options(help = "validate a YAML configuration and report every problem found")]
+    Check(CheckOpts),
+    #[options(help = "find the feeds of a web page and print them as YAML configuration")]
+    Discover(DiscoverOpts),
 }
 
 /// `frust import OUTPUT OPML_FILE [OPML_FILE…]`
@@ -76,3 +82,37 @@ impl ExportOpts {
         self.args.get(1).map(String::as_str)
     }
 }
+
+/// `frust check [CONFIG_FILE]`
+///
+/// Without CONFIG_FILE, the file given via `-c` (or `config.yaml`) is checked.
+#[derive(Debug, Options)]
+pub struct CheckOpts {
+    #[options(help = "print help message")]
+    pub help: bool,
+
+    #[options(free)]
+    pub args: Vec<String>,
+}
+
+impl CheckOpts {
+    pub fn config_file(&self) -> Option<&str> {
+        self.args.first().map(String::as_str)
+    }
+}
+
+/// `frust discover URL`
+#[derive(Debug, Options)]
+pub struct DiscoverOpts {
+    #[options(help = "print help message")]
+    pub help: bool,
+
+    #[options(free)]
+    pub args: Vec<String>,
+}
+
+impl DiscoverOpts {
+    pub fn url(&self) -> Option<&str> {
+        self.args.first().map(String::as_str)
+    }
+}
@@ -1,11 +1,14 @@
 use std::fs;
 use std::io::BufWriter;
+use std::time::Duration;
 
 use quick_xml::Writer;
 
-use crate::cli::{ExportOpts, ImportOpts};
+use crate::cli::{CheckOpts, DiscoverOpts, ExportOpts, ImportOpts};
 use crate::error::FrustError;
 use crate::opml::{ParsedGroup, build_yaml, parse_opml, write_opml};
+use crate::processing::discovery;
+use crate::processing::fetch::{Fetcher, RetryPolicy, build_client};
 
 /// `frust export OUTPUT`
 ///
@@ -91,7 +94,14 @@ pub fn export_opml(opts: &ExportOpts) -> Result<(), FrustError> {
         .ok_or_else(|| FrustError::Config("usage: frust export OUTPUT CONFIG_FILE".to_string()))?;
 
     tracing::info!("Loading config from {}", config_file);
-    let app = crate::config::load_config_file(config_file.to_string());
+    let mut app = crate::config::load_config_file(config_file.to_string())?;
+    // Titles and URLs learnt from the feeds, when frust already ran with this config
+    let states_path = format!("{}/states.redb", app.output);
+    if std::path::Path::new(&states_path).exists() {
+        let articles_path = format!("{}/articles.redb", app.output);
+        let storage = crate::storage::Storage::new(&articles_path, &states_path)?;
+        app.complete_feeds(&storage.load_all_states()?);
+    }
 
     if let Some(parent) = std::path::Path::new(output).parent()
         && !parent.as_os_str().is_empty()
@@ -106,3 +116,53 @@ pub fn export_opml(opts: &ExportOpts) -> Result<(), FrustError> {
     tracing::info!("OPML written to {}", output);
     Ok(())
 }
+
+/// `frust check [CONFIG_FILE]`
+///
+/// Loads the YAML configuration like the aggregator does, and also reports the mistakes
+/// it would silently ignore: unknown keys, undefined filters, invalid CSS selectors,
+/// duplicate feed URLs and output files written by several groups or feeds.
+pub fn check_config(opts: &CheckOpts, default_config: &str) -> Result<(), FrustError> {
+    let config_file = opts.config_file().unwrap_or(default_config);
+    tracing::info!("Checking config file {}", config_file);
+    let app = crate::config::check_config_file(config_file)?;
+    tracing::info!(
+        "Config file {} is valid ({} group(s), {} feed(s))",
+        config_file,
+        app.groups.len(),
+        app.groups.values().map(|g| g.feeds.len()).sum::<usize>()
+    );
+    Ok(())
+}
+
+/// `frust discover URL`
+///
+/// Finds the feeds of the web page at URL and prints them to stdout as a group of the
+/// YAML configuration, in the same format as `frust import`.
+pub async fn discover(opts: &DiscoverOpts) -> Result<(), FrustError> {
+    let url = opts
+        .url()
+        .ok_or_else(|| FrustError::Config("usage: frust discover URL".to_string()))?;
+    let client = build_client(
+        Duration::from_secs(crate::DEFAULT_HTTP_TIMEOUT.into()),
+        None,
+    )?;
+    let fetcher = Fetcher::new(client, 1, Duration::ZERO, RetryPolicy::default());
+    let found = discovery::discover(&fetcher, url).await?;
+
+    let title = if found.page_title.is_empty() {
+        url::Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_string))
+            .unwrap_or_else(|| url.to_string())
+    } else {
+        found.page_title
+    };
+    let group = ParsedGroup {
+        slug: slug::slugify(&title),
+        title,
+        feeds: found.feeds,
+    };
+    print!("{}", build_yaml(&[group]));
+    Ok(())
+}
@@ -1,12 +1,624 @@
-use std::{collections::HashMap, convert::TryFrom};
+use std::{
+    collections::{HashMap, HashSet},
+    convert::TryFrom,
+    path::{Path, PathBuf},
+};
 
 use linked_hash_map::LinkedHashMap;
-use regex::{RegexSet, RegexSetBuilder};
+use regex::{RegexBuilder, RegexSet, RegexSetBuilder};
 use slug::slugify;
 use twox_hash::XxHash3_64;
-use yaml_rust::Yaml;
+use yaml_rust::{
+    Yaml,
+    parser::{Event, MarkedEventReceiver, Parser},
+    scanner::Marker,
+};
 
-use crate::model::{App, Feed, Filter, Group};
+use crate::error::{ConfigIssue, FrustError};
+use crate::model::{
+    App, ContentMode, ExportStrategy, Feed, Filter, FilterExpr, FilterRule, Group, HttpAuth,
+    HttpSettings, Predicate, PredicateField, PredicateOp,
+};
+
+/// Collects every problem found while loading the config, so that they are all
+/// reported at once instead of stopping at the first one.
+#[derive(Default)]
+struct Diagnostics {
+    /// 1-based line and column of each YAML node, keyed by its path (e.g. `groups[2].feeds[0].url`)
+    positions: HashMap<String, (usize, usize)>,
+    issues: Vec<ConfigIssue>,
+    /// Also report the mistakes the aggregator silently ignores (unknown keys, undefined
+    /// filters, colliding outputs…), as done by `frust check`.
+    strict: bool,
+}
+
+impl Diagnostics {
+    /// Record a problem at `path`. When the path itself has no position (e.g. a missing
+    /// field), the position of the closest ancestor is used.
+    fn error(&mut self, path: &str, message: impl Into<String>) {
+        let mut lookup = path;
+        let position = loop {
+            if let Some(position) = self.positions.get(lookup) {
+                break Some(*position);
+            }
+            match lookup.rfind(['.', '[']) {
+                Some(i) => lookup = &lookup[..i],
+                None => break None,
+            }
+        };
+        self.issues.push(ConfigIssue {
+            path: path.to_string(),
+            position,
+            message: message.into(),
+        });
+    }
+}
+
+/// Keys accepted at each level of the config file, used to spot typos in strict mode.
+const APP_KEYS: &[&str] = &[
+    "output",
+    "base_url",
+    "workers",
+    "max_per_host",
+    "host_delay_ms",
+    "retries",
+    "retry_delay_ms",
+    "retrieve_server_media",
+    "retention",
+    "media",
+    "media_max_size",
+    "timeout",
+    "min_refresh_time",
+    "enrichment_prepend",
+    "enrichment_append",
+    "content_mode",
+    "filters",
+    "groups",
+    "http",
+];
+const FILTER_KEYS: &[&str] = &[
+    "slug",
+    "expressions",
+    "is_regex",
+    "is_include",
+    "must_match_all",
+    "predicates",
+    "filter_in_title",
+    "filter_in_summary",
+    "filter_in_content",
+    "keep",
+];
+const GROUP_KEYS: &[&str] = &[
+    "title",
+    "slug",
+    "output",
+    "strategy",
+    "format",
+    "retention",
+    "min_refresh_time",
+    "media",
+    "media_max_size",
+    "content_mode",
+    "enrichment_prepend",
+    "enrichment_append",
+    "filters",
+    "feeds",
+    "http",
+    "description",
+    "language",
+    "icon",
+    "author",
+    "site_url",
+];
+const FEED_KEYS: &[&str] = &[
+    "title",
+    "url",
+    "slug",
+    "page_url",
+    "output",
+    "retention",
+    "min_refresh_time",
+    "filters",
+    "content_mode",
+    "selector",
+    "media",
+    "media_max_size",
+    "enrichment_prepend",
+    "enrichment_append",
+    "http",
+];
+const HTTP_KEYS: &[&str] = &["user_agent", "headers", "auth", "proxy", "cookies"];
+/// Values of the group `format` key, the extensions of the exported files.
+const EXPORT_FORMATS: &[&str] = &["rss", "xml", "atom", "json", "md", "epub"];
+
+/// What has already been declared while loading groups and feeds, to detect the
+/// duplicates and the references to undefined filters.
+#[derive(Default)]
+struct Declared {
+    /// Filter ids defined in the `filters` section
+    filters: Vec<u64>,
+    /// Maps a group id to the path of the group that first used it
+    groups: HashMap<u64, String>,
+    /// Maps a feed id to the path of the feed that first used it
+    feeds: HashMap<u64, String>,
+    /// Maps a feed URL to the path of the feed that first used it
+    urls: HashMap<String, String>,
+    /// Maps a resolved output file to the path of the group or feed writing it
+    outputs: HashMap<PathBuf, String>,
+    /// Resolved output directories of the groups split into several files
+    split_dirs: HashSet<PathBuf>,
+}
+
+/// A container being walked by [`PositionCollector`].
+enum Frame {
+    Mapping { path: String, key: Option<String> },
+    Sequence { path: String, index: usize },
+}
+
+/// Records the position of every YAML node while the document is parsed, because
+/// `yaml_rust::Yaml` values do not keep track of where they come from.
+#[derive(Default)]
+struct PositionCollector {
+    stack: Vec<Frame>,
+    positions: HashMap<String, (usize, usize)>,
+}
+
+impl PositionCollector {
+    /// Path of the node starting now. Mapping keys are recorded and return `None`.
+    fn node_path(&mut self, ev: &Event, mark: Marker) -> Option<String> {
+        match self.stack.last_mut() {
+            None => Some(String::new()),
+            Some(Frame::Sequence { path, index }) => Some(format!("{}[{}]", path, index)),
+            Some(Frame::Mapping { path, key }) => match key.take() {
+                Some(k) => Some(join_path(path, &k)),
+                None => {
+                    let k = match ev {
+                        Event::Scalar(s, ..) => s.clone(),
+                        _ => "?".to_string(),
+                    };
+                    // Point to the key rather than its value: it is where users look first.
+                    // The mapping itself is located at its first key, block mapping starts
+                    // being reported after that key has been scanned.
+                    let position = (mark.line(), mark.col() + 1);
+                    self.positions.entry(path.clone()).or_insert(position);
+                    self.positions
+                        .entry(join_path(path, &k))
+                        .or_insert(position);
+                    *key = Some(k);
+                    None
+                }
+            },
+        }
+    }
+
+    /// A child node is complete: move on to the next key or item of the parent.
+    fn child_done(&mut self) {
+        if let Some(Frame::Sequence { index, .. }) = self.stack.last_mut() {
+            *index += 1;
+        }
+    }
+}
+
+impl MarkedEventReceiver for PositionCollector {
+    fn on_event(&mut self, ev: Event, mark: Marker) {
+        match ev {
+            Event::Scalar(..) | Event::Alias(_) => {
+                if let Some(path) = self.node_path(&ev, mark) {
+                    self.positions
+                        .entry(path)
+                        .or_insert((mark.line(), mark.col() + 1));
+                    self.child_done();
+                }
+            }
+            Event::MappingStart(_) => {
+                let path = self.node_path(&ev, mark).unwrap_or_default();
+                self.stack.push(Frame::Mapping { path, key: None });
+            }
+            Event::SequenceStart(_) => {
+                let path = self.node_path(&ev, mark).unwrap_or_default();
+                self.positions
+                    .entry(path.clone())
+                    .or_insert((mark.line(), mark.col() + 1));
+                self.stack.push(Frame::Sequence { path, index: 0 });
+            }
+            Event::MappingEnd | Event::SequenceEnd => {
+                self.stack.pop();
+                self.child_done();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Append `key` to a YAML path (`groups[0]` + `url` → `groups[0].url`).
+fn join_path(parent: &str, key: &str) -> String {
+    if parent.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", parent, key)
+    }
+}
+
+/// Report the keys of the mapping located at `parent` that are not in `known`
+/// (strict mode only).
+fn check_unknown_keys(
+    map: &LinkedHashMap<Yaml, Yaml>,
+    known: &[&str],
+    parent: &str,
+    diag: &mut Diagnostics,
+) {
+    if !diag.strict {
+        return;
+    }
+    for key in map.keys() {
+        match key.as_str() {
+            Some(k) if known.contains(&k) => {}
+            Some(k) => diag.error(&join_path(parent, k), format!("unknown key '{}'", k)),
+            None => diag.error(parent, "keys must be strings"),
+        }
+    }
+}
+
+/// Read the optional `filters` list of a group or a feed. Items are either filter slugs,
+/// whose undefined ones are only reported in strict mode, or `keep:`/`exclude:` mappings
+/// holding a filter expression (see [`parse_filter_expr`]).
+fn get_filter_rules_from_map(
+    map: &LinkedHashMap<Yaml, Yaml>,
+    parent: &str,
+    declared: &Declared,
+    diag: &mut Diagnostics,
+) -> Vec<FilterRule> {
+    let path = join_path(parent, "filters");
+    let Some(value) = get_field(map, "filters") else {
+        return Vec::new();
+    };
+    let Some(values) = value.as_vec() else {
+        diag.error(&path, "expected a list");
+        return Vec::new();
+    };
+    let mut rules = Vec::with_capacity(values.len());
+    for (i, v) in values.iter().enumerate() {
+        let item_path = format!("{}[{}]", path, i);
+        if let Some(name) = v.as_str() {
+            let h = XxHash3_64::oneshot(name.as_bytes());
+            if diag.strict && !declared.filters.contains(&h) {
+                diag.error(&item_path, format!("filter '{}' is not defined", name));
+            }
+            rules.push(FilterRule::Named(h));
+            continue;
+        }
+        let rule = match v.as_hash().filter(|m| m.len() == 1).and_then(|m| m.front()) {
+            Some((Yaml::String(k), expr)) if k == "keep" => {
+                parse_filter_expr(expr, &join_path(&item_path, k), declared, diag)
+                    .map(FilterRule::Keep)
+            }
+            Some((Yaml::String(k), expr)) if k == "exclude" => {
+                parse_filter_expr(expr, &join_path(&item_path, k), declared, diag)
+                    .map(FilterRule::Exclude)
+            }
+            _ => {
+                diag.error(
+                    &item_path,
+                    "expected a filter slug, or a mapping with a single `keep` or `exclude` key",
+                );
+                None
+            }
+        };
+        rules.extend(rule);
+    }
+    rules
+}
+
+/// Parse a filter expression: a filter slug, or a mapping with a single `any` (list),
+/// `all` (list) or `not` (expression) key, nested as deep as needed. Unlike the plain
+/// slugs of a `filters` list, slugs used in expressions must always be defined.
+fn parse_filter_expr(
+    value: &Yaml,
+    path: &str,
+    declared: &Declared,
+    diag: &mut Diagnostics,
+) -> Option<FilterExpr> {
+    if let Some(name) = value.as_str() {
+        let h = XxHash3_64::oneshot(name.as_bytes());
+        if !declared.filters.contains(&h) {
+            diag.error(path, format!("filter '{}' is not defined", name));
+            return None;
+        }
+        return Some(FilterExpr::Filter(h));
+    }
+    let Some((Yaml::String(op), operand)) = value
+        .as_hash()
+        .filter(|m| m.len() == 1)
+        .and_then(|m| m.front())
+    else {
+        diag.error(
+            path,
+            "expected a filter slug, or a mapping with a single `any`, `all` or `not` key",
+        );
+        return None;
+    };
+    let op_path = join_path(path, op);
+    match op.as_str() {
+        "not" => parse_filter_expr(operand, &op_path, declared, diag)
+            .map(|e| FilterExpr::Not(Box::new(e))),
+        "any" | "all" => {
+            let Some(items) = operand.as_vec().filter(|items| !items.is_empty()) else {
+                diag.error(&op_path, "expected a non-empty list of filter expressions");
+                return None;
+            };
+            // parse every item so that all their problems are reported
+            let exprs: Vec<Option<FilterExpr>> = items
+                .iter()
+                .enumerate()
+                .map(|(i, item)| {
+                    parse_filter_expr(item, &format!("{}[{}]", op_path, i), declared, diag)
+                })
+                .collect();
+            let exprs: Vec<FilterExpr> = exprs.into_iter().collect::<Option<_>>()?;
+            Some(if op == "any" {
+                FilterExpr::Any(exprs)
+            } else {
+                FilterExpr::All(exprs)
+            })
+        }
+        _ => {
+            diag.error(
+                &op_path,
+                format!(
+                    "unknown filter operator '{}' (expected any, all or not)",
+                    op
+                ),
+            );
+            None
+        }
+    }
+}
+
+/// Read a secret: a string, or a mapping with a single `env` (name of an environment
+/// variable) or `file` (path of a file, whose content is trimmed) key, so that the
+/// secrets can stay out of the config file.
+fn get_secret(value: &Yaml, path: &str, diag: &mut Diagnostics) -> Option<String> {
+    if let Some(secret) = value.as_str() {
+        return Some(secret.to_string());
+    }
+    match value
+        .as_hash()
+        .filter(|m| m.len() == 1)
+        .and_then(|m| m.front())
+    {
+        Some((Yaml::String(k), Yaml::String(name))) if k == "env" => match std::env::var(name) {
+            Ok(secret) => Some(secret),
+            Err(_) => {
+                diag.error(
+                    &join_path(path, k),
+                    format!("environment variable '{}' is not set", name),
+                );
+                None
+            }
+        },
+        Some((Yaml::String(k), Yaml::String(file))) if k == "file" => {
+            match std::fs::read_to_string(file) {
+                Ok(secret) => Some(secret.trim().to_string()),
+                Err(e) => {
+                    diag.error(
+                        &join_path(path, k),
+                        format!("cannot read '{}': {}", file, e),
+                    );
+                    None
+                }
+            }
+        }
+        _ => {
+            diag.error(
+                path,
+                "expected a string, or a mapping with a single `env` or `file` key",
+            );
+            None
+        }
+    }
+}
+
+/// Read a mapping of names to secrets (headers or cookies) located at `path`, and merge
+/// it into `values`: an entry replaces the inherited one with the same name.
+fn merge_named_secrets(
+    value: &Yaml,
+    values: &mut Vec<(String, String)>,
+    is_header: bool,
+    path: &str,
+    diag: &mut Diagnostics,
+) {
+    let Some(map) = value.as_hash() else {
+        diag.error(path, "expected a mapping");
+        return;
+    };
+    for (k, v) in map {
+        let Some(name) = k.as_str() else {
+            diag.error(path, "keys must be strings");
+            continue;
+        };
+        let item_path = join_path(path, name);
+        let Some(secret) = get_secret(v, &item_path, diag) else {
+            continue;
+        };
+        if is_header {
+            if reqwest::header::HeaderName::from_bytes(name.as_bytes()).is_err() {
+                diag.error(&item_path, format!("invalid header name '{}'", name));
+                continue;
+            }
+            if reqwest::header::HeaderValue::from_str(&secret).is_err() {
+                diag.error(&item_path, "invalid header value");
+                continue;
+            }
+        }
+        let same_name = |(n, _): &(String, String)| {
+            if is_header {
+                n.eq_ignore_ascii_case(name)
+            } else {
+                n == name
+            }
+        };
+        match values.iter_mut().find(|entry| same_name(entry)) {
+            Some(entry) => entry.1 = secret,
+            None => values.push((name.to_string(), secret)),
+        }
+    }
+}
+
+/// Read the `auth` mapping located at `path`: a single `basic` (`username` and optional
+/// `password`) or `bearer` (token) key.
+fn parse_http_auth(value: &Yaml, path: &str, diag: &mut Diagnostics) -> Option<HttpAuth> {
+    match value
+        .as_hash()
+        .filter(|m| m.len() == 1)
+        .and_then(|m| m.front())
+    {
+        Some((Yaml::String(k), credentials)) if k == "basic" => {
+            let basic_path = join_path(path, k);
+            let Some(m) = credentials.as_hash() else {
+                diag.error(
+                    &basic_path,
+                    "expected a mapping with `username` and `password`",
+                );
+                return None;
+            };
+            check_unknown_keys(m, &["username", "password"], &basic_path, diag);
+            let username = match get_field(m, "username") {
+                Some(v) => get_secret(v, &join_path(&basic_path, "username"), diag)?,
+                None => {
+                    diag.error(&join_path(&basic_path, "username"), "field is missing");
+                    return None;
+                }
+            };
+            let password = match get_field(m, "password") {
+                Some(v) => Some(get_secret(v, &join_path(&basic_path, "password"), diag)?),
+                None => None,
+            };
+            Some(HttpAuth::Basic { username, password })
+        }
+        Some((Yaml::String(k), token)) if k == "bearer" => {
+            get_secret(token, &join_path(path, k), diag).map(HttpAuth::Bearer)
+        }
+        _ => {
+            diag.error(
+                path,
+                "expected a mapping with a single `basic` or `bearer` key",
+            );
+            None
+        }
+    }
+}
+
+/// Read the optional `http` mapping of the app, a group or a feed on top of the
+/// `inherited` settings: the values it defines replace the inherited ones, headers and
+/// cookies are merged by name.
+fn get_http_settings_from_map(
+    map: &LinkedHashMap<Yaml, Yaml>,
+    inherited: &HttpSettings,
+    parent: &str,
+    diag: &mut Diagnostics,
+) -> HttpSettings {
+    let mut settings = inherited.clone();
+    let path = join_path(parent, "http");
+    let Some(value) = get_field(map, "http") else {
+        return settings;
+    };
+    let Some(m) = value.as_hash() else {
+        diag.error(&path, "expected a mapping");
+        return settings;
+    };
+    check_unknown_keys(m, HTTP_KEYS, &path, diag);
+
+    let user_agent = get_string_field_from_map(m, "user_agent", false, &path, diag);
+    if !user_agent.is_empty() {
+        settings.user_agent = Some(user_agent);
+    }
+    if let Some(headers) = get_field(m, "headers") {
+        merge_named_secrets(
+            headers,
+            &mut settings.headers,
+            true,
+            &join_path(&path, "headers"),
+            diag,
+        );
+    }
+    if let Some(auth) = get_field(m, "auth") {
+        settings.auth = parse_http_auth(auth, &join_path(&path, "auth"), diag);
+    }
+    let proxy = get_string_field_from_map(m, "proxy", false, &path, diag);
+    if !proxy.is_empty() {
+        match reqwest::Proxy::all(&proxy) {
+            Ok(_) => settings.proxy = Some(proxy),
+            Err(e) => diag.error(
+                &join_path(&path, "proxy"),
+                format!("invalid proxy URL: {}", e),
+            ),
+        }
+    }
+    if let Some(cookies) = get_field(m, "cookies") {
+        merge_named_secrets(
+            cookies,
+            &mut settings.cookies,
+            false,
+            &join_path(&path, "cookies"),
+            diag,
+        );
+    }
+    settings
+}
+
+/// Remember the output file written by the group or feed at `path`, reporting it when
+/// another one already writes there, or when one of them is in the directory of a
+/// group split into several files (`is_dir`), which removes the files it did not write
+/// (strict mode only).
+fn declare_output(
+    app_output: &str,
+    output: &str,
+    is_dir: bool,
+    path: &str,
+    declared: &mut Declared,
+    diag: &mut Diagnostics,
+) {
+    if !diag.strict {
+        return;
+    }
+    let dest = Path::new(app_output).join(output);
+    if let Some(other) = declared.outputs.get(&dest) {
+        diag.error(
+            &join_path(path, "output"),
+            format!(
+                "output '{}' is already written by {}",
+                dest.display(),
+                other
+            ),
+        );
+        return;
+    }
+    let nested = declared.outputs.iter().find(|(other_dest, _)| {
+        (is_dir && other_dest.starts_with(&dest))
+            || (declared.split_dirs.contains(*other_dest) && dest.starts_with(other_dest))
+    });
+    if let Some((other_dest, other)) = nested {
+        diag.error(
+            &join_path(path, "output"),
+            format!(
+                "output '{}' and the output '{}' of {} are nested, the files of a split group must be alone in their directory",
+                dest.display(),
+                other_dest.display(),
+                other
+            ),
+        );
+    }
+    if is_dir {
+        declared.split_dirs.insert(dest.clone());
+    }
+    declared.outputs.insert(dest, path.to_string());
+}
+
+/// Get a field, `null` values being handled as missing ones.
+fn get_field<'a>(map: &'a LinkedHashMap<Yaml, Yaml>, field: &str) -> Option<&'a Yaml> {
+    map.get(&Yaml::String(field.to_string()))
+        .filter(|v| !v.is_null())
+}
 
 /// Concatenates two optional enrichment template strings.
 /// If both are `Some`, they are joined without any separator.
@@ -20,193 +632,471 @@ fn concat_enrichment(outer: Option<&str>, inner: Option<&str>) -> Option<String>
     }
 }
 
+/// Read a string field of the mapping located at `parent`. Missing optional fields
+/// give an empty string.
 fn get_string_field_from_map(
     map: &LinkedHashMap<Yaml, Yaml>,
-    field: String,
+    field: &str,
     required: bool,
-    yaml_path: Option<String>,
+    parent: &str,
+    diag: &mut Diagnostics,
 ) -> String {
-    if let Some(value) = map.get(&Yaml::String(field)) {
-        return value.as_str().unwrap().to_string();
+    match get_field(map, field) {
+        Some(Yaml::String(value)) => value.clone(),
+        Some(_) => {
+            diag.error(&join_path(parent, field), "expected a string");
+            String::new()
+        }
+        None => {
+            if required {
+                diag.error(&join_path(parent, field), "field is missing");
+            }
+            String::new()
+        }
     }
-    if required {
-        panic!(
-            "Field missing in config file: {}",
-            yaml_path.unwrap_or_else(|| "UNKNOWN".to_string())
+}
+
+/// Read an optional boolean field of the mapping located at `parent`.
+fn get_bool_field_from_map(
+    map: &LinkedHashMap<Yaml, Yaml>,
+    field: &str,
+    parent: &str,
+    diag: &mut Diagnostics,
+) -> Option<bool> {
+    let value = get_field(map, field)?;
+    if value.as_bool().is_none() {
+        diag.error(
+            &join_path(parent, field),
+            "expected a boolean (true or false)",
         );
     }
-    String::with_capacity(0)
+    value.as_bool()
+}
+
+/// Read an optional integer field of the mapping located at `parent`, checking that it
+/// fits in `T`.
+fn get_int_field_from_map<T: TryFrom<i64>>(
+    map: &LinkedHashMap<Yaml, Yaml>,
+    field: &str,
+    parent: &str,
+    diag: &mut Diagnostics,
+) -> Option<T> {
+    let value = get_field(map, field)?;
+    match value.as_i64().map(T::try_from) {
+        Some(Ok(v)) => Some(v),
+        Some(Err(_)) => {
+            diag.error(&join_path(parent, field), "integer out of range");
+            None
+        }
+        None => {
+            diag.error(&join_path(parent, field), "expected an integer");
+            None
+        }
+    }
+}
+
+/// Read an optional list of strings (e.g. filter slugs) of the mapping located at `parent`.
+fn get_string_list_from_map(
+    map: &LinkedHashMap<Yaml, Yaml>,
+    field: &str,
+    parent: &str,
+    diag: &mut Diagnostics,
+) -> Vec<String> {
+    let path = join_path(parent, field);
+    let Some(value) = get_field(map, field) else {
+        return Vec::new();
+    };
+    let Some(values) = value.as_vec() else {
+        diag.error(&path, "expected a list");
+        return Vec::new();
+    };
+    let mut strings = Vec::with_capacity(values.len());
+    for (i, v) in values.iter().enumerate() {
+        match v.as_str() {
+            Some(s) => strings.push(s.to_string()),
+            None => diag.error(&format!("{}[{}]", path, i), "expected a string"),
+        }
+    }
+    strings
+}
+
+/// Read an optional enrichment template, empty strings being ignored.
+fn get_template_from_map(
+    map: &LinkedHashMap<Yaml, Yaml>,
+    field: &str,
+    parent: &str,
+    diag: &mut Diagnostics,
+) -> Option<String> {
+    Some(get_string_field_from_map(map, field, false, parent, diag)).filter(|s| !s.is_empty())
+}
+
+/// Compute the slug of a feed: the explicit `slug:` key when provided, otherwise the
+/// URL host followed by a short hash of the full URL, so that several feeds hosted on
+/// the same site (e.g. YouTube channels) get distinct slugs and ids.
+pub(crate) fn feed_slug(explicit: &str, url: &url::Url) -> String {
+    if !explicit.is_empty() {
+        return slugify(explicit);
+    }
+    format!(
+        "{}-{:08x}",
+        slugify(url.host_str().unwrap_or("no-host")),
+        XxHash3_64::oneshot(url.as_str().as_bytes()) as u32
+    )
+}
+
+/// Map the feed ids used before slugs were unique (XXH3 of the slugified host) to the
+/// current feed ids. A legacy id maps to several feeds when they share the same host.
+pub(crate) fn legacy_feed_ids(app: &App) -> HashMap<u64, Vec<u64>> {
+    let mut legacy: HashMap<u64, Vec<u64>> = HashMap::new();
+    for (feed_id, feed) in app.groups.values().flat_map(|g| g.feeds.iter()) {
+        let Ok(url) = url::Url::parse(&feed.url) else {
+            continue;
+        };
+        let old_slug = slugify(url.host_str().unwrap_or("no-host"));
+        legacy
+            .entry(XxHash3_64::oneshot(old_slug.as_bytes()))
+            .or_default()
+            .push(*feed_id);
+    }
+    legacy
+}
+
+/// Read the optional `predicates` list of the filter located at `parent`.
+fn get_predicates_from_map(
+    map: &LinkedHashMap<Yaml, Yaml>,
+    parent: &str,
+    diag: &mut Diagnostics,
+) -> Vec<Predicate> {
+    let path = join_path(parent, "predicates");
+    let Some(value) = get_field(map, "predicates") else {
+        return Vec::new();
+    };
+    let Some(values) = value.as_vec() else {
+        diag.error(&path, "expected a list");
+        return Vec::new();
+    };
+    let mut predicates = Vec::with_capacity(values.len());
+    for (i, v) in values.iter().enumerate() {
+        let item_path = format!("{}[{}]", path, i);
+        let Some(m) = v.as_hash() else {
+            diag.error(&item_path, "expected a mapping");
+            continue;
+        };
+        check_unknown_keys(m, &["field", "op", "value"], &item_path, diag);
+        predicates.extend(parse_predicate(m, &item_path, diag));
+    }
+    predicates
+}
+
+/// Parse a `{ field, op, value }` predicate, checking that the operator applies to the
+/// field and that the value has the expected type.
+fn parse_predicate(
+    map: &LinkedHashMap<Yaml, Yaml>,
+    path: &str,
+    diag: &mut Diagnostics,
+) -> Option<Predicate> {
+    let field = get_string_field_from_map(map, "field", true, path, diag);
+    let op = get_string_field_from_map(map, "op", true, path, diag);
+    let value_path = join_path(path, "value");
+    let Some(value) = get_field(map, "value") else {
+        diag.error(&value_path, "field is missing");
+        return None;
+    };
+    if field.is_empty() || op.is_empty() {
+        return None; // already reported as missing
+    }
+    let field = match PredicateField::try_from(field.as_str()) {
+        Ok(f) => f,
+        Err(e) => {
+            diag.error(&join_path(path, "field"), e);
+            return None;
+        }
+    };
+    let (negate, base_op) = match op.strip_prefix("not_") {
+        Some(base) if matches!(base, "equals" | "in" | "starts_with") => (true, base),
+        _ => (false, op.as_str()),
+    };
+
+    // Text values are compared lowercased, numbers and dates as integers
+    let text = |diag: &mut Diagnostics| match value {
+        Yaml::String(s) => Some(s.to_lowercase()),
+        Yaml::Integer(i) => Some(i.to_string()),
+        _ => {
+            diag.error(&value_path, "expected a string");
+            None
+        }
+    };
+    let number = |diag: &mut Diagnostics| match value {
+        Yaml::Integer(i) => Some(*i),
+        // dates are compared as UNIX timestamps
+        Yaml::String(s) if field == PredicateField::Published => {
+            match chrono::DateTime::parse_from_rfc3339(s)
+                .map(|d| d.timestamp())
+                .or_else(|_| {
+                    chrono::NaiveDate::parse_from_str(s, "%Y-%m-%d")
+                        .map(|d| d.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp())
+                }) {
+                Ok(ts) => Some(ts),
+                Err(_) => {
+                    diag.error(&value_path, "expected a date (YYYY-MM-DD or RFC 3339)");
+                    None
+                }
+            }
+        }
+        _ => {
+            diag.error(&value_path, "expected an integer");
+            None
+        }
+    };
+
+    let op = match (base_op, field.is_numeric()) {
+        ("equals", false) => PredicateOp::Equals(text(diag)?),
+        ("starts_with", false) => PredicateOp::StartsWith(text(diag)?),
+        ("in", false) => {
+            let Some(items) = value.as_vec() else {
+                diag.error(&value_path, "expected a list");
+                return None;
+            };
+            let mut values = Vec::with_capacity(items.len());
+            for (i, item) in items.iter().enumerate() {
+                match item.as_str() {
+                    Some(s) => values.push(s.to_lowercase()),
+                    None => diag.error(&format!("{}[{}]", value_path, i), "expected a string"),
+                }
+            }
+            PredicateOp::In(values)
+        }
+        ("regex", false) => {
+            let Some(pattern) = value.as_str() else {
+                diag.error(&value_path, "expected a string");
+                return None;
+            };
+            match RegexBuilder::new(pattern).case_insensitive(true).build() {
+                Ok(re) => PredicateOp::Regex(re),
+                Err(e) => {
+                    diag.error(&value_path, format!("invalid regex: {}", e));
+                    return None;
+                }
+            }
+        }
+        ("equals", true) => PredicateOp::NumberEquals(number(diag)?),
+        ("greater_than", true) => PredicateOp::GreaterThan(number(diag)?),
+        ("less_than", true) => PredicateOp::LessThan(number(diag)?),
+        ("in" | "starts_with" | "regex" | "greater_than" | "less_than", _) => {
+            diag.error(
+                &join_path(path, "op"),
+                format!(
+                    "operator '{}' cannot be used on {} field '{}'",
+                    op,
+                    if field.is_numeric() {
+                        "numeric"
+                    } else {
+                        "text"
+                    },
+                    map.get(&Yaml::String("field".to_string()))
+                        .and_then(Yaml::as_str)
+                        .unwrap_or_default()
+                ),
+            );
+            return None;
+        }
+        _ => {
+            diag.error(
+                &join_path(path, "op"),
+                format!(
+                    "unknown operator '{}' (expected one of: equals, not_equals, in, not_in, starts_with, not_starts_with, regex, greater_than, less_than)",
+                    op
+                ),
+            );
+            return None;
+        }
+    };
+    Some(Predicate { field, op, negate })
+}
+
+/// Read the optional `content_mode` key, or return the inherited mode when it is missing.
+fn get_content_mode_from_map(
+    map: &LinkedHashMap<Yaml, Yaml>,
+    inherited: &ContentMode,
+    parent: &str,
+    diag: &mut Diagnostics,
+) -> ContentMode {
+    let mode = get_string_field_from_map(map, "content_mode", false, parent, diag);
+    if mode.is_empty() {
+        return inherited.clone();
+    }
+    ContentMode::try_from(mode.as_str()).unwrap_or_else(|e| {
+        diag.error(&join_path(parent, "content_mode"), e);
+        inherited.clone()
+    })
+}
+
+/// Read the optional group `strategy` key, monolithic when it is missing.
+fn get_strategy_from_map(
+    map: &LinkedHashMap<Yaml, Yaml>,
+    parent: &str,
+    diag: &mut Diagnostics,
+) -> ExportStrategy {
+    let strategy = get_string_field_from_map(map, "strategy", false, parent, diag);
+    if strategy.is_empty() {
+        return ExportStrategy::Monolithic;
+    }
+    ExportStrategy::try_from(strategy.as_str()).unwrap_or_else(|e| {
+        diag.error(&join_path(parent, "strategy"), e);
+        ExportStrategy::Monolithic
+    })
 }
 
 impl App {
-    fn load_globals(&mut self, map: &LinkedHashMap<Yaml, Yaml>) {
+    fn load_globals(&mut self, map: &LinkedHashMap<Yaml, Yaml>, diag: &mut Diagnostics) {
+        check_unknown_keys(map, APP_KEYS, "", diag);
         // load output folder
-        let output =
-            get_string_field_from_map(map, "output".to_string(), false, Some("output".to_string()));
+        let output = get_string_field_from_map(map, "output", false, "", diag);
         if !output.is_empty() {
             self.output = output;
         }
+        // public URL of the output folder, for the absolute links of the exports
+        let base_url = get_string_field_from_map(map, "base_url", false, "", diag);
+        if !base_url.is_empty() {
+            match url::Url::parse(&base_url) {
+                Ok(_) => self.base_url = Some(base_url.trim_end_matches('/').to_string()),
+                Err(e) => diag.error("base_url", format!("invalid URL: {}", e)),
+            }
+        }
         // set the number of workers
-        if let Some(value) = map.get(&Yaml::String("workers".to_string())) {
-            self.workers = usize::try_from(value.as_i64().unwrap())
-                .expect("Invalid data in config file: workers");
+        if let Some(workers) = get_int_field_from_map(map, "workers", "", diag) {
+            self.workers = workers;
+        }
+        // politeness towards each host
+        if let Some(max_per_host) = get_int_field_from_map(map, "max_per_host", "", diag) {
+            self.max_per_host = max_per_host;
+        }
+        if let Some(delay) = get_int_field_from_map(map, "host_delay_ms", "", diag) {
+            self.host_delay_ms = delay;
+        }
+        // retries of transient HTTP failures
+        if let Some(retries) = get_int_field_from_map(map, "retries", "", diag) {
+            self.retries = retries;
+        }
+        if let Some(delay) = get_int_field_from_map(map, "retry_delay_ms", "", diag) {
+            self.retry_delay_ms = delay;
         }
         // set if we should retrieve media from server
-        if let Some(value) = map.get(&Yaml::String("retrieve_server_media".to_string())) {
-            self.retrieve_media_server = value
-                .as_bool()
-                .expect("Invalid data in config file: retrieve_server_media");
+        if let Some(value) = get_bool_field_from_map(map, "retrieve_server_media", "", diag) {
+            self.retrieve_media_server = value;
+        }
+        // article retention in days (0 = keep forever)
+        if let Some(retention) = get_int_field_from_map(map, "retention", "", diag) {
+            self.retention = retention;
         }
         // enable media asset download
-        if let Some(value) = map.get(&Yaml::String("media".to_string())) {
-            self.media = value.as_bool().expect("Invalid data in config file: media");
+        if let Some(media) = get_bool_field_from_map(map, "media", "", diag) {
+            self.media = media;
         }
         // max asset size in bytes (0 = no limit)
-        if let Some(value) = map.get(&Yaml::String("media_max_size".to_string())) {
-            self.media_max_size = value
-                .as_i64()
-                .expect("Invalid data in config file: media_max_size")
-                as u64;
+        if let Some(max_size) = get_int_field_from_map(map, "media_max_size", "", diag) {
+            self.media_max_size = max_size;
         }
         // set the timeout for HTTP queries
-        if let Some(value) = map.get(&Yaml::String("timeout".to_string())) {
-            self.timeout = u8::try_from(value.as_i64().unwrap())
-                .expect("Invalid data in config file: timeout");
+        if let Some(timeout) = get_int_field_from_map(map, "timeout", "", diag) {
+            self.timeout = timeout;
+        }
+        // minimum time in seconds between two checks of a feed
+        if let Some(min_refresh) = get_int_field_from_map(map, "min_refresh_time", "", diag) {
+            self.min_refresh_time = min_refresh;
         }
         // app-level enrichment templates
-        self.enrichment_prepend = map
-            .get(&Yaml::String("enrichment_prepend".to_string()))
-            .and_then(|v| v.as_str())
-            .filter(|s| !s.is_empty())
-            .map(|s| s.to_string());
-        self.enrichment_append = map
-            .get(&Yaml::String("enrichment_append".to_string()))
-            .and_then(|v| v.as_str())
-            .filter(|s| !s.is_empty())
-            .map(|s| s.to_string());
-    }
-
-    fn load_filters(&mut self, map: &LinkedHashMap<Yaml, Yaml>) {
-        if let Some(filters) = map.get(&Yaml::String("filters".to_string())) {
-            let values = filters
-                .as_vec()
-                .expect("Invalid field in config file: filters");
+        self.enrichment_prepend = get_template_from_map(map, "enrichment_prepend", "", diag);
+        self.enrichment_append = get_template_from_map(map, "enrichment_append", "", diag);
+        // app-level content mode
+        self.content_mode = get_content_mode_from_map(map, &self.content_mode, "", diag);
+        // app-level HTTP settings
+        self.http = get_http_settings_from_map(map, &self.http, "", diag);
+    }
+
+    fn load_filters(&mut self, map: &LinkedHashMap<Yaml, Yaml>, diag: &mut Diagnostics) {
+        if let Some(filters) = get_field(map, "filters") {
+            let Some(values) = filters.as_vec() else {
+                diag.error("filters", "expected a list");
+                return;
+            };
             self.filters = HashMap::with_capacity(values.len());
             for (i, f) in values.iter().enumerate() {
-                let m = f.as_hash().expect("Invalid data in config file: filters");
+                let path = format!("filters[{}]", i);
+                let Some(m) = f.as_hash() else {
+                    diag.error(&path, "expected a mapping");
+                    continue;
+                };
+                check_unknown_keys(m, FILTER_KEYS, &path, diag);
                 // process filter name
-                let slug = get_string_field_from_map(
-                    m,
-                    "slug".to_string(),
-                    true,
-                    Some(format!("filters[{}].slug", i)),
-                );
+                let slug = get_string_field_from_map(m, "slug", true, &path, diag);
                 let h = XxHash3_64::oneshot(slug.as_bytes());
-                // process filter expressions/sentences
-                let value = m.get(&Yaml::String("expressions".to_string()));
-                if value.is_none() {
-                    panic!(
-                        "Field missing in config file: filters[{}].expressions in filter {}",
-                        i, slug
-                    );
-                }
-                let value = value.unwrap().as_vec();
-                if value.is_none() {
-                    panic!(
-                        "Invalid data in config file: filters[{}].expressions in filter {}",
-                        i, slug
-                    );
-                }
-                let value = value.unwrap();
-                let expressions: Vec<String> = value
-                    .iter()
-                    .map(|exp| {
-                        let sentence = exp
-                            .as_str()
-                            .unwrap_or_else(|| {
-                                panic!("Invalid filters.expressions string for filter {}", slug)
-                            })
-                            .to_string();
-                        sentence.to_lowercase()
-                    })
-                    .collect();
-                let value = m.get(&Yaml::String("is_regex".to_string()));
-                let mut is_regex = false;
-                if let Some(v) = value {
-                    is_regex = v.as_bool().unwrap_or_default();
+                // process filter expressions/sentences, optional when predicates are defined
+                let predicates = get_predicates_from_map(m, &path, diag);
+                if get_field(m, "expressions").is_none() && get_field(m, "predicates").is_none() {
+                    diag.error(&join_path(&path, "expressions"), "field is missing");
                 }
+                let expressions: Vec<String> =
+                    get_string_list_from_map(m, "expressions", &path, diag)
+                        .iter()
+                        .map(|sentence| sentence.to_lowercase())
+                        .collect();
+                let is_regex = get_bool_field_from_map(m, "is_regex", &path, diag).unwrap_or(false);
                 // handle scopes
-                let mut filter_in_title = true;
-                let mut filter_in_summary = true;
-                let mut filter_in_content = false;
-                let value = m.get(&Yaml::String("filter_in_title".to_string()));
-                if let Some(v) = value {
-                    filter_in_title = v.as_bool().unwrap_or_default();
-                }
-                let value = m.get(&Yaml::String("filter_in_summary".to_string()));
-                if let Some(v) = value {
-                    filter_in_summary = v.as_bool().unwrap_or_default();
-                }
-                let value = m.get(&Yaml::String("filter_in_content".to_string()));
-                if let Some(v) = value {
-                    filter_in_content = v.as_bool().unwrap_or_default();
-                }
+                let filter_in_title =
+                    get_bool_field_from_map(m, "filter_in_title", &path, diag).unwrap_or(true);
+                let filter_in_summary =
+                    get_bool_field_from_map(m, "filter_in_summary", &path, diag).unwrap_or(true);
+                let filter_in_content =
+                    get_bool_field_from_map(m, "filter_in_content", &path, diag).unwrap_or(false);
 
-                // process filter is_regex
-                let mut must_match_all = false;
-                if let Some(v) = m.get(&Yaml::String("must_match_all".to_string())) {
-                    must_match_all = v.as_bool().unwrap_or_else(|| {
-                        panic!("Invalid filters.is_regex boolean for filter {}", slug)
-                    });
-                }
+                // include (keep) or exclude filter, `is_include` being an alias of `keep`
+                let keep = match (
+                    get_bool_field_from_map(m, "keep", &path, diag),
+                    get_bool_field_from_map(m, "is_include", &path, diag),
+                ) {
+                    (Some(keep), Some(is_include)) if keep != is_include => {
+                        diag.error(
+                            &join_path(&path, "is_include"),
+                            "conflicts with `keep`, use only one of them",
+                        );
+                        keep
+                    }
+                    (keep, is_include) => keep.or(is_include).unwrap_or(false),
+                };
+
+                // process filter must_match_all
+                let must_match_all =
+                    get_bool_field_from_map(m, "must_match_all", &path, diag).unwrap_or(false);
                 // process filter regexes: will be generated from expressions and is_regex flag
-                // let value = m.get(&Yaml::String("regexes".to_string()));
-                // if value.is_none() {
-                //     panic!(
-                //         "Field missing in config file: filters[{}].regexes in filter {}",
-                //         i, slug
-                //     );
-                // }
-                // let value = value.unwrap().as_vec();
-                // if value.is_none() {
-                //     panic!(
-                //         "Invalid data in config file: filters[{}].regexes in filter {}",
-                //         i, slug
-                //     );
-                // }
-                // let value = value.unwrap();
-                // let expressions: Vec<String> = value
-                //     .iter()
-                //     .map(|exp| {
-                //         exp.as_str()
-                //             .unwrap_or_else(|| {
-                //                 panic!("Invalid filters.regexes string for filter {}", slug)
-                //             })
-                //             .to_string()
-                //     })
-                //     .collect();
                 let mut regexes = RegexSet::empty();
                 if is_regex {
-                    regexes = RegexSetBuilder::new(expressions.clone())
+                    match RegexSetBuilder::new(expressions.clone())
                         .case_insensitive(true)
                         .ignore_whitespace(true)
                         .unicode(true)
                         .build()
-                        .unwrap_or_else(|e| {
-                            panic!("Cannot build one regex for filter {}: {:?}", slug, e)
-                        });
+                    {
+                        Ok(set) => regexes = set,
+                        Err(e) => diag.error(
+                            &join_path(&path, "expressions"),
+                            format!("invalid regex: {}", e),
+                        ),
+                    }
                 }
                 self.filters.insert(
                     h,
                     Filter {
+                        slug,
                         expressions,
                         regexes,
                         is_regex,
                         must_match_all,
+                        predicates,
                         filter_in_title,
                         filter_in_summary,
                         filter_in_content,
-                        keep: false,
+                        keep,
                     },
                 );
             }
@@ -214,67 +1104,126 @@ impl App {
         tracing::info!("Loaded filters: {}", self.filters.len());
     }
 
-    fn load_groups(&mut self, map: &LinkedHashMap<Yaml, Yaml>) {
-        if let Some(groups) = map.get(&Yaml::String("groups".to_string())) {
-            let provided = groups.as_vec().expect("Invalid groups");
+    fn load_groups(&mut self, map: &LinkedHashMap<Yaml, Yaml>, diag: &mut Diagnostics) {
+        if let Some(groups) = get_field(map, "groups") {
+            let Some(provided) = groups.as_vec() else {
+                diag.error("groups", "expected a list");
+                return;
+            };
+            // Feed ids key the stored states and articles: they must be unique app-wide.
+            let mut declared = Declared {
+                filters: self.filters.keys().copied().collect(),
+                ..Declared::default()
+            };
 
-            for g in provided.iter() {
-                let m = g.as_hash().expect("Invalid group hash");
+            for (i, g) in provided.iter().enumerate() {
+                let path = format!("groups[{}]", i);
+                let Some(m) = g.as_hash() else {
+                    diag.error(&path, "expected a mapping");
+                    continue;
+                };
+                check_unknown_keys(m, GROUP_KEYS, &path, diag);
 
-                let mut group_obj = Group::default();
-                group_obj.slug = get_string_field_from_map(m, "slug".to_string(), true, None);
+                let optional_string = |field: &str, diag: &mut Diagnostics| {
+                    Some(get_string_field_from_map(m, field, false, &path, diag))
+                        .filter(|value| !value.is_empty())
+                };
+                let mut group_obj = Group {
+                    title: get_string_field_from_map(m, "title", false, &path, diag),
+                    slug: get_string_field_from_map(m, "slug", true, &path, diag),
+                    // channel metadata of the group outputs
+                    description: optional_string("description", diag),
+                    language: optional_string("language", diag),
+                    icon: optional_string("icon", diag),
+                    author: optional_string("author", diag),
+                    site_url: optional_string("site_url", diag),
+                    ..Group::default()
+                };
+                if let Some(site_url) = &group_obj.site_url
+                    && let Err(e) = url::Url::parse(site_url)
+                {
+                    diag.error(&join_path(&path, "site_url"), format!("invalid URL: {}", e));
+                }
 
                 // --- Group inheritance ---
                 // if group does not have output, it takes it from the App (global
-                group_obj.output = get_string_field_from_map(m, "output".to_string(), false, None);
+                group_obj.output = get_string_field_from_map(m, "output", false, &path, diag);
                 if group_obj.output.is_empty() {
                     group_obj.output = self.output.clone();
                 }
+                // Output files: one document, or a directory of files
+                group_obj.strategy = get_strategy_from_map(m, &path, diag);
+                declare_output(
+                    &self.output,
+                    &group_obj.output,
+                    group_obj.strategy != ExportStrategy::Monolithic,
+                    &path,
+                    &mut declared,
+                    diag,
+                );
+                group_obj.format = optional_string("format", diag);
+                match &group_obj.format {
+                    Some(format) if !EXPORT_FORMATS.contains(&format.as_str()) => diag.error(
+                        &join_path(&path, "format"),
+                        format!(
+                            "unknown format '{}' (expected one of: {})",
+                            format,
+                            EXPORT_FORMATS.join(", ")
+                        ),
+                    ),
+                    None if group_obj.strategy != ExportStrategy::Monolithic => diag.error(
+                        &join_path(&path, "format"),
+                        "field is missing, `output` is a directory with this strategy",
+                    ),
+                    _ => {}
+                }
 
                 // Group retention or global if missing
-                group_obj.retention = m
-                    .get(&Yaml::String("retention".to_string()))
-                    .and_then(|v| v.as_i64())
-                    .map(|v| v as u16)
-                    .unwrap_or(self.retention);
+                group_obj.retention =
+                    get_int_field_from_map(m, "retention", &path, diag).unwrap_or(self.retention);
+                // Group refresh time or global if missing
+                group_obj.min_refresh_time =
+                    get_int_field_from_map(m, "min_refresh_time", &path, diag)
+                        .unwrap_or(self.min_refresh_time);
                 // Group media settings, inherit from app if missing
-                group_obj.media = m
-                    .get(&Yaml::String("media".to_string()))
-                    .and_then(|v| v.as_bool())
-                    .unwrap_or(self.media);
-                group_obj.media_max_size = m
-                    .get(&Yaml::String("media_max_size".to_string()))
-                    .and_then(|v| v.as_i64())
-                    .map(|v| v as u64)
+                group_obj.media =
+                    get_bool_field_from_map(m, "media", &path, diag).unwrap_or(self.media);
+                group_obj.media_max_size = get_int_field_from_map(m, "media_max_size", &path, diag)
                     .unwrap_or(self.media_max_size);
+                // Group content mode, inherit from app if missing
+                group_obj.content_mode =
+                    get_content_mode_from_map(m, &self.content_mode, &path, diag);
+                // Group HTTP settings, merged over the app ones
+                group_obj.http = get_http_settings_from_map(m, &self.http, &path, diag);
 
                 // Group enrichment templates: concatenate app-level + group's own value
                 group_obj.enrichment_prepend = concat_enrichment(
                     self.enrichment_prepend.as_deref(),
-                    m.get(&Yaml::String("enrichment_prepend".to_string()))
-                        .and_then(|v| v.as_str())
-                        .filter(|s| !s.is_empty()),
+                    get_template_from_map(m, "enrichment_prepend", &path, diag).as_deref(),
                 );
                 group_obj.enrichment_append = concat_enrichment(
                     self.enrichment_append.as_deref(),
-                    m.get(&Yaml::String("enrichment_append".to_string()))
-                        .and_then(|v| v.as_str())
-                        .filter(|s| !s.is_empty()),
+                    get_template_from_map(m, "enrichment_append", &path, diag).as_deref(),
                 );
 
                 // Load group filters
-                if let Some(filters) = m.get(&Yaml::String("filters".to_string())) {
-                    for f_val in filters.as_vec().unwrap_or(&vec![]) {
-                        if let Some(name) = f_val.as_str() {
-                            group_obj.filters.push(XxHash3_64::oneshot(name.as_bytes()));
-                        }
-                    }
-                }
+                group_obj.filters = get_filter_rules_from_map(m, &path, &declared, diag);
 
                 // Give group object for feeds that are inheriting it
-                group_obj.load_feeds(m);
+                group_obj.load_feeds(m, &path, &self.output, &mut declared, diag);
 
                 let group_code = XxHash3_64::oneshot(slugify(&group_obj.slug).as_bytes());
+                if let Some(other) = declared.groups.get(&group_code) {
+                    diag.error(
+                        &path,
+                        format!(
+                            "duplicate group slug '{}' (already used by {})",
+                            group_obj.slug, other
+                        ),
+                    );
+                    continue;
+                }
+                declared.groups.insert(group_code, path);
                 self.groups.insert(group_code, group_obj);
             }
             tracing::info!("Loaded groups: {}", self.groups.len());
@@ -283,79 +1232,129 @@ impl App {
 }
 
 impl Group {
-    fn load_feeds(&mut self, map: &LinkedHashMap<Yaml, Yaml>) {
-        if let Some(feeds) = map.get(&Yaml::String("feeds".to_string())) {
-            let provided = feeds.as_vec().expect("Invalid feeds");
+    fn load_feeds(
+        &mut self,
+        map: &LinkedHashMap<Yaml, Yaml>,
+        group_path: &str,
+        app_output: &str,
+        declared: &mut Declared,
+        diag: &mut Diagnostics,
+    ) {
+        if let Some(feeds) = get_field(map, "feeds") {
+            let Some(provided) = feeds.as_vec() else {
+                diag.error(&join_path(group_path, "feeds"), "expected a list");
+                return;
+            };
 
-            for f in provided.iter() {
-                let m = f.as_hash().expect("Invalid feed hash");
+            for (i, f) in provided.iter().enumerate() {
+                let path = format!("{}.feeds[{}]", group_path, i);
+                let Some(m) = f.as_hash() else {
+                    diag.error(&path, "expected a mapping");
+                    continue;
+                };
+                check_unknown_keys(m, FEED_KEYS, &path, diag);
+                let selector = get_string_field_from_map(m, "selector", false, &path, diag);
+                if diag.strict
+                    && !selector.is_empty()
+                    && let Err(e) = scraper::Selector::parse(&selector)
+                {
+                    diag.error(
+                        &join_path(&path, "selector"),
+                        format!("invalid CSS selector: {}", e),
+                    );
+                }
 
                 // --- Feed inheritance ---
                 let mut feed_obj = Feed {
-                    title: get_string_field_from_map(m, "title".to_string(), true, None),
-                    url: get_string_field_from_map(m, "url".to_string(), true, None),
+                    // optional, the title of the feed document is used instead
+                    title: get_string_field_from_map(m, "title", false, &path, diag),
+                    // optional with a `page_url`, the feed is then discovered on the page
+                    url: get_string_field_from_map(
+                        m,
+                        "url",
+                        get_field(m, "page_url").is_none(),
+                        &path,
+                        diag,
+                    ),
                     slug: String::new(), // will be computed later
-                    output: get_string_field_from_map(m, "output".to_string(), false, None),
-                    retention: m
-                        .get(&Yaml::String("retention".to_string()))
-                        .and_then(|v| v.as_i64())
-                        .map(|v| v as u16)
+                    output: Some(get_string_field_from_map(m, "output", false, &path, diag))
+                        .filter(|o| !o.is_empty()), // not inherited: the group already exports it
+                    retention: get_int_field_from_map(m, "retention", &path, diag)
                         .unwrap_or(self.retention), // inherited from group
+                    min_refresh_time: get_int_field_from_map(m, "min_refresh_time", &path, diag)
+                        .unwrap_or(self.min_refresh_time), // inherited from group
                     filters: self.filters.clone(), // starts with group filters
-                    content_mode: crate::model::ContentMode::Default,
-                    selector: Some(get_string_field_from_map(
-                        m,
-                        "selector".to_string(),
-                        false,
-                        None,
-                    )),
-                    page_url: String::new(),
-                    media: m
-                        .get(&Yaml::String("media".to_string()))
-                        .and_then(|v| v.as_bool())
-                        .unwrap_or(self.media), // inherited from group
-                    media_max_size: m
-                        .get(&Yaml::String("media_max_size".to_string()))
-                        .and_then(|v| v.as_i64())
-                        .map(|v| v as u64)
+                    content_mode: get_content_mode_from_map(m, &self.content_mode, &path, diag), // inherited from group
+                    selector: (!selector.is_empty()).then_some(selector),
+                    page_url: get_string_field_from_map(m, "page_url", false, &path, diag),
+                    media: get_bool_field_from_map(m, "media", &path, diag).unwrap_or(self.media), // inherited from group
+                    media_max_size: get_int_field_from_map(m, "media_max_size", &path, diag)
                         .unwrap_or(self.media_max_size), // inherited from group
                     enrichment_prepend: concat_enrichment(
                         self.enrichment_prepend.as_deref(),
-                        m.get(&Yaml::String("enrichment_prepend".to_string()))
-                            .and_then(|v| v.as_str())
-                            .filter(|s| !s.is_empty()),
+                        get_template_from_map(m, "enrichment_prepend", &path, diag).as_deref(),
                     ),
                     enrichment_append: concat_enrichment(
                         self.enrichment_append.as_deref(),
-                        m.get(&Yaml::String("enrichment_append".to_string()))
-                            .and_then(|v| v.as_str())
-                            .filter(|s| !s.is_empty()),
+                        get_template_from_map(m, "enrichment_append", &path, diag).as_deref(),
                     ),
+                    http: get_http_settings_from_map(m, &self.http, &path, diag), // merged over the group ones
                 };
 
-                // If feed does not have output, use the one from the group
-                // that may have taken it from global
-                if feed_obj.output.is_empty() {
-                    feed_obj.output = self.output.clone();
-                }
-
                 // Add feed filters to the one inherited from the group
-                if let Some(f_list) = m.get(&Yaml::String("filters".to_string())) {
-                    for f_val in f_list.as_vec().unwrap_or(&vec![]) {
-                        if let Some(name) = f_val.as_str() {
-                            let h = XxHash3_64::oneshot(name.as_bytes());
-                            if !feed_obj.filters.contains(&h) {
-                                feed_obj.filters.push(h);
-                            }
-                        }
+                for rule in get_filter_rules_from_map(m, &path, declared, diag) {
+                    if !feed_obj.filters.contains(&rule) {
+                        feed_obj.filters.push(rule);
                     }
                 }
 
-                // Compute slug and insertion
-                let parsed_url = url::Url::parse(&feed_obj.url).expect("Invalid URL");
-                feed_obj.slug = slugify(parsed_url.host_str().unwrap_or("no-host"));
+                // Compute slug and insertion, from the page URL of the feeds to discover
+                let (url_key, url) = if feed_obj.url.is_empty() {
+                    ("page_url", &feed_obj.page_url)
+                } else {
+                    ("url", &feed_obj.url)
+                };
+                if url.is_empty() {
+                    continue; // already reported as missing
+                }
+                let parsed_url = match url::Url::parse(url) {
+                    Ok(u) => u,
+                    Err(e) => {
+                        diag.error(&join_path(&path, url_key), format!("invalid URL: {}", e));
+                        continue;
+                    }
+                };
+                feed_obj.slug = feed_slug(
+                    &get_string_field_from_map(m, "slug", false, &path, diag),
+                    &parsed_url,
+                );
+
+                if diag.strict && url_key == "url" {
+                    if let Some(other) = declared.urls.get(parsed_url.as_str()) {
+                        diag.error(
+                            &join_path(&path, "url"),
+                            format!("duplicate feed URL (already used by {})", other),
+                        );
+                        continue;
+                    }
+                    declared.urls.insert(parsed_url.to_string(), path.clone());
+                }
 
                 let feed_code = XxHash3_64::oneshot(feed_obj.slug.as_bytes());
+                if let Some(other) = declared.feeds.get(&feed_code) {
+                    diag.error(
+                        &path,
+                        format!(
+                            "duplicate feed slug '{}' (already used by {})",
+                            feed_obj.slug, other
+                        ),
+                    );
+                    continue;
+                }
+                if let Some(output) = &feed_obj.output {
+                    declare_output(app_output, output, false, &path, declared, diag);
+                }
+                declared.feeds.insert(feed_code, path);
                 self.feeds.insert(feed_code, feed_obj);
             }
             tracing::info!("Loaded feeds: {} (group: {})", self.feeds.len(), self.slug);
@@ -363,41 +1362,85 @@ impl Group {
     }
 }
 
-pub(crate) fn load_config_file(config_file: String) -> App {
-    let result = std::fs::read_to_string(config_file);
-    if let Err(e) = result {
-        tracing::error!("Unable to open config file: {:?}", e);
-        std::process::exit(1);
-    }
-    let result = yaml_rust::YamlLoader::load_from_str(&result.unwrap());
-    if let Err(e) = result {
-        tracing::error!("Unable to parse config file: {:?}", e);
-        std::process::exit(1);
+/// Parse a YAML configuration, reporting every invalid value at once. In `strict` mode,
+/// the mistakes that would otherwise be ignored are reported as well.
+fn load_config_str(content: &str, strict: bool) -> Result<App, FrustError> {
+    let loader = yaml_rust::YamlLoader::load_from_str(content)
+        .map_err(|e| FrustError::Config(format!("Unable to parse config file: {}", e)))?;
+
+    let mut diag = Diagnostics {
+        strict,
+        ..Diagnostics::default()
+    };
+    let mut positions = PositionCollector::default();
+    if Parser::new(content.chars())
+        .load(&mut positions, false)
+        .is_ok()
+    {
+        diag.positions = positions.positions;
     }
-    let loader = result.unwrap();
+
     let mut app = App::default();
-    if let Some(map) = loader[0].as_hash() {
-        app.load_globals(map);
-        app.load_filters(map);
-        app.load_groups(map);
+    match loader.first().and_then(|doc| doc.as_hash()) {
+        Some(map) => {
+            app.load_globals(map, &mut diag);
+            app.load_filters(map, &mut diag);
+            app.load_groups(map, &mut diag);
+        }
+        None => diag.error("", "expected a mapping at the root of the config file"),
+    }
+
+    if diag.issues.is_empty() {
+        Ok(app)
+    } else {
+        Err(FrustError::InvalidConfig(diag.issues))
     }
-    app.clone()
+}
+
+pub(crate) fn load_config_file(config_file: String) -> Result<App, FrustError> {
+    let content = std::fs::read_to_string(&config_file).map_err(|e| {
+        FrustError::Config(format!("Unable to open config file {}: {}", config_file, e))
+    })?;
+    load_config_str(&content, false)
+}
+
+/// Load a config file like [`load_config_file`], also reporting unknown keys, undefined
+/// filters, invalid CSS selectors, duplicate feed URLs and colliding output files.
+pub(crate) fn check_config_file(config_file: &str) -> Result<App, FrustError> {
+    let content = std::fs::read_to_string(config_file).map_err(|e| {
+        FrustError::Config(format!("Unable to open config file {}: {}", config_file, e))
+    })?;
+    load_config_str(&content, true)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model::FeedState;
 
     /// Parses a YAML string into an `App` without touching the filesystem.
     fn app_from_yaml(yaml: &str) -> App {
-        let loader = yaml_rust::YamlLoader::load_from_str(yaml).expect("invalid yaml");
-        let mut app = App::default();
-        if let Some(map) = loader[0].as_hash() {
-            app.load_globals(map);
-            app.load_filters(map);
-            app.load_groups(map);
+        load_config_str(yaml, false).expect("invalid config")
+    }
+
+    /// Parses a YAML string that must be rejected and returns the reported issues.
+    fn issues_from_yaml(yaml: &str) -> Vec<String> {
+        issues_of(load_config_str(yaml, false))
+    }
+
+    /// Same as `issues_from_yaml`, with the checks of `frust check`.
+    fn check_issues_from_yaml(yaml: &str) -> Vec<String> {
+        issues_of(load_config_str(yaml, true))
+    }
+
+    fn issues_of(result: Result<App, FrustError>) -> Vec<String> {
+        match result {
+            Err(FrustError::InvalidConfig(issues)) => {
+                issues.iter().map(|i| i.to_string()).collect()
+            }
+            Err(e) => panic!("unexpected error: {}", e),
+            Ok(_) => panic!("config should be rejected"),
         }
-        app
     }
 
     /// Returns the first feed found in the first group of the app.
@@ -537,4 +1580,900 @@ groups:
         assert!(feed.enrichment_prepend.is_none());
         assert!(feed.enrichment_append.is_none());
     }
+
+    #[test]
+    fn test_content_mode_defaults_to_default() {
+        let app = app_from_yaml(&format!(
+            r#"
+groups:
+- slug: g
+  output: g.atom
+  feeds:
+  - title: F
+    url: {FEED_URL}
+"#
+        ));
+        assert_eq!(first_feed(&app).content_mode, ContentMode::Default);
+    }
+
+    #[test]
+    fn test_content_mode_inherited_from_app_and_group() {
+        let app = app_from_yaml(&format!(
+            r#"
+content_mode: brief
+groups:
+- slug: a
+  output: a.atom
+  feeds:
+  - title: A
+    url: {FEED_URL}
+- slug: b
+  output: b.atom
+  content_mode: force
+  feeds:
+  - title: B
+    url: https://other.example.org/feed.xml
+"#
+        ));
+        let modes: HashMap<&str, &ContentMode> = app
+            .groups
+            .values()
+            .flat_map(|g| {
+                g.feeds
+                    .values()
+                    .map(|f| (f.title.as_str(), &f.content_mode))
+            })
+            .collect();
+        assert_eq!(modes["A"], &ContentMode::Brief);
+        assert_eq!(modes["B"], &ContentMode::Force);
+    }
+
+    #[test]
+    fn test_content_mode_feed_overrides_group() {
+        let app = app_from_yaml(&format!(
+            r#"
+groups:
+- slug: g
+  output: g.atom
+  content_mode: no
+  feeds:
+  - title: F
+    url: {FEED_URL}
+    content_mode: links_only
+"#
+        ));
+        assert_eq!(first_feed(&app).content_mode, ContentMode::LinksOnly);
+    }
+
+    #[test]
+    fn test_content_mode_unknown_value_rejected() {
+        let issues = issues_from_yaml(&format!(
+            r#"
+groups:
+- slug: g
+  output: g.atom
+  feeds:
+  - title: F
+    url: {FEED_URL}
+    content_mode: full
+"#
+        ));
+        assert_eq!(issues.len(), 1);
+        assert!(
+            issues[0].starts_with(
+                "groups[0].feeds[0].content_mode (line 8, column 5): unknown content mode 'full'"
+            ),
+            "{}",
+            issues[0]
+        );
+    }
+
+    #[test]
+    fn test_empty_selector_is_none() {
+        let app = app_from_yaml(&format!(
+            r#"
+groups:
+- slug: g
+  output: g.atom
+  feeds:
+  - title: F
+    url: {FEED_URL}
+"#
+        ));
+        assert!(first_feed(&app).selector.is_none());
+    }
+
+    #[test]
+    fn test_feed_output_only_when_declared() {
+        let app = app_from_yaml(&format!(
+            r#"
+groups:
+- slug: g
+  output: g.atom
+  feeds:
+  - title: A
+    url: {FEED_URL}
+    output: a.atom
+  - title: B
+    url: https://other.example.org/feed.xml
+"#
+        ));
+        let outputs: HashMap<&str, Option<&str>> = app
+            .groups
+            .values()
+            .next()
+            .unwrap()
+            .feeds
+            .values()
+            .map(|f| (f.title.as_str(), f.output.as_deref()))
+            .collect();
+        assert_eq!(outputs["A"], Some("a.atom"));
+        assert_eq!(outputs["B"], None);
+    }
+
+    #[test]
+    fn test_feeds_on_same_host_get_distinct_slugs() {
+        let app = app_from_yaml(
+            r#"
+groups:
+- slug: g
+  output: g.atom
+  feeds:
+  - title: A
+    url: https://www.youtube.com/feeds/videos.xml?channel_id=A
+  - title: B
+    url: https://www.youtube.com/feeds/videos.xml?channel_id=B
+  - title: C
+    url: https://www.youtube.com/feeds/videos.xml?channel_id=C
+"#,
+        );
+        let group = app.groups.values().next().unwrap();
+        assert_eq!(group.feeds.len(), 3);
+        for feed in group.feeds.values() {
+            assert!(feed.slug.starts_with("www-youtube-com-"), "{}", feed.slug);
+        }
+    }
+
+    #[test]
+    fn test_explicit_feed_slug() {
+        let app = app_from_yaml(&format!(
+            r#"
+groups:
+- slug: g
+  output: g.atom
+  feeds:
+  - title: F
+    slug: My Feed
+    url: {FEED_URL}
+"#
+        ));
+        let group = app.groups.values().next().unwrap();
+        let feed = first_feed(&app);
+        assert_eq!(feed.slug, "my-feed");
+        assert!(group.feeds.contains_key(&XxHash3_64::oneshot(b"my-feed")));
+    }
+
+    #[test]
+    fn test_duplicate_feed_slug_in_group_rejected() {
+        let issues = issues_from_yaml(
+            r#"
+groups:
+- slug: g
+  output: g.atom
+  feeds:
+  - title: A
+    slug: dup
+    url: https://a.example.com/feed
+  - title: B
+    slug: dup
+    url: https://b.example.com/feed
+"#,
+        );
+        assert_eq!(
+            issues,
+            vec![
+                "groups[0].feeds[1] (line 9, column 5): duplicate feed slug 'dup' (already used by groups[0].feeds[0])"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_group_slug_rejected() {
+        let issues = issues_from_yaml(
+            r#"
+groups:
+- slug: music
+  output: a.atom
+  feeds: []
+- slug: Music
+  output: b.atom
+  feeds: []
+"#,
+        );
+        assert_eq!(
+            issues,
+            vec![
+                "groups[1] (line 6, column 3): duplicate group slug 'Music' (already used by groups[0])"
+            ]
+        );
+    }
+
+    #[test]
+    fn test_duplicate_feed_url_across_groups_rejected() {
+        let issues = issues_from_yaml(&format!(
+            r#"
+groups:
+- slug: a
+  output: a.atom
+  feeds:
+  - title: A
+    url: {FEED_URL}
+- slug: b
+  output: b.atom
+  feeds:
+  - title: B
+    url: {FEED_URL}
+"#
+        ));
+        assert_eq!(issues.len(), 1);
+        assert!(issues[0].starts_with("groups[1].feeds[0]"), "{}", issues[0]);
+        assert!(issues[0].contains("already used by groups[0].feeds[0]"));
+    }
+
+    #[test]
+    fn test_legacy_feed_ids_grouped_by_host() {
+        let app = app_from_yaml(
+            r#"
+groups:
+- slug: g
+  output: g.atom
+  feeds:
+  - title: A
+    url: https://www.youtube.com/feeds/videos.xml?channel_id=A
+  - title: B
+    url: https://www.youtube.com/feeds/videos.xml?channel_id=B
+  - title: C
+    url: https://linuxfr.org/news.atom
+"#,
+        );
+        let legacy = legacy_feed_ids(&app);
+        assert_eq!(legacy[&XxHash3_64::oneshot(b"www-youtube-com")].len(), 2);
+        assert_eq!(legacy[&XxHash3_64::oneshot(b"linuxfr-org")].len(), 1);
+    }
+
+    #[test]
+    fn test_all_issues_reported_with_positions() {
+        let issues = issues_from_yaml(
+            r#"
+workers: many
+filters:
+- slug: broken
+  expressions: ["(unclosed"]
+  is_regex: true
+groups:
+- slug: g
+  output: g.atom
+  media: maybe
+  feeds:
+  - title: Missing URL
+  - title: Bad URL
+    url: not a url
+"#,
+        );
+        assert_eq!(
+            issues,
+            vec![
+                "workers (line 2, column 1): expected an integer".to_string(),
+                "filters[0].expressions (line 5, column 3): invalid regex: regex parse error:\n    (unclosed\n    ^\nerror: unclosed group".to_string(),
+                "groups[0].media (line 10, column 3): expected a boolean (true or false)".to_string(),
+                "groups[0].feeds[0].url (line 12, column 5): field is missing".to_string(),
+                "groups[0].feeds[1].url (line 14, column 5): invalid URL: relative URL without a base".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_global_retention_inherited() {
+        let app = app_from_yaml(&format!(
+            r#"
+retention: 30
+groups:
+- slug: g
+  output: g.atom
+  feeds:
+  - title: F
+    url: {FEED_URL}
+"#
+        ));
+        assert_eq!(first_feed(&app).retention, 30);
+    }
+
+    #[test]
+    fn test_feed_with_page_url_only() {
+        let app = app_from_yaml(
+            r#"
+groups:
+- slug: g
+  output: g.atom
+  feeds:
+  - title: Blog
+    page_url: https://blog.example.org/
+"#,
+        );
+        let feed = app
+            .groups
+            .values()
+            .next()
+            .unwrap()
+            .feeds
+            .values()
+            .next()
+            .unwrap();
+        assert!(feed.url.is_empty());
+        assert_eq!(feed.page_url, "https://blog.example.org/");
+        assert!(feed.slug.starts_with("blog-example-org-"));
+    }
+
+    #[test]
+    fn test_feeds_completed_from_their_state() {
+        let mut app = app_from_yaml(&format!(
+            r#"
+groups:
+- slug: g
+  output: g.atom
+  feeds:
+  - url: {FEED_URL}
+  - title: Discovered
+    page_url: https://blog.example.org/
+"#
+        ));
+        let ids: HashMap<String, u64> = app
+            .groups
+            .values()
+            .next()
+            .unwrap()
+            .feeds
+            .iter()
+            .map(|(id, f)| (f.url.clone(), *id))
+            .collect();
+        let learnt = FeedState {
+            title: Some("Learnt title".to_string()),
+            page_url: Some("https://example.com/".to_string()),
+            discovered_url: Some("https://blog.example.org/feed".to_string()),
+            ..FeedState::default()
+        };
+        let states = HashMap::from([(ids[FEED_URL], learnt.clone()), (ids[""], learnt)]);
+        app.complete_feeds(&states);
+
+        let feeds = &app.groups.values().next().unwrap().feeds;
+        let plain = &feeds[&ids[FEED_URL]];
+        assert_eq!(plain.name(), "Learnt title");
+        assert_eq!(plain.url, FEED_URL);
+        assert_eq!(plain.page_url, "https://example.com/");
+        let discovered = &feeds[&ids[""]];
+        assert_eq!(discovered.name(), "Discovered");
+        assert_eq!(discovered.url, "https://blog.example.org/feed");
+        assert_eq!(discovered.page_url, "https://blog.example.org/");
+    }
+
+    #[test]
+    fn test_group_channel_metadata() {
+        let app = app_from_yaml(&format!(
+            r#"
+groups:
+- title: Nouvelles
+  slug: news
+  output: news.atom
+  description: Les nouvelles du jour
+  language: fr
+  icon: https://example.com/icon.png
+  author: Jane
+  site_url: https://example.com/
+  feeds:
+  - title: F
+    url: {FEED_URL}
+"#
+        ));
+        let channel = app.groups.values().next().unwrap().channel("/news");
+        assert_eq!(channel.title, "Nouvelles");
+        assert_eq!(channel.link, "/news");
+        assert_eq!(
+            channel.description.as_deref(),
+            Some("Les nouvelles du jour")
+        );
+        assert_eq!(channel.language.as_deref(), Some("fr"));
+        assert_eq!(
+            channel.icon.as_deref(),
+            Some("https://example.com/icon.png")
+        );
+        assert_eq!(channel.author.as_deref(), Some("Jane"));
+        assert_eq!(channel.home_page(), "https://example.com/");
+
+        let issues = issues_from_yaml(
+            r#"
+groups:
+- slug: news
+  output: news.atom
+  site_url: example.com
+  feeds: []
+"#,
+        );
+        assert_eq!(
+            issues,
+            vec![
+                "groups[0].site_url (line 5, column 3): invalid URL: relative URL without a base"
+                    .to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_group_strategy() {
+        let app = app_from_yaml(&format!(
+            r#"
+groups:
+- slug: journal
+  output: journal
+  strategy: daily
+  format: epub
+  feeds:
+  - url: {FEED_URL}
+- slug: news
+  output: news.atom
+  feeds: []
+"#
+        ));
+        let group = |slug: &str| app.groups.values().find(|g| g.slug == slug).unwrap();
+        assert_eq!(group("journal").strategy, ExportStrategy::Daily);
+        assert_eq!(group("journal").format.as_deref(), Some("epub"));
+        assert_eq!(group("news").strategy, ExportStrategy::Monolithic);
+        assert_eq!(group("news").format, None);
+
+        let issues = issues_from_yaml(
+            r#"
+groups:
+- slug: journal
+  output: journal
+  strategy: yearly
+  format: pdf
+  feeds: []
+- slug: news
+  output: news
+  strategy: monthly
+  feeds: []
+"#,
+        );
+        assert_eq!(
+            issues,
+            vec![
+                "groups[0].strategy (line 5, column 3): unknown strategy 'yearly' (expected one of: monolithic, individual, daily, weekly, monthly)".to_string(),
+                "groups[0].format (line 6, column 3): unknown format 'pdf' (expected one of: rss, xml, atom, json, md, epub)".to_string(),
+                "groups[1].format (line 8, column 3): field is missing, `output` is a directory with this strategy".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_base_url() {
+        let app = app_from_yaml(
+            r#"
+output: /var/www/rss
+base_url: https://rss.example.org/
+groups: []
+"#,
+        );
+        assert_eq!(app.base_url.as_deref(), Some("https://rss.example.org"));
+        assert_eq!(
+            app.public_url("news.atom").as_deref(),
+            Some("https://rss.example.org/news.atom")
+        );
+        assert_eq!(
+            app.public_url("./feeds/news.xml").as_deref(),
+            Some("https://rss.example.org/feeds/news.xml")
+        );
+        assert_eq!(
+            app.public_url("/var/www/rss/news.json").as_deref(),
+            Some("https://rss.example.org/news.json")
+        );
+        // Not served under the base URL
+        assert_eq!(app.public_url("/srv/news.atom"), None);
+        assert_eq!(app.public_url("../site/news.atom"), None);
+        assert_eq!(app.public_url("/var/www/rss/../site/news.atom"), None);
+
+        let app = app_from_yaml("groups: []");
+        assert_eq!(app.public_url("news.atom"), None);
+
+        let issues = issues_from_yaml("base_url: rss.example.org\ngroups: []");
+        assert_eq!(
+            issues,
+            vec![
+                "base_url (line 1, column 1): invalid URL: relative URL without a base".to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_min_refresh_time_inherited() {
+        let app = app_from_yaml(&format!(
+            r#"
+min_refresh_time: 1800
+groups:
+- slug: g
+  output: g.atom
+  feeds:
+  - title: F
+    url: {FEED_URL}
+- slug: h
+  output: h.atom
+  min_refresh_time: 3600
+  feeds:
+  - title: G
+    url: {FEED_URL}
+    slug: g2
+  - title: H
+    url: {FEED_URL}
+    slug: h2
+    min_refresh_time: 900
+"#
+        ));
+        assert_eq!(app.min_refresh_time, 1800);
+        let feeds: HashMap<&str, i64> = app
+            .groups
+            .values()
+            .flat_map(|g| g.feeds.values())
+            .map(|f| (f.slug.as_str(), f.min_refresh_time))
+            .collect();
+        assert_eq!(feeds["g2"], 3600);
+        assert_eq!(feeds["h2"], 900);
+        assert_eq!(
+            app.groups
+                .values()
+                .find(|g| g.slug == "g")
+                .unwrap()
+                .feeds
+                .values()
+                .next()
+                .unwrap()
+                .min_refresh_time,
+            1800
+        );
+    }
+
+    #[test]
+    fn test_http_settings_merged_from_app_to_feed() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let token_file = dir.path().join("token");
+        std::fs::write(&token_file, "s3cr3t\n").unwrap();
+        let app = app_from_yaml(&format!(
+            r#"
+http:
+  user_agent: "Mozilla/5.0"
+  headers: {{ Accept-Language: fr, X-Api: app }}
+  proxy: socks5h://127.0.0.1:1080
+groups:
+- slug: g
+  output: g.atom
+  http:
+    headers: {{ x-api: group }}
+    cookies: {{ consent: "yes" }}
+  feeds:
+  - title: F
+    url: {FEED_URL}
+    http:
+      auth:
+        bearer: {{ file: {} }}
+      cookies: {{ session: {{ env: PATH }} }}
+"#,
+            token_file.display()
+        ));
+        assert_eq!(
+            first_feed(&app).http,
+            HttpSettings {
+                user_agent: Some("Mozilla/5.0".to_string()),
+                headers: vec![
+                    ("Accept-Language".to_string(), "fr".to_string()),
+                    ("X-Api".to_string(), "group".to_string()),
+                ],
+                auth: Some(HttpAuth::Bearer("s3cr3t".to_string())),
+                proxy: Some("socks5h://127.0.0.1:1080".to_string()),
+                cookies: vec![
+                    ("consent".to_string(), "yes".to_string()),
+                    ("session".to_string(), std::env::var("PATH").unwrap()),
+                ],
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_http_settings_are_reported() {
+        let issues = issues_from_yaml(&format!(
+            r#"
+http:
+  proxy: "not a proxy"
+  headers: {{ "bad header": x }}
+groups:
+- slug: g
+  output: g.atom
+  feeds:
+  - title: F
+    url: {FEED_URL}
+    http:
+      auth:
+        basic: {{ username: me, password: {{ env: FRUST_NO_SUCH_VARIABLE }} }}
+"#
+        ));
+        assert_eq!(
+            issues,
+            vec![
+                "http.headers.bad header (line 4, column 14): invalid header name 'bad header'".to_string(),
+                "http.proxy (line 3, column 3): invalid proxy URL: builder error".to_string(),
+                "groups[0].feeds[0].http.auth.basic.password.env (line 13, column 44): environment variable 'FRUST_NO_SUCH_VARIABLE' is not set".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_yaml_is_an_error() {
+        assert!(matches!(
+            load_config_str("groups: [\n", false),
+            Err(FrustError::Config(_))
+        ));
+    }
+
+    #[test]
+    fn test_missing_config_file_is_an_error() {
+        assert!(load_config_file("/tmp/frust_no_such_config.yaml".to_string()).is_err());
+    }
+
+    #[test]
+    fn test_check_reports_ignored_mistakes() {
+        let yaml = format!(
+            r#"
+output: out
+filters:
+- slug: known
+  expressions: ["a"]
+  filter_in_titel: true
+groups:
+- slug: a
+  output: same.atom
+  filters: [known, missing]
+  feeds:
+  - title: F
+    url: {FEED_URL}
+    selector: "div["
+- slug: b
+  output: same.atom
+  feeds:
+  - title: G
+    url: {FEED_URL}
+    slug: other
+"#
+        );
+        // Without the checks the config loads fine
+        app_from_yaml(&yaml);
+        assert_eq!(
+            check_issues_from_yaml(&yaml),
+            vec![
+                "filters[0].filter_in_titel (line 6, column 3): unknown key 'filter_in_titel'"
+                    .to_string(),
+                "groups[0].filters[1] (line 10, column 20): filter 'missing' is not defined"
+                    .to_string(),
+                "groups[0].feeds[0].selector (line 14, column 5): invalid CSS selector: Unexpected EOL".to_string(),
+                "groups[1].output (line 16, column 3): output 'out/same.atom' is already written by groups[0]".to_string(),
+                "groups[1].feeds[0].url (line 19, column 5): duplicate feed URL (already used by groups[0].feeds[0])".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_reports_outputs_inside_split_group_directory() {
+        let yaml = format!(
+            r#"
+output: out
+groups:
+- slug: a
+  output: a/inside.atom
+  feeds: []
+- slug: journal
+  output: a
+  strategy: daily
+  format: atom
+  feeds:
+  - title: F
+    url: {FEED_URL}
+    output: a/f.rss
+"#
+        );
+        assert_eq!(
+            check_issues_from_yaml(&yaml),
+            vec![
+                "groups[1].output (line 8, column 3): output 'out/a' and the output 'out/a/inside.atom' of groups[0] are nested, the files of a split group must be alone in their directory".to_string(),
+                "groups[1].feeds[0].output (line 14, column 5): output 'out/a/f.rss' and the output 'out/a' of groups[1] are nested, the files of a split group must be alone in their directory".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sample_config_only_reports_duplicate_url() {
+        let yaml = std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/my-config.yaml"))
+            .unwrap();
+        load_config_str(&yaml, false).expect("the sample config should load");
+        // The sample subscribes to a same channel in two groups, which only `frust check` reports
+        let issues = issues_of(load_config_str(&yaml, true));
+        assert_eq!(
+            issues,
+            vec![
+                "groups[1].feeds[1].url (line 81, column 5): duplicate feed URL (already used by groups[0].feeds[0])"
+                    .to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filter_keep_and_is_include_alias() {
+        let app = app_from_yaml(
+            r#"
+filters:
+- slug: exclude
+  expressions: ["a"]
+- slug: keep
+  expressions: ["b"]
+  keep: true
+- slug: include
+  expressions: ["c"]
+  is_include: true
+"#,
+        );
+        let keep = |slug: &str| app.filters[&XxHash3_64::oneshot(slug.as_bytes())].keep;
+        assert!(!keep("exclude"));
+        assert!(keep("keep"));
+        assert!(keep("include"));
+    }
+
+    #[test]
+    fn test_filter_keep_conflicting_with_is_include_rejected() {
+        assert_eq!(
+            issues_from_yaml(
+                r#"
+filters:
+- slug: f
+  expressions: ["a"]
+  keep: true
+  is_include: false
+"#
+            ),
+            vec![
+                "filters[0].is_include (line 6, column 3): conflicts with `keep`, use only one of them"
+                    .to_string()
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filter_expressions_at_group_and_feed_level() {
+        let app = app_from_yaml(&format!(
+            r#"
+filters:
+- slug: metal
+  expressions: ["metal"]
+- slug: sponsored
+  expressions: ["sponsored"]
+groups:
+- slug: g
+  output: g.atom
+  filters:
+  - keep: {{ all: [metal, {{ not: sponsored }}] }}
+  feeds:
+  - title: F
+    url: {FEED_URL}
+    filters:
+    - sponsored
+    - exclude: {{ any: [sponsored, metal] }}
+"#
+        ));
+        let id = |slug: &str| FilterExpr::Filter(XxHash3_64::oneshot(slug.as_bytes()));
+        assert_eq!(
+            first_feed(&app).filters,
+            vec![
+                FilterRule::Keep(FilterExpr::All(vec![
+                    id("metal"),
+                    FilterExpr::Not(Box::new(id("sponsored")))
+                ])),
+                FilterRule::Named(XxHash3_64::oneshot(b"sponsored")),
+                FilterRule::Exclude(FilterExpr::Any(vec![id("sponsored"), id("metal")])),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_invalid_filter_expressions_rejected() {
+        assert_eq!(
+            issues_from_yaml(&format!(
+                r#"
+filters:
+- slug: metal
+  expressions: ["metal"]
+groups:
+- slug: g
+  output: g.atom
+  feeds:
+  - title: F
+    url: {FEED_URL}
+    filters:
+    - keep: {{ any: [metal, missing] }}
+    - exclude: {{ none: [metal] }}
+    - keep: {{ all: [] }}
+    - {{ any: [metal] }}
+"#
+            )),
+            vec![
+                "groups[0].feeds[0].filters[0].keep.any[1] (line 12, column 28): filter 'missing' is not defined".to_string(),
+                "groups[0].feeds[0].filters[1].exclude.none (line 13, column 18): unknown filter operator 'none' (expected any, all or not)".to_string(),
+                "groups[0].feeds[0].filters[2].keep.all (line 14, column 15): expected a non-empty list of filter expressions".to_string(),
+                "groups[0].feeds[0].filters[3] (line 15, column 9): expected a filter slug, or a mapping with a single `keep` or `exclude` key".to_string(),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filter_predicates_loaded() {
+        let app = app_from_yaml(
+            r#"
+filters:
+- slug: old-sponsored
+  must_match_all: true
+  predicates:
+  - { field: category, op: not_in, value: [News, Rust] }
+  - { field: published, op: less_than, value: 2024-01-31 }
+  - { field: age, op: greater_than, value: 30 }
+"#,
+        );
+        let filter = &app.filters[&XxHash3_64::oneshot(b"old-sponsored")];
+        assert!(filter.expressions.is_empty());
+        assert_eq!(filter.predicates.len(), 3);
+        let p = &filter.predicates[0];
+        assert_eq!(p.field, PredicateField::Category);
+        assert!(p.negate);
+        assert!(matches!(&p.op, PredicateOp::In(v) if v == &["news", "rust"]));
+        assert!(matches!(
+            filter.predicates[1].op,
+            PredicateOp::LessThan(1_706_659_200)
+        ));
+        assert!(matches!(
+            filter.predicates[2].op,
+            PredicateOp::GreaterThan(30)
+        ));
+    }
+
+    #[test]
+    fn test_invalid_filter_predicates_rejected() {
+        assert_eq!(
+            issues_from_yaml(
+                r#"
+filters:
+- slug: f
+  predicates:
+  - { field: colour, op: equals, value: red }
+  - { field: age, op: starts_with, value: 1 }
+  - { field: author, op: greater_than, value: 1 }
+  - { field: author, op: like, value: x }
+  - { field: age, op: less_than, value: soon }
+  - { field: published, op: less_than, value: yesterday }
+  - { field: author, op: regex, value: "(" }
+  - { field: author, op: equals }
+"#
+            ),
+            vec![
+                "filters[0].predicates[0].field (line 5, column 7): unknown predicate field 'colour' (expected one of: author, category, link_domain, enclosure_type, age, content_length, published)".to_string(),
+                "filters[0].predicates[1].op (line 6, column 19): operator 'starts_with' cannot be used on numeric field 'age'".to_string(),
+                "filters[0].predicates[2].op (line 7, column 22): operator 'greater_than' cannot be used on text field 'author'".to_string(),
+                "filters[0].predicates[3].op (line 8, column 22): unknown operator 'like' (expected one of: equals, not_equals, in, not_in, starts_with, not_starts_with, regex, greater_than, less_than)".to_string(),
+                "filters[0].predicates[4].value (line 9, column 34): expected an integer".to_string(),
+                "filters[0].predicates[5].value (line 10, column 40): expected a date (YYYY-MM-DD or RFC 3339)".to_string(),
+                "filters[0].predicates[6].value (line 11, column 33): invalid regex: regex parse error:\n    (\n    ^\nerror: unclosed group".to_string(),
+                "filters[0].predicates[7].value (line 12, column 7): field is missing".to_string(),
+            ]
+        );
+    }
 }
@@ -1,11 +1,49 @@
+use std::fmt;
+
 use thiserror::Error;
 
+/// A single problem found in the YAML config.
+#[derive(Debug)]
+pub(crate) struct ConfigIssue {
+    /// Path of the offending value, e.g. `groups[2].feeds[0].url`
+    pub(crate) path: String,
+    /// 1-based line and column in the config file, when known
+    pub(crate) position: Option<(usize, usize)>,
+    pub(crate) message: String,
+}
+
+impl fmt::Display for ConfigIssue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.path.is_empty() {
+            "<root>"
+        } else {
+            &self.path
+        };
+        match self.position {
+            Some((line, col)) => write!(
+                f,
+                "{} (line {}, column {}): {}",
+                path, line, col, self.message
+            ),
+            None => write!(f, "{}: {}", path, self.message),
+        }
+    }
+}
+
+fn format_issues(issues: &[ConfigIssue]) -> String {
+    issues.iter().map(|i| format!("\n  - {}", i)).collect()
+}
+
 #[derive(Debug, Error)]
 pub(crate) enum FrustError {
-    /// YAML config is missing a required field or contains an invalid value.
+    /// YAML config cannot be read or a command is missing an argument.
     #[error("Config error: {0}")]
     Config(String),
 
+    /// YAML config is missing required fields or contains invalid values, all reported at once.
+    #[error("Invalid config file ({} problem(s)):{}", .0.len(), format_issues(.0))]
+    InvalidConfig(Vec<ConfigIssue>),
+
     /// Filesystem operation failed (create dir, read dir, remove file…).
     #[error("I/O error: {0}")]
     Io(#[from] std::io::Error),
@@ -14,6 +52,10 @@ pub(crate) enum FrustError {
     #[error("HTTP error: {0}")]
     Http(#[from] reqwest::Error),
 
+    /// The server answered with an HTTP error status (4xx or 5xx).
+    #[error("HTTP status {0}")]
+    HttpStatus(u16),
+
     /// redb: opening / creating the database file.
     #[error("Database error: {0}")]
     DbOpen(#[from] redb::DatabaseError),
@@ -38,6 +80,10 @@ pub(crate) enum FrustError {
     #[error("Serialization error: {0}")]
     Serialization(String),
 
+    /// No feed could be found for a web page.
+    #[error("Feed discovery error: {0}")]
+    Discovery(String),
+
     /// feed-rs XML / Atom parsing failed.
     #[error("Feed parse error: {0}")]
     FeedParse(String),
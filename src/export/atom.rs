@@ -9,10 +9,10 @@ use tracing::info;
 
 use crate::{
     error::FrustError,
-    model::{Article, Enrichment},
+    model::{Article, Channel, Enrichment},
 };
 
-use super::{Exporter, render_template};
+use super::{Exporter, enrich, markdown_to_html};
 
 pub(crate) struct AtomExporter;
 
@@ -20,8 +20,7 @@ impl Exporter for AtomExporter {
     fn generate(
         &self,
         articles: &[Article],
-        title: &str,
-        link: &str,
+        channel: &Channel,
         destination: &Path,
         enrichments: &HashMap<u64, Enrichment>,
     ) -> Result<(), FrustError> {
@@ -31,7 +30,7 @@ impl Exporter for AtomExporter {
         }
         let file = fs::File::create(destination)?;
         let mut writer = Writer::new_with_indent(BufWriter::new(file), b' ', 2);
-        write_atom_to(&mut writer, articles, title, link, enrichments)
+        write_atom_to(&mut writer, articles, channel, enrichments)
     }
 }
 
@@ -42,8 +41,7 @@ impl Exporter for AtomExporter {
 pub(crate) fn write_atom_to<W: std::io::Write>(
     writer: &mut Writer<W>,
     articles: &[Article],
-    title: &str,
-    link: &str,
+    channel: &Channel,
     enrichments: &HashMap<u64, Enrichment>,
 ) -> Result<(), FrustError> {
     writer
@@ -53,23 +51,53 @@ pub(crate) fn write_atom_to<W: std::io::Write>(
     // <feed xmlns="http://www.w3.org/2005/Atom">
     let mut feed_tag = BytesStart::new("feed");
     feed_tag.push_attribute(("xmlns", "http://www.w3.org/2005/Atom"));
+    if let Some(language) = &channel.language {
+        feed_tag.push_attribute(("xml:lang", language.as_str()));
+    }
     writer
         .write_event(Event::Start(feed_tag))
         .map_err(|e| FrustError::Export(e.to_string()))?;
 
-    write_text_element(writer, "title", title)?;
-    write_text_element(writer, "id", link)?;
+    write_text_element(writer, "title", &channel.title)?;
+    if let Some(description) = &channel.description {
+        write_text_element(writer, "subtitle", description)?;
+    }
+    write_text_element(writer, "id", &channel.link)?;
 
     // <link rel="alternate" href="..."/>
     {
         let mut link_tag = BytesStart::new("link");
         link_tag.push_attribute(("rel", "alternate"));
-        link_tag.push_attribute(("href", link));
+        link_tag.push_attribute(("href", channel.home_page()));
+        writer
+            .write_event(Event::Empty(link_tag))
+            .map_err(|e| FrustError::Export(e.to_string()))?;
+    }
+    // <link rel="self" href="..."/>
+    if let Some(self_url) = &channel.self_url {
+        let mut link_tag = BytesStart::new("link");
+        link_tag.push_attribute(("rel", "self"));
+        link_tag.push_attribute(("type", "application/atom+xml"));
+        link_tag.push_attribute(("href", self_url.as_str()));
         writer
             .write_event(Event::Empty(link_tag))
             .map_err(|e| FrustError::Export(e.to_string()))?;
     }
 
+    if let Some(author) = &channel.author {
+        writer
+            .write_event(Event::Start(BytesStart::new("author")))
+            .map_err(|e| FrustError::Export(e.to_string()))?;
+        write_text_element(writer, "name", author)?;
+        writer
+            .write_event(Event::End(BytesEnd::new("author")))
+            .map_err(|e| FrustError::Export(e.to_string()))?;
+    }
+    if let Some(icon) = &channel.icon {
+        write_text_element(writer, "icon", icon)?;
+        write_text_element(writer, "logo", icon)?;
+    }
+
     // <updated> — most recent article timestamp, or now
     let updated = articles
         .iter()
@@ -146,33 +174,16 @@ fn write_entry<W: std::io::Write>(
         write_text_element(writer, "summary", summary)?;
     }
 
-    // <content type="text"> — full markdown content, optionally enriched
-    let enriched;
-    let content: &str = if let Some(e) = enrichment {
-        let pre = e
-            .prepend
-            .as_deref()
-            .map(|t| render_template(t, e, article))
-            .unwrap_or_default();
-        let app = e
-            .append
-            .as_deref()
-            .map(|t| render_template(t, e, article))
-            .unwrap_or_default();
-        enriched = format!("{pre}{}{app}", article.content);
-        &enriched
-    } else {
-        &article.content
-    };
-
+    // <content type="html"> — full markdown content rendered to HTML, optionally enriched
+    let content = enrich(&article.content, enrichment, article);
     if !content.is_empty() {
         let mut content_tag = BytesStart::new("content");
-        content_tag.push_attribute(("type", "text"));
+        content_tag.push_attribute(("type", "html"));
         writer
             .write_event(Event::Start(content_tag))
             .map_err(|e| FrustError::Export(e.to_string()))?;
         writer
-            .write_event(Event::Text(BytesText::new(content)))
+            .write_event(Event::Text(BytesText::new(&markdown_to_html(&content))))
             .map_err(|e| FrustError::Export(e.to_string()))?;
         writer
             .write_event(Event::End(BytesEnd::new("content")))
@@ -242,8 +253,7 @@ mod tests {
         AtomExporter
             .generate(
                 &[],
-                "Empty Feed",
-                "https://example.com",
+                &Channel::new("Empty Feed", "https://example.com"),
                 &dest,
                 &no_enrichment(),
             )
@@ -263,8 +273,7 @@ mod tests {
         AtomExporter
             .generate(
                 &articles,
-                "My Feed",
-                "https://example.com",
+                &Channel::new("My Feed", "https://example.com"),
                 &dest,
                 &no_enrichment(),
             )
@@ -275,12 +284,67 @@ mod tests {
         assert!(xml.contains("href=\"https://example.com/1\""));
     }
 
+    #[test]
+    fn test_atom_channel_metadata() {
+        let dir = TempDir::new().unwrap();
+        let dest = output_path(&dir, "feed.atom");
+        let channel = Channel {
+            description: Some("Nouvelles".to_string()),
+            language: Some("fr".to_string()),
+            icon: Some("https://example.com/icon.png".to_string()),
+            author: Some("Jane".to_string()),
+            site_url: Some("https://example.com/".to_string()),
+            ..Channel::new("News", "/news")
+        };
+        AtomExporter
+            .generate(&[], &channel, &dest, &no_enrichment())
+            .unwrap();
+        let xml = read_xml(&dest);
+        assert!(xml.contains("xml:lang=\"fr\""));
+        assert!(xml.contains("<subtitle>Nouvelles</subtitle>"));
+        assert!(xml.contains("<id>/news</id>"));
+        assert!(xml.contains("href=\"https://example.com/\""));
+        assert!(xml.contains("<name>Jane</name>"));
+        assert!(xml.contains("<icon>https://example.com/icon.png</icon>"));
+        assert!(xml.contains("<logo>https://example.com/icon.png</logo>"));
+    }
+
+    #[test]
+    fn test_atom_self_link() {
+        let dir = TempDir::new().unwrap();
+        let dest = output_path(&dir, "feed.atom");
+        let channel = Channel {
+            site_url: Some("https://example.com/".to_string()),
+            self_url: Some("https://rss.example.org/news.atom".to_string()),
+            ..Channel::new("News", "https://rss.example.org/news.atom")
+        };
+        AtomExporter
+            .generate(&[], &channel, &dest, &no_enrichment())
+            .unwrap();
+        let xml = read_xml(&dest);
+        assert!(xml.contains("<id>https://rss.example.org/news.atom</id>"));
+        assert!(xml.contains("<link rel=\"alternate\" href=\"https://example.com/\"/>"));
+        assert!(xml.contains(
+            "<link rel=\"self\" type=\"application/atom+xml\" href=\"https://rss.example.org/news.atom\"/>"
+        ));
+
+        AtomExporter
+            .generate(&[], &Channel::new("News", "/news"), &dest, &no_enrichment())
+            .unwrap();
+        assert!(!read_xml(&dest).contains("rel=\"self\""));
+    }
+
     #[test]
     fn test_atom_creates_parent_dirs() {
         let dir = TempDir::new().unwrap();
         let dest = dir.path().join("sub").join("dir").join("feed.atom");
         AtomExporter
-            .generate(&[], "Feed", "https://example.com", &dest, &no_enrichment())
+            .generate(
+                &[],
+                &Channel::new("Feed", "https://example.com"),
+                &dest,
+                &no_enrichment(),
+            )
             .unwrap();
         assert!(dest.exists());
     }
@@ -298,8 +362,7 @@ mod tests {
         AtomExporter
             .generate(
                 &[article],
-                "Podcast Feed",
-                "https://example.com",
+                &Channel::new("Podcast Feed", "https://example.com"),
                 &dest,
                 &no_enrichment(),
             )
@@ -325,8 +388,7 @@ mod tests {
         AtomExporter
             .generate(
                 &articles,
-                "Feed",
-                "https://example.com",
+                &Channel::new("Feed", "https://example.com"),
                 &dest,
                 &no_enrichment(),
             )
@@ -346,16 +408,15 @@ mod tests {
         AtomExporter
             .generate(
                 &[article],
-                "Feed",
-                "https://example.com",
+                &Channel::new("Feed", "https://example.com"),
                 &dest,
                 &no_enrichment(),
             )
             .unwrap();
         let xml = read_xml(&dest);
         assert!(xml.contains("<summary>Short summary</summary>"));
-        assert!(xml.contains("type=\"text\""));
-        assert!(xml.contains("Full **markdown** content"));
+        assert!(xml.contains("type=\"html\""));
+        assert!(xml.contains("Full &lt;strong&gt;markdown&lt;/strong&gt; content"));
     }
 
     #[test]
@@ -369,8 +430,7 @@ mod tests {
         AtomExporter
             .generate(
                 &articles,
-                "Feed",
-                "https://example.com",
+                &Channel::new("Feed", "https://example.com"),
                 &dest,
                 &no_enrichment(),
             )
@@ -404,8 +464,7 @@ mod tests {
         AtomExporter
             .generate(
                 &[article],
-                "Feed",
-                "https://example.com",
+                &Channel::new("Feed", "https://example.com"),
                 &dest,
                 &enrichments,
             )
@@ -413,6 +472,10 @@ mod tests {
         let xml = read_xml(&dest);
         assert!(xml.contains("SOURCE: My Feed"), "prepend missing");
         assert!(xml.contains("getpocket.com"), "append missing");
+        assert!(
+            xml.contains("&lt;a href=&quot;https://getpocket.com"),
+            "append not rendered as HTML"
+        );
         assert!(xml.contains("Article body"), "content missing");
     }
 }
@@ -3,11 +3,11 @@ use std::{collections::HashMap, fs, path::Path};
 use chrono::DateTime;
 use epub_builder::{EpubBuilder, EpubContent, EpubVersion, ZipLibrary};
 use pulldown_cmark::{Parser, html};
-use tracing::info;
+use tracing::{debug, info, warn};
 
 use crate::{error::FrustError, model::Article};
 
-use super::{Enrichment, Exporter};
+use super::{Channel, Enrichment, Exporter, escape_xml};
 
 pub(crate) struct EpubExporter;
 
@@ -15,8 +15,7 @@ impl Exporter for EpubExporter {
     fn generate(
         &self,
         articles: &[Article],
-        title: &str,
-        _link: &str,
+        channel: &Channel,
         destination: &Path,
         _enrichments: &HashMap<u64, Enrichment>,
     ) -> Result<(), FrustError> {
@@ -33,10 +32,29 @@ impl Exporter for EpubExporter {
 
         builder.epub_version(EpubVersion::V30);
         builder
-            .metadata("title", title)
+            .metadata("title", channel.title.as_str())
             .map_err(|e| FrustError::Export(e.to_string()))?
-            .metadata("lang", "en")
+            .metadata("lang", channel.language.as_deref().unwrap_or("en"))
             .map_err(|e| FrustError::Export(e.to_string()))?;
+        if let Some(author) = &channel.author {
+            builder
+                .metadata("author", author.as_str())
+                .map_err(|e| FrustError::Export(e.to_string()))?;
+        }
+        if let Some(description) = &channel.description {
+            builder
+                .metadata("description", description.as_str())
+                .map_err(|e| FrustError::Export(e.to_string()))?;
+        }
+        if let Some((name, image, mime)) = channel
+            .icon
+            .as_deref()
+            .and_then(|icon| cover_image(icon, destination))
+        {
+            builder
+                .add_cover_image(name, image.as_slice(), mime)
+                .map_err(|e| FrustError::Export(e.to_string()))?;
+        }
 
         let mut sorted: Vec<&Article> = articles.iter().collect();
         sorted.sort_by_key(|a| a.timestamp);
@@ -57,6 +75,34 @@ impl Exporter for EpubExporter {
     }
 }
 
+/// Cover of the book from the channel icon, when it is a local image file (a relative
+/// path is relative to the EPUB directory): `(name in the book, bytes, MIME type)`.
+fn cover_image(icon: &str, destination: &Path) -> Option<(String, Vec<u8>, &'static str)> {
+    if icon.contains("://") {
+        debug!("Remote icon {} is not used as the EPUB cover", icon);
+        return None;
+    }
+    let path = destination
+        .parent()
+        .map_or_else(|| Path::new(icon).to_path_buf(), |dir| dir.join(icon));
+    let ext = path.extension()?.to_str()?.to_ascii_lowercase();
+    let mime = match ext.as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "svg" => "image/svg+xml",
+        _ => return None,
+    };
+    match fs::read(&path) {
+        Ok(image) => Some((format!("cover.{ext}"), image, mime)),
+        Err(e) => {
+            warn!("Cannot read the EPUB cover {}: {}", path.display(), e);
+            None
+        }
+    }
+}
+
 fn article_to_xhtml(article: &Article) -> String {
     let mut body_html = String::new();
     html::push_html(&mut body_html, Parser::new(&article.content));
@@ -82,13 +128,6 @@ fn article_to_xhtml(article: &Article) -> String {
     )
 }
 
-fn escape_xml(s: &str) -> String {
-    s.replace('&', "&amp;")
-        .replace('<', "&lt;")
-        .replace('>', "&gt;")
-        .replace('"', "&quot;")
-}
-
 // ── tests ─────────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -128,8 +167,7 @@ mod tests {
         EpubExporter
             .generate(
                 &[],
-                "Empty Feed",
-                "https://example.com",
+                &Channel::new("Empty Feed", "https://example.com"),
                 &dest,
                 &no_enrichment(),
             )
@@ -148,8 +186,7 @@ mod tests {
         EpubExporter
             .generate(
                 &articles,
-                "My Feed",
-                "https://example.com",
+                &Channel::new("My Feed", "https://example.com"),
                 &dest,
                 &no_enrichment(),
             )
@@ -159,12 +196,56 @@ mod tests {
         assert_eq!(&bytes[..2], b"PK");
     }
 
+    #[test]
+    fn test_epub_channel_metadata_and_cover() {
+        let dir = TempDir::new().unwrap();
+        let dest = output_path(&dir, "feed.epub");
+        fs::write(dir.path().join("cover.png"), b"not really a PNG").unwrap();
+        let channel = Channel {
+            language: Some("fr".to_string()),
+            author: Some("Jane".to_string()),
+            icon: Some("cover.png".to_string()),
+            ..Channel::new("Nouvelles", "/news")
+        };
+        EpubExporter
+            .generate(&[], &channel, &dest, &no_enrichment())
+            .unwrap();
+
+        let mut archive = zip::ZipArchive::new(fs::File::open(&dest).unwrap()).unwrap();
+        let mut opf = String::new();
+        std::io::Read::read_to_string(&mut archive.by_name("OEBPS/content.opf").unwrap(), &mut opf)
+            .unwrap();
+        assert!(opf.contains(">fr</dc:language>"));
+        assert!(opf.contains(">Jane</dc:creator>"));
+        assert!(opf.contains("cover.png"));
+    }
+
+    #[test]
+    fn test_cover_image_from_local_files_only() {
+        let dir = TempDir::new().unwrap();
+        let dest = output_path(&dir, "feed.epub");
+        assert!(cover_image("https://example.com/icon.png", &dest).is_none());
+        assert!(cover_image("missing.png", &dest).is_none());
+        fs::write(dir.path().join("icon.txt"), b"text").unwrap();
+        assert!(cover_image("icon.txt", &dest).is_none());
+        fs::write(dir.path().join("icon.JPG"), b"jpeg").unwrap();
+        let (name, image, mime) = cover_image("icon.JPG", &dest).unwrap();
+        assert_eq!(name, "cover.jpg");
+        assert_eq!(image, b"jpeg");
+        assert_eq!(mime, "image/jpeg");
+    }
+
     #[test]
     fn test_epub_creates_parent_dirs() {
         let dir = TempDir::new().unwrap();
         let dest = dir.path().join("sub").join("dir").join("feed.epub");
         EpubExporter
-            .generate(&[], "Feed", "https://example.com", &dest, &no_enrichment())
+            .generate(
+                &[],
+                &Channel::new("Feed", "https://example.com"),
+                &dest,
+                &no_enrichment(),
+            )
             .unwrap();
         assert!(dest.exists());
     }
@@ -180,8 +261,7 @@ mod tests {
         EpubExporter
             .generate(
                 &articles,
-                "Multi Feed",
-                "https://example.com",
+                &Channel::new("Multi Feed", "https://example.com"),
                 &dest,
                 &no_enrichment(),
             )
@@ -201,8 +281,7 @@ mod tests {
         EpubExporter
             .generate(
                 &[article],
-                "Content Feed",
-                "https://example.com",
+                &Channel::new("Content Feed", "https://example.com"),
                 &dest,
                 &no_enrichment(),
             )
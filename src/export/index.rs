@@ -0,0 +1,481 @@
+use std::{collections::HashMap, fmt::Write, fs, path::Path};
+
+use chrono::DateTime;
+use tracing::info;
+
+use crate::{
+    error::FrustError,
+    model::{App, Feed, FeedState, RejectedArticle},
+};
+
+use super::escape_xml;
+
+/// Outcome of the current run for a feed.
+#[derive(Debug, Default, Clone)]
+pub(crate) struct FeedReport {
+    /// Articles stored during the run
+    pub(crate) new_articles: usize,
+    /// Entries dropped by the retention or the filters during the run
+    pub(crate) rejected: usize,
+    /// Why the feed could not be processed (unreachable, HTTP error, parse error…)
+    pub(crate) error: Option<String>,
+}
+
+/// Everything shown on the index page.
+pub(crate) struct IndexData<'a> {
+    pub(crate) app: &'a App,
+    /// Stored feed states, keyed by feed id
+    pub(crate) states: &'a HashMap<u64, FeedState>,
+    /// Reports of the feeds processed during the run, keyed by feed id
+    pub(crate) reports: &'a HashMap<u64, FeedReport>,
+    /// Remembered rejected entries
+    pub(crate) rejected: &'a [RejectedArticle],
+}
+
+/// Ignored articles listed per feed, the most recent ones
+const MAX_IGNORED_PER_FEED: usize = 20;
+
+/// Write `destination` (usually `{output}/index.html`): a landing page linking to every
+/// generated output, with the health of each feed and the articles ignored by filters.
+pub(crate) fn write_index(data: &IndexData, destination: &Path) -> Result<(), FrustError> {
+    info!("Writing index page to {}", destination.display());
+    if let Some(parent) = destination.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(destination, render_index(data))?;
+    Ok(())
+}
+
+/// MIME type advertised in `<link rel="alternate">`, `None` for non-feed outputs.
+fn feed_mime_type(output: &str) -> Option<&'static str> {
+    match Path::new(output).extension().and_then(|e| e.to_str()) {
+        Some("atom") => Some("application/atom+xml"),
+        Some("json") => Some("application/feed+json"),
+        Some("rss") | Some("xml") => Some("application/rss+xml"),
+        _ => None,
+    }
+}
+
+fn format_ts(ts: i64) -> String {
+    DateTime::from_timestamp(ts, 0)
+        .map(|d| d.format("%Y-%m-%d %H:%M UTC").to_string())
+        .unwrap_or_default()
+}
+
+/// Problem of the feed to list in the errors section: the error of the current run,
+/// the stored error of a feed still failing, or the HTTP error status of the last check.
+fn feed_error(report: Option<&FeedReport>, state: Option<&FeedState>) -> Option<String> {
+    if let Some(error) = report.and_then(|r| r.error.clone()) {
+        return Some(error);
+    }
+    if let Some(state) = state.filter(|s| s.consecutive_failures > 0)
+        && let Some(error) = &state.last_error
+    {
+        let next_check = state.next_check_ts.map(format_ts).unwrap_or_default();
+        return Some(format!(
+            "{} ({} consecutive failure(s), next check {})",
+            error, state.consecutive_failures, next_check
+        ));
+    }
+    state
+        .and_then(|s| s.last_http_status)
+        .filter(|status| *status >= 400)
+        .map(|status| format!("HTTP {}", status))
+}
+
+fn render_index(data: &IndexData) -> String {
+    let app = data.app;
+    let mut groups: Vec<_> = app.groups.values().collect();
+    groups.sort_by(|a, b| a.slug.cmp(&b.slug));
+
+    // Every generated output: (title, output path)
+    let mut outputs: Vec<(&str, &str)> = Vec::new();
+    for group in &groups {
+        outputs.push((group_title(group), &group.output));
+        let mut feeds: Vec<&Feed> = group.feeds.values().collect();
+        feeds.sort_by(|a, b| a.title.cmp(&b.title));
+        outputs.extend(
+            feeds
+                .iter()
+                .filter_map(|f| f.output.as_deref().map(|o| (f.name(), o))),
+        );
+    }
+
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n<title>Frust feeds</title>\n",
+    );
+    for (title, output) in &outputs {
+        if let Some(mime) = feed_mime_type(output)
+            && let Some(href) = app.output_href(output)
+        {
+            let _ = writeln!(
+                html,
+                "<link rel=\"alternate\" type=\"{}\" title=\"{}\" href=\"{}\">",
+                mime,
+                escape_xml(title),
+                escape_xml(&href)
+            );
+        }
+    }
+    html.push_str("</head>\n<body>\n<h1>Frust feeds</h1>\n");
+
+    // Feeds, grouped
+    let mut errors: Vec<(String, String)> = Vec::new();
+    for group in &groups {
+        let _ = match app.output_href(&group.output) {
+            Some(href) => writeln!(
+                html,
+                "<h2><a href=\"{}\">{}</a></h2>",
+                escape_xml(&href),
+                escape_xml(group_title(group))
+            ),
+            None => writeln!(html, "<h2>{}</h2>", escape_xml(group_title(group))),
+        };
+        html.push_str("<table>\n<tr><th>Feed</th><th>Output</th><th>Last check</th><th>HTTP status</th><th>New</th><th>Ignored</th></tr>\n");
+        let mut feeds: Vec<(&u64, &Feed)> = group.feeds.iter().collect();
+        feeds.sort_by(|a, b| a.1.title.cmp(&b.1.title));
+        for (feed_id, feed) in feeds {
+            let state = data.states.get(feed_id);
+            let report = data.reports.get(feed_id);
+            let page = if feed.page_url.is_empty() {
+                &feed.url
+            } else {
+                &feed.page_url
+            };
+            let output = feed
+                .output
+                .as_deref()
+                .and_then(|o| app.output_href(o))
+                .map_or(String::new(), |href| {
+                    format!(
+                        "<a href=\"{}\">{}</a>",
+                        escape_xml(&href),
+                        escape_xml(&href)
+                    )
+                });
+            let _ = writeln!(
+                html,
+                "<tr><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape_xml(page),
+                escape_xml(feed.name()),
+                output,
+                state
+                    .and_then(|s| s.last_check_ts)
+                    .map(format_ts)
+                    .unwrap_or_default(),
+                state
+                    .and_then(|s| s.last_http_status)
+                    .map(|s| s.to_string())
+                    .unwrap_or_default(),
+                report
+                    .map(|r| r.new_articles.to_string())
+                    .unwrap_or_default(),
+                report.map(|r| r.rejected.to_string()).unwrap_or_default(),
+            );
+            if let Some(error) = feed_error(report, state) {
+                errors.push((feed.name().to_string(), error));
+            }
+        }
+        html.push_str("</table>\n");
+    }
+
+    // Feed errors
+    html.push_str("<h2>Errors</h2>\n");
+    if errors.is_empty() {
+        html.push_str("<p>No error.</p>\n");
+    } else {
+        html.push_str("<ul>\n");
+        for (title, error) in &errors {
+            let _ = writeln!(
+                html,
+                "<li>{}: {}</li>",
+                escape_xml(title),
+                escape_xml(error)
+            );
+        }
+        html.push_str("</ul>\n");
+    }
+
+    // Articles dropped by filters (the expired ones are not worth listing)
+    let feed_titles: HashMap<u64, &str> = app
+        .groups
+        .values()
+        .flat_map(|g| g.feeds.iter().map(|(id, f)| (*id, f.name())))
+        .collect();
+    let mut ignored: Vec<&RejectedArticle> = data
+        .rejected
+        .iter()
+        .filter(|r| r.filter.is_some())
+        .collect();
+    ignored.sort_by_key(|r| std::cmp::Reverse((r.rejected_at, r.timestamp)));
+    let mut listed: HashMap<u64, usize> = HashMap::new();
+    let total = ignored.len();
+    ignored.retain(|r| {
+        let count = listed.entry(r.feed_id).or_default();
+        *count += 1;
+        *count <= MAX_IGNORED_PER_FEED
+    });
+    html.push_str("<h2>Ignored articles</h2>\n");
+    if ignored.len() < total {
+        let _ = writeln!(
+            html,
+            "<p>The {} most recent of each feed, out of {}.</p>",
+            MAX_IGNORED_PER_FEED, total
+        );
+    }
+    if ignored.is_empty() {
+        html.push_str("<p>No ignored article.</p>\n");
+    } else {
+        html.push_str("<table>\n<tr><th>Article</th><th>Feed</th><th>Date</th><th>Reason</th><th>Filter</th></tr>\n");
+        for r in ignored {
+            let _ = writeln!(
+                html,
+                "<tr><td><a href=\"{}\">{}</a></td><td>{}</td><td>{}</td><td>{}</td><td>{}</td></tr>",
+                escape_xml(&r.url),
+                escape_xml(&r.title),
+                escape_xml(feed_titles.get(&r.feed_id).copied().unwrap_or_default()),
+                format_ts(r.timestamp),
+                escape_xml(&r.reason),
+                escape_xml(r.filter.as_deref().unwrap_or_default()),
+            );
+        }
+        html.push_str("</table>\n");
+    }
+
+    html.push_str("</body>\n</html>\n");
+    html
+}
+
+/// Title of a group, its slug when it has none.
+fn group_title(group: &crate::model::Group) -> &str {
+    if group.title.is_empty() {
+        &group.slug
+    } else {
+        &group.title
+    }
+}
+
+// ── tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{ContentMode, Group, HttpSettings};
+    use tempfile::TempDir;
+
+    fn make_feed(title: &str, output: Option<&str>) -> Feed {
+        Feed {
+            title: title.to_string(),
+            slug: title.to_lowercase(),
+            url: format!("https://{}.example.com/feed.xml", title.to_lowercase()),
+            page_url: String::new(),
+            content_mode: ContentMode::Default,
+            selector: None,
+            filters: Vec::new(),
+            output: output.map(str::to_string),
+            retention: 0,
+            min_refresh_time: 600,
+            media: false,
+            media_max_size: 0,
+            enrichment_prepend: None,
+            enrichment_append: None,
+            http: HttpSettings::default(),
+        }
+    }
+
+    fn make_app(output: &str) -> App {
+        let group = Group {
+            title: "Tech & News".to_string(),
+            slug: "tech".to_string(),
+            output: "tech.atom".to_string(),
+            feeds: HashMap::from([
+                (1, make_feed("Alpha", None)),
+                (2, make_feed("Beta", Some("beta.json"))),
+            ]),
+            ..Group::default()
+        };
+        App {
+            output: output.to_string(),
+            groups: HashMap::from([(10, group)]),
+            ..App::default()
+        }
+    }
+
+    fn make_rejected(id: u64, reason: &str, filter: Option<&str>) -> RejectedArticle {
+        RejectedArticle {
+            filters_hash: 0,
+            id,
+            feed_id: 1,
+            title: format!("Rejected <{}>", id),
+            url: format!("https://alpha.example.com/{}", id),
+            timestamp: 1_700_000_000,
+            rejected_at: 1_700_000_000,
+            reason: reason.to_string(),
+            filter: filter.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_index_links_every_output() {
+        let app = make_app("/srv/rss");
+        let html = render_index(&IndexData {
+            app: &app,
+            states: &HashMap::new(),
+            reports: &HashMap::new(),
+            rejected: &[],
+        });
+        assert!(html.contains(
+            "<link rel=\"alternate\" type=\"application/atom+xml\" title=\"Tech &amp; News\" href=\"tech.atom\">"
+        ));
+        assert!(html.contains(
+            "<link rel=\"alternate\" type=\"application/feed+json\" title=\"Beta\" href=\"beta.json\">"
+        ));
+        assert!(html.contains("<h2><a href=\"tech.atom\">Tech &amp; News</a></h2>"));
+        assert!(html.contains("<a href=\"beta.json\">beta.json</a>"));
+        assert!(html.contains("<p>No error.</p>"));
+        assert!(html.contains("<p>No ignored article.</p>"));
+    }
+
+    #[test]
+    fn test_index_lists_errors_and_ignored_articles() {
+        let app = make_app("/srv/rss");
+        let states = HashMap::from([(
+            2,
+            FeedState {
+                last_etag: None,
+                last_check_ts: Some(1_700_000_000),
+                last_modified_ts: None,
+                last_http_status: Some(404),
+                ..FeedState::default()
+            },
+        )]);
+        let reports = HashMap::from([
+            (
+                1,
+                FeedReport {
+                    new_articles: 0,
+                    rejected: 0,
+                    error: Some("HTTP error: connection refused".to_string()),
+                },
+            ),
+            (
+                2,
+                FeedReport {
+                    new_articles: 3,
+                    rejected: 1,
+                    error: None,
+                },
+            ),
+        ]);
+        let rejected = [
+            make_rejected(1, "excluded", Some("sponsored")),
+            make_rejected(2, "expired", None),
+        ];
+        let html = render_index(&IndexData {
+            app: &app,
+            states: &states,
+            reports: &reports,
+            rejected: &rejected,
+        });
+        assert!(html.contains("<li>Alpha: HTTP error: connection refused</li>"));
+        assert!(html.contains("<li>Beta: HTTP 404</li>"));
+        assert!(html.contains("<td>2023-11-14 22:13 UTC</td><td>404</td><td>3</td><td>1</td>"));
+        assert!(html.contains(
+            "<tr><td><a href=\"https://alpha.example.com/1\">Rejected &lt;1&gt;</a></td><td>Alpha</td><td>2023-11-14 22:13 UTC</td><td>excluded</td><td>sponsored</td></tr>"
+        ));
+        // expired entries are not listed
+        assert!(!html.contains("Rejected &lt;2&gt;"));
+    }
+
+    #[test]
+    fn test_index_caps_ignored_articles_per_feed() {
+        let app = make_app("/srv/rss");
+        let rejected: Vec<RejectedArticle> = (0..MAX_IGNORED_PER_FEED as u64 + 5)
+            .map(|id| RejectedArticle {
+                rejected_at: 1_700_000_000 + id as i64,
+                ..make_rejected(id, "excluded", Some("sponsored"))
+            })
+            .chain([RejectedArticle {
+                feed_id: 2,
+                ..make_rejected(100, "excluded", Some("sponsored"))
+            }])
+            .collect();
+        let html = render_index(&IndexData {
+            app: &app,
+            states: &HashMap::new(),
+            reports: &HashMap::new(),
+            rejected: &rejected,
+        });
+        assert_eq!(
+            html.matches("<td>excluded</td>").count(),
+            MAX_IGNORED_PER_FEED + 1
+        );
+        // the oldest entries of the first feed are left out
+        assert!(html.contains("Rejected &lt;24&gt;"));
+        assert!(!html.contains("Rejected &lt;4&gt;"));
+        assert!(html.contains("Rejected &lt;100&gt;"));
+        assert!(html.contains("<p>The 20 most recent of each feed, out of 26.</p>"));
+    }
+
+    #[test]
+    fn test_index_does_not_link_outputs_outside_the_directory() {
+        let mut app = make_app("/srv/rss");
+        let group = app.groups.get_mut(&10).unwrap();
+        group.output = "/var/www/tech.atom".to_string();
+        group.feeds.get_mut(&2).unwrap().output = Some("../beta.json".to_string());
+        group.feeds.get_mut(&1).unwrap().output = Some("/srv/rss/feeds/alpha.rss".to_string());
+        let html = render_index(&IndexData {
+            app: &app,
+            states: &HashMap::new(),
+            reports: &HashMap::new(),
+            rejected: &[],
+        });
+        assert!(html.contains("<h2>Tech &amp; News</h2>"));
+        assert!(!html.contains("/var/www"));
+        assert!(!html.contains("beta.json"));
+        assert!(html.contains("<a href=\"feeds/alpha.rss\">feeds/alpha.rss</a>"));
+    }
+
+    #[test]
+    fn test_index_lists_feed_backing_off() {
+        let app = make_app("/srv/rss");
+        let states = HashMap::from([(
+            1,
+            FeedState {
+                last_check_ts: Some(1_700_000_000),
+                consecutive_failures: 3,
+                last_error: Some("HTTP status 503".to_string()),
+                next_check_ts: Some(1_700_007_200),
+                ..FeedState::default()
+            },
+        )]);
+        let html = render_index(&IndexData {
+            app: &app,
+            states: &states,
+            reports: &HashMap::new(),
+            rejected: &[],
+        });
+        assert!(html.contains(
+            "<li>Alpha: HTTP status 503 (3 consecutive failure(s), next check 2023-11-15 00:13 UTC)</li>"
+        ));
+    }
+
+    #[test]
+    fn test_write_index_creates_file() {
+        let dir = TempDir::new().unwrap();
+        let app = make_app(dir.path().to_str().unwrap());
+        let dest = dir.path().join("index.html");
+        write_index(
+            &IndexData {
+                app: &app,
+                states: &HashMap::new(),
+                reports: &HashMap::new(),
+                rejected: &[],
+            },
+            &dest,
+        )
+        .unwrap();
+        let html = fs::read_to_string(&dest).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+    }
+}
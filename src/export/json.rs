@@ -1,13 +1,7 @@
-use std::{
-    collections::{BTreeMap, HashMap},
-    fs,
-    io::BufWriter,
-    path::Path,
-};
+use std::{collections::HashMap, fs, io::BufWriter, path::Path};
 
 use chrono::{DateTime, Utc};
 use serde::Serialize;
-use slug::slugify;
 use tracing::info;
 
 use crate::{
@@ -15,7 +9,9 @@ use crate::{
     model::{Article, ExportStrategy},
 };
 
-use super::{Enrichment, Exporter, render_template};
+use super::{
+    Channel, Enrichment, Exporter, article_filenames, enrich, group_by_period, markdown_to_html,
+};
 
 pub(crate) struct JsonExporter {
     pub(crate) strategy: ExportStrategy,
@@ -41,6 +37,9 @@ struct ItemDto<'a> {
     url: &'a str,
     #[serde(skip_serializing_if = "str::is_empty")]
     title: &'a str,
+    /// Markdown content rendered to HTML.
+    #[serde(skip_serializing_if = "String::is_empty")]
+    content_html: String,
     /// Markdown content maps to `content_text` (plain text per spec).
     #[serde(skip_serializing_if = "String::is_empty")]
     content_text: String,
@@ -53,6 +52,12 @@ struct ItemDto<'a> {
     attachments: Vec<AttachmentDto<'a>>,
 }
 
+/// JSON Feed 1.1 author object
+#[derive(Serialize)]
+struct AuthorDto<'a> {
+    name: &'a str,
+}
+
 /// JSON Feed 1.1 top-level feed object (used for Monolithic strategy)
 #[derive(Serialize)]
 struct FeedDto<'a> {
@@ -60,6 +65,19 @@ struct FeedDto<'a> {
     title: &'a str,
     #[serde(skip_serializing_if = "str::is_empty")]
     home_page_url: &'a str,
+    /// URL of the JSON Feed itself.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    feed_url: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    description: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    icon: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    favicon: Option<&'a str>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    authors: Vec<AuthorDto<'a>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    language: Option<&'a str>,
     items: Vec<ItemDto<'a>>,
 }
 
@@ -71,26 +89,17 @@ fn to_item<'a>(article: &'a Article, enrichment: Option<&Enrichment>) -> ItemDto
     } else {
         None
     };
-    let content_text = match enrichment {
-        Some(e) => {
-            let pre = e
-                .prepend
-                .as_deref()
-                .map(|t| render_template(t, e, article))
-                .unwrap_or_default();
-            let app = e
-                .append
-                .as_deref()
-                .map(|t| render_template(t, e, article))
-                .unwrap_or_default();
-            format!("{pre}{}{app}", article.content)
-        }
-        None => article.content.clone(),
+    let content_text = enrich(&article.content, enrichment, article);
+    let content_html = if content_text.is_empty() {
+        String::new()
+    } else {
+        markdown_to_html(&content_text)
     };
     ItemDto {
         id: &article.url,
         url: &article.url,
         title: &article.title,
+        content_html,
         content_text,
         summary: article.summary.as_deref(),
         date_published,
@@ -106,20 +115,6 @@ fn to_item<'a>(article: &'a Article, enrichment: Option<&Enrichment>) -> ItemDto
     }
 }
 
-fn article_filename(article: &Article) -> String {
-    let date = DateTime::<Utc>::from_timestamp(article.timestamp, 0)
-        .unwrap_or_default()
-        .format("%Y-%m-%d")
-        .to_string();
-    let title_slug = slugify(&article.title);
-    let title_slug = if title_slug.is_empty() {
-        article.id.to_string()
-    } else {
-        title_slug
-    };
-    format!("{date}-{title_slug}.json")
-}
-
 fn write_json<T: Serialize>(value: &T, path: &Path) -> Result<(), FrustError> {
     if let Some(parent) = path.parent() {
         fs::create_dir_all(parent)?;
@@ -135,18 +130,17 @@ impl Exporter for JsonExporter {
     fn generate(
         &self,
         articles: &[Article],
-        title: &str,
-        link: &str,
+        channel: &Channel,
         destination: &Path,
         enrichments: &HashMap<u64, Enrichment>,
     ) -> Result<(), FrustError> {
         info!("Exporting to JSON");
         match self.strategy {
-            ExportStrategy::Monolithic => {
-                monolithic(articles, title, link, destination, enrichments)
-            }
+            ExportStrategy::Monolithic => monolithic(articles, channel, destination, enrichments),
             ExportStrategy::Individual => individual(articles, destination, enrichments),
-            ExportStrategy::Daily => daily(articles, destination, enrichments),
+            ExportStrategy::Daily | ExportStrategy::Weekly | ExportStrategy::Monthly => {
+                periodic(articles, self.strategy, destination, enrichments)
+            }
         }
     }
 }
@@ -154,15 +148,24 @@ impl Exporter for JsonExporter {
 /// Produces a complete JSON Feed 1.1 document.
 fn monolithic(
     articles: &[Article],
-    title: &str,
-    link: &str,
+    channel: &Channel,
     destination: &Path,
     enrichments: &HashMap<u64, Enrichment>,
 ) -> Result<(), FrustError> {
     let feed = FeedDto {
         version: "https://jsonfeed.org/version/1.1",
-        title,
-        home_page_url: link,
+        title: &channel.title,
+        home_page_url: channel.home_page(),
+        feed_url: channel.self_url.as_deref(),
+        description: channel.description.as_deref(),
+        icon: channel.icon.as_deref(),
+        favicon: channel.icon.as_deref(),
+        authors: channel
+            .author
+            .iter()
+            .map(|name| AuthorDto { name })
+            .collect(),
+        language: channel.language.as_deref(),
         items: articles
             .iter()
             .map(|a| to_item(a, enrichments.get(&a.feed_id)))
@@ -178,17 +181,7 @@ fn individual(
     enrichments: &HashMap<u64, Enrichment>,
 ) -> Result<(), FrustError> {
     fs::create_dir_all(destination)?;
-    let mut used: HashMap<String, u32> = HashMap::new();
-    for article in articles {
-        let base = article_filename(article);
-        let stem = base.trim_end_matches(".json").to_string();
-        let idx = used.entry(stem.clone()).or_insert(0);
-        let filename = if *idx == 0 {
-            format!("{stem}.json")
-        } else {
-            format!("{stem}-{idx}.json")
-        };
-        *idx += 1;
+    for (article, filename) in articles.iter().zip(article_filenames(articles, "json")) {
         let path = destination.join(filename);
         let file = fs::File::create(&path)?;
         serde_json::to_writer_pretty(
@@ -200,25 +193,18 @@ fn individual(
     Ok(())
 }
 
-/// Produces one JSON file per day (an array of JSON Feed item objects each).
-fn daily(
+/// Produces one JSON file per day, week or month (an array of JSON Feed item objects each).
+fn periodic(
     articles: &[Article],
+    strategy: ExportStrategy,
     destination: &Path,
     enrichments: &HashMap<u64, Enrichment>,
 ) -> Result<(), FrustError> {
     fs::create_dir_all(destination)?;
-    let mut by_day: BTreeMap<String, Vec<&Article>> = BTreeMap::new();
-    for article in articles {
-        let day = DateTime::<Utc>::from_timestamp(article.timestamp, 0)
-            .unwrap_or_default()
-            .format("%Y-%m-%d")
-            .to_string();
-        by_day.entry(day).or_default().push(article);
-    }
-    for (day, day_articles) in &by_day {
-        let path = destination.join(format!("{day}.json"));
+    for (period, period_articles) in &group_by_period(articles, strategy) {
+        let path = destination.join(format!("{period}.json"));
         let file = fs::File::create(&path)?;
-        let items: Vec<ItemDto> = day_articles
+        let items: Vec<ItemDto> = period_articles
             .iter()
             .map(|a| to_item(a, enrichments.get(&a.feed_id)))
             .collect();
@@ -269,8 +255,7 @@ mod tests {
         }
         .generate(
             &[],
-            "My Feed",
-            "https://example.com",
+            &Channel::new("My Feed", "https://example.com"),
             &dest,
             &no_enrichment(),
         )
@@ -285,6 +270,81 @@ mod tests {
         assert!(v["items"].as_array().unwrap().is_empty());
     }
 
+    #[test]
+    fn test_monolithic_content_html_is_sanitized() {
+        let dir = TempDir::new().unwrap();
+        let dest = dir.path().join("feed.json");
+        let mut article = make_article(1, "Rich", "https://example.com/r", 0);
+        article.content =
+            "A [link](https://example.com/a) <img src=x onerror=alert(1)>".to_string();
+        JsonExporter {
+            strategy: ExportStrategy::Monolithic,
+        }
+        .generate(
+            &[article],
+            &Channel::new("Feed", "https://example.com"),
+            &dest,
+            &no_enrichment(),
+        )
+        .unwrap();
+        let v = parse(&dest);
+        let html = v["items"][0]["content_html"].as_str().unwrap();
+        assert!(html.contains("<a href=\"https://example.com/a\""));
+        assert!(!html.contains("onerror"));
+        // The Markdown stays available as plain text
+        assert!(
+            v["items"][0]["content_text"]
+                .as_str()
+                .unwrap()
+                .starts_with("A [link](https://example.com/a)")
+        );
+    }
+
+    #[test]
+    fn test_monolithic_channel_metadata() {
+        let dir = TempDir::new().unwrap();
+        let dest = dir.path().join("feed.json");
+        let channel = Channel {
+            description: Some("Nouvelles".to_string()),
+            language: Some("fr".to_string()),
+            icon: Some("https://example.com/icon.png".to_string()),
+            author: Some("Jane".to_string()),
+            site_url: Some("https://example.com/".to_string()),
+            self_url: Some("https://rss.example.org/news.json".to_string()),
+            ..Channel::new("News", "https://rss.example.org/news.json")
+        };
+        JsonExporter {
+            strategy: ExportStrategy::Monolithic,
+        }
+        .generate(&[], &channel, &dest, &no_enrichment())
+        .unwrap();
+        let v = parse(&dest);
+        assert_eq!(v["home_page_url"].as_str().unwrap(), "https://example.com/");
+        assert_eq!(
+            v["feed_url"].as_str().unwrap(),
+            "https://rss.example.org/news.json"
+        );
+        assert_eq!(v["description"].as_str().unwrap(), "Nouvelles");
+        assert_eq!(v["language"].as_str().unwrap(), "fr");
+        assert_eq!(v["icon"].as_str().unwrap(), "https://example.com/icon.png");
+        assert_eq!(
+            v["favicon"].as_str().unwrap(),
+            "https://example.com/icon.png"
+        );
+        assert_eq!(v["authors"][0]["name"].as_str().unwrap(), "Jane");
+
+        // Metadata left out of the configuration is not written
+        JsonExporter {
+            strategy: ExportStrategy::Monolithic,
+        }
+        .generate(&[], &Channel::new("Feed", "/feed"), &dest, &no_enrichment())
+        .unwrap();
+        let v = parse(&dest);
+        assert!(v["language"].is_null());
+        assert!(v["authors"].is_null());
+        assert!(v["feed_url"].is_null());
+    }
+
     #[test]
     fn test_monolithic_article_fields() {
         let dir = TempDir::new().unwrap();
@@ -300,8 +360,7 @@ mod tests {
         }
         .generate(
             &articles,
-            "Feed",
-            "https://example.com",
+            &Channel::new("Feed", "https://example.com"),
             &dest,
             &no_enrichment(),
         )
@@ -328,7 +387,12 @@ mod tests {
         JsonExporter {
             strategy: ExportStrategy::Monolithic,
         }
-        .generate(&[], "Feed", "https://example.com", &dest, &no_enrichment())
+        .generate(
+            &[],
+            &Channel::new("Feed", "https://example.com"),
+            &dest,
+            &no_enrichment(),
+        )
         .unwrap();
         assert!(dest.exists());
     }
@@ -344,8 +408,7 @@ mod tests {
         }
         .generate(
             &[article],
-            "Feed",
-            "https://example.com",
+            &Channel::new("Feed", "https://example.com"),
             &dest,
             &no_enrichment(),
         )
@@ -369,8 +432,7 @@ mod tests {
         }
         .generate(
             &[article],
-            "Podcast Feed",
-            "https://example.com",
+            &Channel::new("Podcast Feed", "https://example.com"),
             &dest,
             &no_enrichment(),
         )
@@ -395,8 +457,7 @@ mod tests {
         }
         .generate(
             &articles,
-            "Feed",
-            "https://example.com",
+            &Channel::new("Feed", "https://example.com"),
             &dest,
             &no_enrichment(),
         )
@@ -419,8 +480,7 @@ mod tests {
         }
         .generate(
             &articles,
-            "Feed",
-            "https://example.com",
+            &Channel::new("Feed", "https://example.com"),
             &dest,
             &no_enrichment(),
         )
@@ -444,8 +504,7 @@ mod tests {
         }
         .generate(
             &articles,
-            "Feed",
-            "https://example.com",
+            &Channel::new("Feed", "https://example.com"),
             &dest,
             &no_enrichment(),
         )
@@ -472,8 +531,7 @@ mod tests {
         }
         .generate(
             &articles,
-            "Feed",
-            "https://example.com",
+            &Channel::new("Feed", "https://example.com"),
             &dest,
             &no_enrichment(),
         )
@@ -501,8 +559,7 @@ mod tests {
         }
         .generate(
             &articles,
-            "Feed",
-            "https://example.com",
+            &Channel::new("Feed", "https://example.com"),
             &dest,
             &no_enrichment(),
         )
@@ -536,8 +593,7 @@ mod tests {
         }
         .generate(
             &[article],
-            "Feed",
-            "https://example.com",
+            &Channel::new("Feed", "https://example.com"),
             &dest,
             &enrichments,
         )
@@ -547,5 +603,11 @@ mod tests {
         assert!(ct.contains("Via My Source:"), "prepend missing");
         assert!(ct.contains("getpocket.com"), "append missing");
         assert!(ct.contains("The content"), "original content missing");
+        let html = v["items"][0]["content_html"].as_str().unwrap();
+        assert!(html.starts_with("<p>Via My Source:"), "prepend missing");
+        assert!(
+            html.contains("<a href=\"https://getpocket.com/save?url=https://example.com/1\""),
+            "append not rendered as HTML"
+        );
     }
 }
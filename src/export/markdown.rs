@@ -1,12 +1,11 @@
 use std::{
-    collections::{BTreeMap, HashMap},
+    collections::HashMap,
     fs,
     io::{BufWriter, Write},
     path::Path,
 };
 
 use chrono::{DateTime, Utc};
-use slug::slugify;
 use tracing::info;
 
 use crate::{
@@ -14,7 +13,7 @@ use crate::{
     model::{Article, ExportStrategy},
 };
 
-use super::{Enrichment, Exporter};
+use super::{Channel, Enrichment, Exporter, article_filenames, group_by_period};
 
 pub(crate) struct MarkdownExporter {
     pub(crate) strategy: ExportStrategy,
@@ -24,16 +23,17 @@ impl Exporter for MarkdownExporter {
     fn generate(
         &self,
         articles: &[Article],
-        title: &str,
-        link: &str,
+        channel: &Channel,
         destination: &Path,
         _enrichments: &HashMap<u64, Enrichment>,
     ) -> Result<(), FrustError> {
         info!("Exporting to Markdown");
         match self.strategy {
-            ExportStrategy::Monolithic => monolithic(articles, title, link, destination),
+            ExportStrategy::Monolithic => monolithic(articles, channel, destination),
             ExportStrategy::Individual => individual(articles, destination),
-            ExportStrategy::Daily => daily(articles, destination),
+            ExportStrategy::Daily | ExportStrategy::Weekly | ExportStrategy::Monthly => {
+                periodic(articles, self.strategy, destination)
+            }
         }
     }
 }
@@ -55,24 +55,9 @@ fn article_to_md(article: &Article) -> String {
     s
 }
 
-fn article_filename(article: &Article, ext: &str) -> String {
-    let date = DateTime::<Utc>::from_timestamp(article.timestamp, 0)
-        .unwrap_or_default()
-        .format("%Y-%m-%d")
-        .to_string();
-    let title_slug = slugify(&article.title);
-    let title_slug = if title_slug.is_empty() {
-        article.id.to_string()
-    } else {
-        title_slug
-    };
-    format!("{date}-{title_slug}.{ext}")
-}
-
 fn monolithic(
     articles: &[Article],
-    title: &str,
-    link: &str,
+    channel: &Channel,
     destination: &Path,
 ) -> Result<(), FrustError> {
     if let Some(parent) = destination.parent() {
@@ -80,8 +65,11 @@ fn monolithic(
     }
     let file = fs::File::create(destination)?;
     let mut w = BufWriter::new(file);
-    writeln!(w, "# {title}")?;
-    writeln!(w, "\nSource: {link}\n")?;
+    writeln!(w, "# {}", channel.title)?;
+    if let Some(description) = &channel.description {
+        writeln!(w, "\n{description}")?;
+    }
+    writeln!(w, "\nSource: {}\n", channel.home_page())?;
     for article in articles {
         writeln!(w, "{}", article_to_md(article))?;
     }
@@ -90,40 +78,25 @@ fn monolithic(
 
 fn individual(articles: &[Article], destination: &Path) -> Result<(), FrustError> {
     fs::create_dir_all(destination)?;
-    let mut used: HashMap<String, u32> = HashMap::new();
-    for article in articles {
-        let base = article_filename(article, "md");
-        // Strip .md to get the stem for dedup, then re-append
-        let stem = base.trim_end_matches(".md").to_string();
-        let idx = used.entry(stem.clone()).or_insert(0);
-        let filename = if *idx == 0 {
-            format!("{stem}.md")
-        } else {
-            format!("{stem}-{idx}.md")
-        };
-        *idx += 1;
+    for (article, filename) in articles.iter().zip(article_filenames(articles, "md")) {
         fs::write(destination.join(filename), article_to_md(article))?;
     }
     Ok(())
 }
 
-fn daily(articles: &[Article], destination: &Path) -> Result<(), FrustError> {
+/// One file per day, week or month, headed by the period.
+fn periodic(
+    articles: &[Article],
+    strategy: ExportStrategy,
+    destination: &Path,
+) -> Result<(), FrustError> {
     fs::create_dir_all(destination)?;
-    // BTreeMap gives deterministic (chronological) order over ISO date keys
-    let mut by_day: BTreeMap<String, Vec<&Article>> = BTreeMap::new();
-    for article in articles {
-        let day = DateTime::<Utc>::from_timestamp(article.timestamp, 0)
-            .unwrap_or_default()
-            .format("%Y-%m-%d")
-            .to_string();
-        by_day.entry(day).or_default().push(article);
-    }
-    for (day, day_articles) in &by_day {
-        let path = destination.join(format!("{day}.md"));
+    for (period, period_articles) in &group_by_period(articles, strategy) {
+        let path = destination.join(format!("{period}.md"));
         let file = fs::File::create(&path)?;
         let mut w = BufWriter::new(file);
-        writeln!(w, "# {day}\n")?;
-        for article in day_articles {
+        writeln!(w, "# {period}\n")?;
+        for article in period_articles {
             writeln!(w, "{}", article_to_md(article))?;
         }
     }
@@ -166,8 +139,7 @@ mod tests {
         }
         .generate(
             &[],
-            "My Feed",
-            "https://example.com",
+            &Channel::new("My Feed", "https://example.com"),
             &dest,
             &no_enrichment(),
         )
@@ -193,8 +165,7 @@ mod tests {
         }
         .generate(
             &articles,
-            "My Feed",
-            "https://example.com",
+            &Channel::new("My Feed", "https://example.com"),
             &dest,
             &no_enrichment(),
         )
@@ -212,7 +183,12 @@ mod tests {
         MarkdownExporter {
             strategy: ExportStrategy::Monolithic,
         }
-        .generate(&[], "Feed", "https://example.com", &dest, &no_enrichment())
+        .generate(
+            &[],
+            &Channel::new("Feed", "https://example.com"),
+            &dest,
+            &no_enrichment(),
+        )
         .unwrap();
         assert!(dest.exists());
     }
@@ -233,8 +209,7 @@ mod tests {
         }
         .generate(
             &articles,
-            "Feed",
-            "https://example.com",
+            &Channel::new("Feed", "https://example.com"),
             &dest,
             &no_enrichment(),
         )
@@ -268,8 +243,7 @@ mod tests {
         }
         .generate(
             &articles,
-            "Feed",
-            "https://example.com",
+            &Channel::new("Feed", "https://example.com"),
             &dest,
             &no_enrichment(),
         )
@@ -294,8 +268,7 @@ mod tests {
         }
         .generate(
             &articles,
-            "Feed",
-            "https://example.com",
+            &Channel::new("Feed", "https://example.com"),
             &dest,
             &no_enrichment(),
         )
@@ -320,8 +293,7 @@ mod tests {
         }
         .generate(
             &articles,
-            "Feed",
-            "https://example.com",
+            &Channel::new("Feed", "https://example.com"),
             &dest,
             &no_enrichment(),
         )
@@ -350,8 +322,7 @@ mod tests {
         }
         .generate(
             &articles,
-            "Feed",
-            "https://example.com",
+            &Channel::new("Feed", "https://example.com"),
             &dest,
             &no_enrichment(),
         )
@@ -379,8 +350,7 @@ mod tests {
         }
         .generate(
             &articles,
-            "Feed",
-            "https://example.com",
+            &Channel::new("Feed", "https://example.com"),
             &dest,
             &no_enrichment(),
         )
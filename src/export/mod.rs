@@ -1,8 +1,10 @@
 pub(crate) mod atom;
 pub(crate) mod epub;
+pub(crate) mod index;
 pub(crate) mod json;
 pub(crate) mod markdown;
 pub(crate) mod rss;
+pub(crate) mod split;
 pub(crate) mod zip;
 
 pub(crate) use atom::AtomExporter;
@@ -10,12 +12,20 @@ pub(crate) use epub::EpubExporter;
 pub(crate) use json::JsonExporter;
 pub(crate) use markdown::MarkdownExporter;
 pub(crate) use rss::RssExporter;
+pub(crate) use split::SplitExporter;
 
-use std::{collections::HashMap, path::Path};
+use std::{
+    collections::{BTreeMap, HashMap},
+    path::Path,
+};
+
+use chrono::{DateTime, Utc};
+use pulldown_cmark::{Parser, html};
+use slug::slugify;
 
 use crate::{
     error::FrustError,
-    model::{Article, Enrichment},
+    model::{Article, Channel, Enrichment, ExportStrategy},
 };
 
 /// Substitute `{{key}}` placeholders in `template` using feed + article data.
@@ -30,19 +40,110 @@ pub(crate) fn render_template(template: &str, e: &Enrichment, article: &Article)
         .replace("{{article.id}}", &article.id.to_string())
 }
 
+/// `text` of an article surrounded by the rendered prepend/append templates of its feed.
+pub(crate) fn enrich(text: &str, enrichment: Option<&Enrichment>, article: &Article) -> String {
+    let Some(e) = enrichment else {
+        return text.to_string();
+    };
+    let render = |template: Option<&str>| {
+        template
+            .map(|t| render_template(t, e, article))
+            .unwrap_or_default()
+    };
+    format!(
+        "{}{text}{}",
+        render(e.prepend.as_deref()),
+        render(e.append.as_deref())
+    )
+}
+
+/// Render Markdown article content to HTML, sanitized as it may come from any feed.
+pub(crate) fn markdown_to_html(markdown: &str) -> String {
+    let mut body = String::new();
+    html::push_html(&mut body, Parser::new(markdown));
+    ammonia::clean(&body)
+}
+
+/// File names of articles exported one per file: `{date}-{title slug}.{ext}`. Articles
+/// sharing a name are told apart by their id (`{date}-{title slug}-{id}.{ext}`), so the
+/// name of a file does not depend on the order or the other articles of an export.
+/// Same order as `articles`.
+pub(crate) fn article_filenames(articles: &[Article], ext: &str) -> Vec<String> {
+    let stems: Vec<String> = articles
+        .iter()
+        .map(|article| {
+            let date = DateTime::<Utc>::from_timestamp(article.timestamp, 0)
+                .unwrap_or_default()
+                .format("%Y-%m-%d");
+            let title_slug = slugify(&article.title);
+            if title_slug.is_empty() {
+                format!("{date}-{}", article.id)
+            } else {
+                format!("{date}-{title_slug}")
+            }
+        })
+        .collect();
+    let mut counts: HashMap<&str, usize> = HashMap::new();
+    for stem in &stems {
+        *counts.entry(stem).or_default() += 1;
+    }
+    stems
+        .iter()
+        .zip(articles)
+        .map(|(stem, article)| {
+            if counts[stem.as_str()] > 1 {
+                format!("{stem}-{:x}.{ext}", article.id)
+            } else {
+                format!("{stem}.{ext}")
+            }
+        })
+        .collect()
+}
+
+/// Articles grouped by the period of their date, in chronological order of the periods
+/// named `2024-01-15` (daily, the default), `2024-W03` (weekly, ISO week) or `2024-01`
+/// (monthly). Articles keep their order within a period.
+pub(crate) fn group_by_period(
+    articles: &[Article],
+    strategy: ExportStrategy,
+) -> BTreeMap<String, Vec<&Article>> {
+    let format = match strategy {
+        ExportStrategy::Weekly => "%G-W%V",
+        ExportStrategy::Monthly => "%Y-%m",
+        _ => "%Y-%m-%d",
+    };
+    let mut by_period: BTreeMap<String, Vec<&Article>> = BTreeMap::new();
+    for article in articles {
+        let period = DateTime::<Utc>::from_timestamp(article.timestamp, 0)
+            .unwrap_or_default()
+            .format(format)
+            .to_string();
+        by_period.entry(period).or_default().push(article);
+    }
+    by_period
+}
+
+/// Escape the characters that are special in XML/HTML text and attribute values.
+pub(crate) fn escape_xml(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 pub(crate) trait Exporter {
     /// `articles`:     items to export.
-    /// `title`:        channel/document title (group or feed name).
-    /// `link`:         canonical URL of the channel (base URL of the output site).
-    /// `destination`:  for Monolithic, path to the output file; for Individual/Daily, path to the output directory.
+    /// `channel`:      title, canonical link and metadata of the channel/document (group or
+    ///                 feed alone); exporters emit the metadata their format supports.
+    /// `destination`:  for Monolithic, path to the output file; for the other strategies, path
+    ///                 to the output directory.
     /// `enrichments`:  per-feed enrichment config keyed by `Article::feed_id`.
     ///                 RSS, Atom and JSON exporters inject the rendered prepend/append;
     ///                 other exporters may ignore it.
     fn generate(
         &self,
         articles: &[Article],
-        title: &str,
-        link: &str,
+        channel: &Channel,
         destination: &Path,
         enrichments: &HashMap<u64, Enrichment>,
     ) -> Result<(), FrustError>;
@@ -8,7 +8,7 @@ use tracing::info;
 
 use crate::{error::FrustError, model::Article};
 
-use super::{Enrichment, Exporter, render_template};
+use super::{Channel, Enrichment, Exporter, enrich, markdown_to_html};
 
 pub(crate) struct RssExporter;
 
@@ -16,8 +16,7 @@ impl Exporter for RssExporter {
     fn generate(
         &self,
         articles: &[Article],
-        title: &str,
-        link: &str,
+        channel: &Channel,
         destination: &Path,
         enrichments: &HashMap<u64, Enrichment>,
     ) -> Result<(), FrustError> {
@@ -36,6 +35,10 @@ impl Exporter for RssExporter {
         // <rss version="2.0">
         let mut rss_tag = BytesStart::new("rss");
         rss_tag.push_attribute(("version", "2.0"));
+        rss_tag.push_attribute(("xmlns:content", "http://purl.org/rss/1.0/modules/content/"));
+        if channel.self_url.is_some() {
+            rss_tag.push_attribute(("xmlns:atom", "http://www.w3.org/2005/Atom"));
+        }
         writer
             .write_event(Event::Start(rss_tag))
             .map_err(|e| FrustError::Export(e.to_string()))?;
@@ -45,9 +48,7 @@ impl Exporter for RssExporter {
             .write_event(Event::Start(BytesStart::new("channel")))
             .map_err(|e| FrustError::Export(e.to_string()))?;
 
-        write_text_element(&mut writer, "title", title)?;
-        write_text_element(&mut writer, "link", link)?;
-        write_text_element(&mut writer, "description", title)?;
+        write_channel(&mut writer, channel)?;
 
         for article in articles {
             write_item(&mut writer, article, enrichments.get(&article.feed_id))?;
@@ -86,6 +87,48 @@ fn write_text_element<W: std::io::Write>(
     Ok(())
 }
 
+fn write_channel<W: std::io::Write>(
+    writer: &mut Writer<W>,
+    channel: &Channel,
+) -> Result<(), FrustError> {
+    write_text_element(writer, "title", &channel.title)?;
+    write_text_element(writer, "link", channel.home_page())?;
+    // <atom:link rel="self"/>, recommended by the RSS Advisory Board
+    if let Some(self_url) = &channel.self_url {
+        let mut link_tag = BytesStart::new("atom:link");
+        link_tag.push_attribute(("href", self_url.as_str()));
+        link_tag.push_attribute(("rel", "self"));
+        link_tag.push_attribute(("type", "application/rss+xml"));
+        writer
+            .write_event(Event::Empty(link_tag))
+            .map_err(|e| FrustError::Export(e.to_string()))?;
+    }
+    write_text_element(
+        writer,
+        "description",
+        channel.description.as_deref().unwrap_or(&channel.title),
+    )?;
+    if let Some(language) = &channel.language {
+        write_text_element(writer, "language", language)?;
+    }
+    // RSS 2.0 expects an email address, optionally followed by the name
+    if let Some(author) = channel.author.as_deref().filter(|a| a.contains('@')) {
+        write_text_element(writer, "managingEditor", author)?;
+    }
+    if let Some(icon) = &channel.icon {
+        writer
+            .write_event(Event::Start(BytesStart::new("image")))
+            .map_err(|e| FrustError::Export(e.to_string()))?;
+        write_text_element(writer, "url", icon)?;
+        write_text_element(writer, "title", &channel.title)?;
+        write_text_element(writer, "link", channel.home_page())?;
+        writer
+            .write_event(Event::End(BytesEnd::new("image")))
+            .map_err(|e| FrustError::Export(e.to_string()))?;
+    }
+    Ok(())
+}
+
 fn write_item<W: std::io::Write>(
     writer: &mut Writer<W>,
     article: &Article,
@@ -101,23 +144,12 @@ fn write_item<W: std::io::Write>(
 
     let base = article.summary.as_deref().unwrap_or(&article.content);
     if !base.is_empty() || enrichment.is_some() {
-        let description = match enrichment {
-            Some(e) => {
-                let pre = e
-                    .prepend
-                    .as_deref()
-                    .map(|t| render_template(t, e, article))
-                    .unwrap_or_default();
-                let app = e
-                    .append
-                    .as_deref()
-                    .map(|t| render_template(t, e, article))
-                    .unwrap_or_default();
-                format!("{pre}{base}{app}")
-            }
-            None => base.to_string(),
-        };
-        write_text_element(writer, "description", &description)?;
+        write_text_element(writer, "description", &enrich(base, enrichment, article))?;
+    }
+    // Full content rendered to HTML, the description stays plain text
+    if !article.content.is_empty() {
+        let html = markdown_to_html(&enrich(&article.content, enrichment, article));
+        write_text_element(writer, "content:encoded", &html)?;
     }
 
     // pubDate in RFC 2822 format
@@ -193,8 +225,7 @@ mod tests {
         RssExporter
             .generate(
                 &[],
-                "Empty Feed",
-                "https://example.com",
+                &Channel::new("Empty Feed", "https://example.com"),
                 &dest,
                 &no_enrichment(),
             )
@@ -213,8 +244,7 @@ mod tests {
         RssExporter
             .generate(
                 &articles,
-                "My Feed",
-                "https://example.com",
+                &Channel::new("My Feed", "https://example.com"),
                 &dest,
                 &no_enrichment(),
             )
@@ -224,12 +254,75 @@ mod tests {
         assert!(xml.contains("<link>https://example.com/1</link>"));
     }
 
+    #[test]
+    fn test_rss_channel_metadata() {
+        let dir = TempDir::new().unwrap();
+        let dest = output_path(&dir, "feed.xml");
+        let channel = Channel {
+            description: Some("Nouvelles".to_string()),
+            language: Some("fr".to_string()),
+            icon: Some("https://example.com/icon.png".to_string()),
+            author: Some("news@example.com (Jane)".to_string()),
+            site_url: Some("https://example.com/".to_string()),
+            ..Channel::new("News", "/news")
+        };
+        RssExporter
+            .generate(&[], &channel, &dest, &no_enrichment())
+            .unwrap();
+        let xml = read_xml(&dest);
+        assert!(xml.contains("<link>https://example.com/</link>"));
+        assert!(xml.contains("<description>Nouvelles</description>"));
+        assert!(xml.contains("<language>fr</language>"));
+        assert!(xml.contains("<managingEditor>news@example.com (Jane)</managingEditor>"));
+        assert!(xml.contains("<url>https://example.com/icon.png</url>"));
+
+        // Not an email address: no managingEditor
+        let channel = Channel {
+            author: Some("Jane".to_string()),
+            ..channel
+        };
+        RssExporter
+            .generate(&[], &channel, &dest, &no_enrichment())
+            .unwrap();
+        assert!(!read_xml(&dest).contains("managingEditor"));
+    }
+
+    #[test]
+    fn test_rss_self_link() {
+        let dir = TempDir::new().unwrap();
+        let dest = output_path(&dir, "feed.xml");
+        let channel = Channel {
+            self_url: Some("https://rss.example.org/news.xml".to_string()),
+            ..Channel::new("News", "https://rss.example.org/news.xml")
+        };
+        RssExporter
+            .generate(&[], &channel, &dest, &no_enrichment())
+            .unwrap();
+        let xml = read_xml(&dest);
+        assert!(xml.contains("xmlns:atom=\"http://www.w3.org/2005/Atom\""));
+        assert!(xml.contains("<link>https://rss.example.org/news.xml</link>"));
+        assert!(xml.contains(
+            "<atom:link href=\"https://rss.example.org/news.xml\" rel=\"self\" type=\"application/rss+xml\"/>"
+        ));
+
+        // No base URL, no self link
+        RssExporter
+            .generate(&[], &Channel::new("News", "/news"), &dest, &no_enrichment())
+            .unwrap();
+        assert!(!read_xml(&dest).contains("atom"));
+    }
+
     #[test]
     fn test_rss_creates_parent_dirs() {
         let dir = TempDir::new().unwrap();
         let dest = dir.path().join("sub").join("dir").join("feed.xml");
         RssExporter
-            .generate(&[], "Feed", "https://example.com", &dest, &no_enrichment())
+            .generate(
+                &[],
+                &Channel::new("Feed", "https://example.com"),
+                &dest,
+                &no_enrichment(),
+            )
             .unwrap();
         assert!(dest.exists());
     }
@@ -247,8 +340,7 @@ mod tests {
         RssExporter
             .generate(
                 &[article],
-                "Podcast Feed",
-                "https://example.com",
+                &Channel::new("Podcast Feed", "https://example.com"),
                 &dest,
                 &no_enrichment(),
             )
@@ -273,8 +365,7 @@ mod tests {
         RssExporter
             .generate(
                 &articles,
-                "Feed",
-                "https://example.com",
+                &Channel::new("Feed", "https://example.com"),
                 &dest,
                 &no_enrichment(),
             )
@@ -283,6 +374,30 @@ mod tests {
         assert!(xml.contains("<pubDate>"), "pubDate element missing");
     }
 
+    #[test]
+    fn test_rss_content_encoded_is_sanitized_html() {
+        let dir = TempDir::new().unwrap();
+        let dest = output_path(&dir, "feed.xml");
+        let mut article = make_article(1, "Rich", "https://example.com/r", 0);
+        article.summary = Some("Plain **summary**".to_string());
+        article.content = "Some **bold** text<script>alert(1)</script>".to_string();
+        RssExporter
+            .generate(
+                &[article],
+                &Channel::new("Feed", "https://example.com"),
+                &dest,
+                &no_enrichment(),
+            )
+            .unwrap();
+        let xml = read_xml(&dest);
+        assert!(xml.contains("xmlns:content=\"http://purl.org/rss/1.0/modules/content/\""));
+        assert!(xml.contains("<description>Plain **summary**</description>"));
+        assert!(
+            xml.contains("<content:encoded>&lt;p&gt;Some &lt;strong&gt;bold&lt;/strong&gt; text")
+        );
+        assert!(!xml.contains("alert"));
+    }
+
     #[test]
     fn test_rss_enrichment_prepend_append() {
         let dir = TempDir::new().unwrap();
@@ -307,8 +422,7 @@ mod tests {
         RssExporter
             .generate(
                 &[article],
-                "Feed",
-                "https://example.com",
+                &Channel::new("Feed", "https://example.com"),
                 &dest,
                 &enrichments,
             )
@@ -316,6 +430,8 @@ mod tests {
         let xml = read_xml(&dest);
         assert!(xml.contains("Read on Test Feed"), "prepend missing");
         assert!(xml.contains("getpocket.com"), "append missing");
+        // Templates are rendered as HTML links in the full content
+        assert!(xml.contains("&lt;a href=&quot;https://example.com/1&quot;"));
         assert!(xml.contains("Body text"), "original content missing");
     }
 }
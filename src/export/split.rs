@@ -0,0 +1,311 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+    path::Path,
+};
+
+use regex::Regex;
+use tracing::info;
+
+use crate::{
+    error::FrustError,
+    model::{Article, ExportStrategy},
+};
+
+use super::{Channel, Enrichment, Exporter, article_filenames, group_by_period};
+
+/// Writes a single-document format (RSS, Atom, EPUB) once per file of a non-monolithic
+/// strategy: one feed or book per article, or per day, week or month, in the
+/// `destination` directory. The files of the strategy left by previous runs are removed,
+/// e.g. the ones of expired articles.
+pub(crate) struct SplitExporter {
+    pub(crate) inner: Box<dyn Exporter>,
+    pub(crate) strategy: ExportStrategy,
+    /// Extension of the written files, e.g. `atom`
+    pub(crate) extension: String,
+}
+
+impl Exporter for SplitExporter {
+    fn generate(
+        &self,
+        articles: &[Article],
+        channel: &Channel,
+        destination: &Path,
+        enrichments: &HashMap<u64, Enrichment>,
+    ) -> Result<(), FrustError> {
+        // (file name, title, articles) of every file
+        let parts: Vec<(String, String, Vec<Article>)> = match self.strategy {
+            ExportStrategy::Monolithic => {
+                return self
+                    .inner
+                    .generate(articles, channel, destination, enrichments);
+            }
+            ExportStrategy::Individual => article_filenames(articles, &self.extension)
+                .into_iter()
+                .zip(articles)
+                .map(|(filename, article)| (filename, article.title.clone(), vec![article.clone()]))
+                .collect(),
+            _ => group_by_period(articles, self.strategy)
+                .into_iter()
+                .map(|(period, period_articles)| {
+                    (
+                        format!("{period}.{}", self.extension),
+                        format!("{} – {period}", channel.title),
+                        period_articles.into_iter().cloned().collect(),
+                    )
+                })
+                .collect(),
+        };
+        fs::create_dir_all(destination)?;
+        info!(
+            "Splitting {} article(s) into {} file(s) in {}",
+            articles.len(),
+            parts.len(),
+            destination.display()
+        );
+
+        let mut written = HashSet::new();
+        for (filename, title, part) in parts {
+            let part_channel = Channel {
+                title,
+                link: format!("{}/{}", channel.link.trim_end_matches('/'), filename),
+                self_url: channel
+                    .self_url
+                    .as_ref()
+                    .map(|url| format!("{}/{}", url.trim_end_matches('/'), filename)),
+                ..channel.clone()
+            };
+            self.inner.generate(
+                &part,
+                &part_channel,
+                &destination.join(&filename),
+                enrichments,
+            )?;
+            written.insert(filename);
+        }
+        self.remove_stale_files(destination, &written)
+    }
+}
+
+impl SplitExporter {
+    /// Names of the files written by the strategy, e.g. `2024-W03.atom` for the weekly one.
+    fn file_pattern(&self) -> Regex {
+        let stem = match self.strategy {
+            ExportStrategy::Individual => r"\d{4}-\d{2}-\d{2}-[a-z0-9-]+",
+            ExportStrategy::Weekly => r"\d{4}-W\d{2}",
+            ExportStrategy::Monthly => r"\d{4}-\d{2}",
+            _ => r"\d{4}-\d{2}-\d{2}",
+        };
+        Regex::new(&format!(r"^{}\.{}$", stem, regex::escape(&self.extension))).unwrap()
+    }
+
+    /// Remove the files named like the ones of the strategy that were not `written`.
+    /// Other files, such as the output of another group, are left alone.
+    fn remove_stale_files(
+        &self,
+        destination: &Path,
+        written: &HashSet<String>,
+    ) -> Result<(), FrustError> {
+        let pattern = self.file_pattern();
+        for entry in fs::read_dir(destination)? {
+            let path = entry?.path();
+            let stale = path.is_file()
+                && path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .is_some_and(|n| pattern.is_match(n) && !written.contains(n));
+            if stale {
+                info!("Removing stale file {}", path.display());
+                fs::remove_file(&path)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+// ── tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{AtomExporter, EpubExporter, RssExporter};
+    use tempfile::TempDir;
+
+    const DAY: i64 = 86_400;
+    /// 2024-01-15 12:00:00 UTC, a Monday
+    const MONDAY: i64 = 1_705_320_000;
+
+    fn make_article(id: u64, title: &str, ts: i64) -> Article {
+        Article {
+            id,
+            feed_id: 1,
+            title: title.to_string(),
+            url: format!("https://example.com/{id}"),
+            content: format!("Content of {title}"),
+            summary: None,
+            timestamp: ts,
+            added_at: ts,
+            is_full_content: false,
+            enclosures: vec![],
+        }
+    }
+
+    fn split(inner: Box<dyn Exporter>, strategy: ExportStrategy, ext: &str) -> SplitExporter {
+        SplitExporter {
+            inner,
+            strategy,
+            extension: ext.to_string(),
+        }
+    }
+
+    fn file_names(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn test_monthly_atom_feeds() {
+        let dir = TempDir::new().unwrap();
+        let dest = dir.path().join("news");
+        let articles = vec![
+            make_article(3, "February", MONDAY + 20 * DAY),
+            make_article(2, "Later in January", MONDAY + DAY),
+            make_article(1, "January", MONDAY),
+        ];
+        let channel = Channel {
+            self_url: Some("https://rss.example.org/news".to_string()),
+            ..Channel::new("News", "https://rss.example.org/news")
+        };
+        split(Box::new(AtomExporter), ExportStrategy::Monthly, "atom")
+            .generate(&articles, &channel, &dest, &HashMap::new())
+            .unwrap();
+
+        assert_eq!(file_names(&dest), vec!["2024-01.atom", "2024-02.atom"]);
+        let january = fs::read_to_string(dest.join("2024-01.atom")).unwrap();
+        assert!(january.contains("<title>News – 2024-01</title>"));
+        assert!(january.contains("<id>https://rss.example.org/news/2024-01.atom</id>"));
+        assert!(january.contains("href=\"https://rss.example.org/news/2024-01.atom\""));
+        assert_eq!(january.matches("<entry>").count(), 2);
+        assert!(!january.contains("February"));
+    }
+
+    #[test]
+    fn test_monolithic_writes_a_single_file() {
+        let dir = TempDir::new().unwrap();
+        let dest = dir.path().join("news.atom");
+        split(Box::new(AtomExporter), ExportStrategy::Monolithic, "atom")
+            .generate(
+                &[make_article(1, "January", MONDAY)],
+                &Channel::new("News", "/news.atom"),
+                &dest,
+                &HashMap::new(),
+            )
+            .unwrap();
+        assert!(dest.is_file());
+    }
+
+    #[test]
+    fn test_weekly_files_named_after_iso_week() {
+        let dir = TempDir::new().unwrap();
+        let dest = dir.path().join("news");
+        let articles = vec![
+            make_article(3, "Next Monday", MONDAY + 7 * DAY),
+            make_article(2, "Sunday", MONDAY + 6 * DAY),
+            make_article(1, "Monday", MONDAY),
+        ];
+        split(Box::new(RssExporter), ExportStrategy::Weekly, "rss")
+            .generate(
+                &articles,
+                &Channel::new("News", "/news"),
+                &dest,
+                &HashMap::new(),
+            )
+            .unwrap();
+        assert_eq!(file_names(&dest), vec!["2024-W03.rss", "2024-W04.rss"]);
+
+        // a week without articles any more is removed, not the other files
+        fs::write(dest.join("2024-01-15.rss"), "kept").unwrap();
+        split(Box::new(RssExporter), ExportStrategy::Weekly, "rss")
+            .generate(
+                &articles[..1],
+                &Channel::new("News", "/news"),
+                &dest,
+                &HashMap::new(),
+            )
+            .unwrap();
+        assert_eq!(file_names(&dest), vec!["2024-01-15.rss", "2024-W04.rss"]);
+    }
+
+    #[test]
+    fn test_daily_and_individual_epubs() {
+        let dir = TempDir::new().unwrap();
+        let articles = vec![
+            make_article(3, "Tomorrow", MONDAY + DAY),
+            make_article(2, "Same title", MONDAY),
+            make_article(1, "Same title", MONDAY),
+        ];
+        let channel = Channel::new("Journal", "/journal");
+
+        let daily = dir.path().join("daily");
+        split(Box::new(EpubExporter), ExportStrategy::Daily, "epub")
+            .generate(&articles, &channel, &daily, &HashMap::new())
+            .unwrap();
+        assert_eq!(
+            file_names(&daily),
+            vec!["2024-01-15.epub", "2024-01-16.epub"]
+        );
+
+        let individual = dir.path().join("individual");
+        split(Box::new(EpubExporter), ExportStrategy::Individual, "epub")
+            .generate(&articles, &channel, &individual, &HashMap::new())
+            .unwrap();
+        assert_eq!(
+            file_names(&individual),
+            vec![
+                "2024-01-15-same-title-1.epub",
+                "2024-01-15-same-title-2.epub",
+                "2024-01-16-tomorrow.epub",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_individual_names_are_stable_and_stale_files_removed() {
+        let dir = TempDir::new().unwrap();
+        let dest = dir.path().join("news");
+        let exporter = split(Box::new(AtomExporter), ExportStrategy::Individual, "atom");
+        let channel = Channel::new("News", "/news");
+        let articles = vec![
+            make_article(0x1b, "Same title", MONDAY + DAY),
+            make_article(0x2c, "Same title", MONDAY + DAY),
+            make_article(0x3d, "Expiring", MONDAY),
+        ];
+        exporter
+            .generate(&articles, &channel, &dest, &HashMap::new())
+            .unwrap();
+        fs::write(dest.join("notes.txt"), "kept").unwrap();
+        // e.g. the output of another group
+        fs::write(dest.join("music.atom"), "kept").unwrap();
+
+        // the first article expired, the others come in another order
+        let articles = vec![articles[1].clone(), articles[0].clone()];
+        exporter
+            .generate(&articles, &channel, &dest, &HashMap::new())
+            .unwrap();
+        assert_eq!(
+            file_names(&dest),
+            vec![
+                "2024-01-16-same-title-1b.atom",
+                "2024-01-16-same-title-2c.atom",
+                "music.atom",
+                "notes.txt",
+            ]
+        );
+        let first = fs::read_to_string(dest.join("2024-01-16-same-title-1b.atom")).unwrap();
+        assert!(first.contains("https://example.com/27"));
+    }
+}
@@ -12,16 +12,16 @@ use zip::{CompressionMethod, ZipWriter, write::SimpleFileOptions};
 use crate::{
     error::FrustError,
     export::atom::write_atom_to,
-    model::{Enrichment, Group},
+    model::{Enrichment, ExportStrategy, Group},
     storage::Storage,
 };
 
 /// Derive the Atom filename that will appear inside the ZIP for a given group.
 ///
 /// Uses the basename of the configured `output` path (e.g. `divers.atom`) when
-/// set, otherwise falls back to `{slug}.atom`.
+/// set to a file, otherwise falls back to `{slug}.atom`.
 fn group_atom_name(group: &Group) -> String {
-    if !group.output.is_empty() {
+    if !group.output.is_empty() && group.strategy == ExportStrategy::Monolithic {
         Path::new(&group.output)
             .file_name()
             .map(|n| n.to_string_lossy().into_owned())
@@ -38,11 +38,12 @@ fn group_atom_name(group: &Group) -> String {
 /// The group/feed structure and the redb paths are derived from the YAML config
 /// at `config_path` (defaults to `"config.yaml"` when called from the CLI).
 pub(crate) fn build_zip_archive(output_path: &str, config_path: &str) -> Result<(), FrustError> {
-    let app = crate::config::load_config_file(config_path.to_string());
+    let mut app = crate::config::load_config_file(config_path.to_string())?;
 
     let articles_path = format!("{}/articles.redb", app.output);
     let states_path = format!("{}/states.redb", app.output);
     let storage = Storage::new(&articles_path, &states_path)?;
+    app.complete_feeds(&storage.load_all_states()?);
 
     if let Some(parent) = Path::new(output_path).parent()
         && !parent.as_os_str().is_empty()
@@ -64,7 +65,7 @@ pub(crate) fn build_zip_archive(output_path: &str, config_path: &str) -> Result<
             enrichments.insert(
                 *feed_id,
                 Enrichment {
-                    feed_title: feed.title.clone(),
+                    feed_title: feed.name().to_string(),
                     feed_url: feed.url.clone(),
                     feed_slug: feed.slug.clone(),
                     feed_page_url: feed.page_url.clone(),
@@ -84,8 +85,7 @@ pub(crate) fn build_zip_archive(output_path: &str, config_path: &str) -> Result<
         write_atom_to(
             &mut writer,
             &articles,
-            &group.title,
-            &group.output,
+            &group.channel(&group.output),
             &enrichments,
         )?;
         let xml_bytes = writer.into_inner();
@@ -126,7 +126,6 @@ pub(crate) fn build_zip_archive(output_path: &str, config_path: &str) -> Result<
 mod tests {
     use std::{collections::HashSet, io::Read};
 
-    use slug::slugify;
     use twox_hash::XxHash3_64;
     use zip::ZipArchive;
 
@@ -163,6 +162,12 @@ mod tests {
     #[test]
     fn test_group_atom_name_falls_back_to_slug() {
         assert_eq!(group_atom_name(&make_group("my-feed", "")), "my-feed.atom");
+        // The output is a directory
+        let journal = Group {
+            strategy: ExportStrategy::Daily,
+            ..make_group("journal", "journal")
+        };
+        assert_eq!(group_atom_name(&journal), "journal.atom");
     }
 
     // ── helpers ───────────────────────────────────────────────────────────────
@@ -195,10 +200,10 @@ mod tests {
     }
 
     /// Compute the feed_id the same way config loading does:
-    /// slugify the URL hostname, then XXH3-hash it.
+    /// derive the feed slug from the URL, then XXH3-hash it.
     fn feed_id_for_url(url: &str) -> u64 {
         let parsed = url::Url::parse(url).unwrap();
-        let slug = slugify(parsed.host_str().unwrap_or("no-host"));
+        let slug = crate::config::feed_slug("", &parsed);
         XxHash3_64::oneshot(slug.as_bytes())
     }
 
@@ -28,6 +28,8 @@ pub(crate) mod utils;
 
 const DEFAULT_HTTP_TIMEOUT: u8 = 10;
 const DEFAULT_RETRIEVE_SERVER_MEDIA: bool = false;
+/// Days after which an entry dropped by the retention or the filters is evaluated again
+const REJECTED_MAX_AGE_DAYS: i64 = 30;
 static START_TIME: OnceLock<DateTime<Utc>> = OnceLock::new();
 
 fn create_output_structure(app: &App) -> Result<(), FrustError> {
@@ -63,7 +65,13 @@ async fn run_aggregator(config_path: &str) -> ExitCode {
     }
 
     let mut exit_code = ExitCode::SUCCESS;
-    let app = crate::config::load_config_file(config_path.to_string());
+    let app = match crate::config::load_config_file(config_path.to_string()) {
+        Ok(app) => app,
+        Err(e) => {
+            tracing::error!("{}", e);
+            return ExitCode::FAILURE;
+        }
+    };
     START_TIME.set(Utc::now()).unwrap();
     std::fs::create_dir_all(app.output.clone()).unwrap_or_else(|e| {
         tracing::error!("Unable to create output directory: {}", e);
@@ -79,6 +87,11 @@ async fn run_aggregator(config_path: &str) -> ExitCode {
         let articles_path = format!("{}/articles.redb", app.output);
         let states_path = format!("{}/states.redb", app.output);
         if let Ok(storage) = Storage::new(&articles_path, &states_path) {
+            match storage.migrate_feed_ids(&crate::config::legacy_feed_ids(&app)) {
+                Ok(0) => {}
+                Ok(n) => tracing::info!("Migrated {} article(s) to unique feed ids", n),
+                Err(e) => tracing::warn!("Feed id migration failed: {}", e),
+            }
             let feed_retentions: HashMap<u64, u16> = app
                 .groups
                 .values()
@@ -90,6 +103,14 @@ async fn run_aggregator(config_path: &str) -> ExitCode {
                 Ok(n) => tracing::info!("Cleaned {} expired article(s)", n),
                 Err(e) => tracing::warn!("Article cleanup failed: {}", e),
             }
+            match storage.delete_stale_rejected(
+                now_ts - REJECTED_MAX_AGE_DAYS * 86_400,
+                &crate::processing::filter::feed_fingerprints(&app),
+            ) {
+                Ok(0) => {}
+                Ok(n) => tracing::info!("Forgot {} rejected article(s) to evaluate again", n),
+                Err(e) => tracing::warn!("Rejected articles cleanup failed: {}", e),
+            }
             let media_dir = format!("{}/media", app.output);
             match storage.purge_orphaned_media(&media_dir) {
                 Ok(0) => info!("No media to delete"),
@@ -140,6 +161,19 @@ async fn main() -> ExitCode {
                 return ExitCode::FAILURE;
             }
         }
+        Some(Command::Check(ref o)) => {
+            let config_path = opts.config.as_deref().unwrap_or("config.yaml");
+            if let Err(e) = command::check_config(o, config_path) {
+                tracing::error!("{}", e);
+                return ExitCode::FAILURE;
+            }
+        }
+        Some(Command::Discover(ref o)) => {
+            if let Err(e) = command::discover(o).await {
+                tracing::error!("{}", e);
+                return ExitCode::FAILURE;
+            }
+        }
         None => {
             let config_path = opts.config.as_deref().unwrap_or("config.yaml");
             return run_aggregator(config_path).await;
@@ -1,6 +1,9 @@
-use std::collections::HashMap;
+use std::{
+    collections::HashMap,
+    path::{Component, Path},
+};
 
-use regex::RegexSet;
+use regex::{Regex, RegexSet};
 use rkyv::{Archive, Deserialize, Serialize};
 
 use crate::{DEFAULT_HTTP_TIMEOUT, DEFAULT_RETRIEVE_SERVER_MEDIA};
@@ -8,6 +11,8 @@ use crate::{DEFAULT_HTTP_TIMEOUT, DEFAULT_RETRIEVE_SERVER_MEDIA};
 #[derive(Debug, Clone)]
 pub(crate) struct App {
     pub(crate) output: String,
+    /// Public URL of the output directory, without trailing slash
+    pub(crate) base_url: Option<String>,
     /// Timeout in seconds
     pub(crate) timeout: u8,
     pub(crate) retrieve_media_server: bool,
@@ -15,6 +20,14 @@ pub(crate) struct App {
     pub(crate) min_refresh_time: i64,
     // https://stackoverflow.com/questions/22155130/
     pub(crate) workers: usize,
+    /// Maximum number of simultaneous requests to a same host
+    pub(crate) max_per_host: usize,
+    /// Minimum delay in milliseconds between the starts of two requests to a same host
+    pub(crate) host_delay_ms: u64,
+    /// Retries of a request after a transient failure (connection, timeout, 429, 502-504)
+    pub(crate) retries: u32,
+    /// Wait in milliseconds before the first retry, doubled for each following one
+    pub(crate) retry_delay_ms: u64,
     /// List of filters, the u64 key is a XXH3 of the slug
     pub(crate) filters: HashMap<u64, Filter>,
     /// All groups, the key is a xxh3 of the slug
@@ -29,16 +42,64 @@ pub(crate) struct App {
     pub(crate) enrichment_prepend: Option<String>,
     /// Mustache-style template injected after each article's content at export time (app-level default).
     pub(crate) enrichment_append: Option<String>,
+    /// How article content is retrieved (app-level default).
+    pub(crate) content_mode: ContentMode,
+    /// HTTP settings of the requests (app-level default).
+    pub(crate) http: HttpSettings,
+}
+
+impl App {
+    /// Complete every feed with the metadata of its stored state, for the exports.
+    pub(crate) fn complete_feeds(&mut self, states: &HashMap<u64, FeedState>) {
+        for (feed_id, feed) in self.groups.values_mut().flat_map(|g| g.feeds.iter_mut()) {
+            if let Some(state) = states.get(feed_id) {
+                feed.complete_from_state(state);
+            }
+        }
+    }
+
+    /// Path of an output file relative to the output directory, with `/` separators, for
+    /// an output configured relative to the directory or absolute inside it. `None` for a
+    /// file outside the directory, e.g. `../site/news.atom`.
+    pub(crate) fn output_href(&self, output: &str) -> Option<String> {
+        let path = Path::new(output);
+        let relative = if path.is_absolute() {
+            path.strip_prefix(&self.output).ok()?
+        } else {
+            path
+        };
+        let mut parts = Vec::new();
+        for component in relative.components() {
+            match component {
+                Component::Normal(part) => parts.push(part.to_string_lossy()),
+                Component::CurDir => {}
+                _ => return None,
+            }
+        }
+        Some(parts.join("/"))
+    }
+
+    /// Absolute URL of an output file, see [`Self::output_href`]. `None` without
+    /// `base_url` or for a file outside the output directory.
+    pub(crate) fn public_url(&self, output: &str) -> Option<String> {
+        let base_url = self.base_url.as_ref()?;
+        Some(format!("{}/{}", base_url, self.output_href(output)?))
+    }
 }
 
 impl Default for App {
     fn default() -> Self {
         Self {
             output: std::env::current_dir().unwrap().display().to_string(),
+            base_url: None,
             retrieve_media_server: DEFAULT_RETRIEVE_SERVER_MEDIA,
             timeout: DEFAULT_HTTP_TIMEOUT,
             min_refresh_time: 600,
             workers: std::thread::available_parallelism().unwrap().get(),
+            max_per_host: 2,
+            host_delay_ms: 0,
+            retries: 2,
+            retry_delay_ms: 1000,
             filters: HashMap::with_capacity(0),
             groups: HashMap::with_capacity(0),
             retention: 0,
@@ -46,6 +107,8 @@ impl Default for App {
             media_max_size: 0,
             enrichment_prepend: None,
             enrichment_append: None,
+            content_mode: ContentMode::Default,
+            http: HttpSettings::default(),
         }
     }
 }
@@ -58,11 +121,19 @@ pub(crate) struct Group {
     /// All feeds, the key is a xxh3 of the slug
     pub(crate) feeds: HashMap<u64, Feed>,
     /// Applied filter, from the first in the list to the last
-    pub(crate) filters: Vec<u64>,
-    /// Set this output file path if you want to aggregate the feeds in the group
+    pub(crate) filters: Vec<FilterRule>,
+    /// Set this output file path if you want to aggregate the feeds in the group, a
+    /// directory with a non-monolithic `strategy`
     pub(crate) output: String,
+    /// How the articles of the group are split into output files
+    pub(crate) strategy: ExportStrategy,
+    /// Format of the output files (`rss`, `atom`, `json`, `md` or `epub`), the extension
+    /// of `output` when missing
+    pub(crate) format: Option<String>,
     /// Article retention in days
     pub(crate) retention: u16,
+    /// Minimum time in seconds between two checks of a feed
+    pub(crate) min_refresh_time: i64,
     /// Download media assets (images, audio, video) to `media/<xxh3>.<ext>`
     pub(crate) media: bool,
     /// Maximum asset size in bytes to download (0 = no limit)
@@ -71,12 +142,71 @@ pub(crate) struct Group {
     pub(crate) enrichment_prepend: Option<String>,
     /// Mustache-style template injected after each article's content at export time (group-level default).
     pub(crate) enrichment_append: Option<String>,
+    /// How article content is retrieved (group-level default).
+    pub(crate) content_mode: ContentMode,
+    /// HTTP settings of the requests (group-level default).
+    pub(crate) http: HttpSettings,
+    /// Description of the group outputs
+    pub(crate) description: Option<String>,
+    /// Language of the group outputs, e.g. `fr` or `en-US`
+    pub(crate) language: Option<String>,
+    /// URL of the image of the group outputs
+    pub(crate) icon: Option<String>,
+    /// Author of the group outputs
+    pub(crate) author: Option<String>,
+    /// Home page of the website the group is about
+    pub(crate) site_url: Option<String>,
 }
 
-#[repr(u8)]
+impl Group {
+    /// Channel metadata of the group output, written at `link`.
+    pub(crate) fn channel(&self, link: &str) -> Channel {
+        let title = if self.title.is_empty() {
+            &self.slug
+        } else {
+            &self.title
+        };
+        Channel {
+            description: self.description.clone(),
+            language: self.language.clone(),
+            icon: self.icon.clone(),
+            author: self.author.clone(),
+            site_url: self.site_url.clone(),
+            ..Channel::new(title, link)
+        }
+    }
+}
+
+/// HTTP settings of the feed fetches and page scraping, inherited app → group → feed.
+#[derive(Debug, Default, PartialEq, Clone)]
+pub(crate) struct HttpSettings {
+    /// Replaces the default `frust/<version>` user agent
+    pub(crate) user_agent: Option<String>,
+    /// Extra request headers, as name and value
+    pub(crate) headers: Vec<(String, String)>,
+    pub(crate) auth: Option<HttpAuth>,
+    /// HTTP, HTTPS or SOCKS proxy URL
+    pub(crate) proxy: Option<String>,
+    /// Cookies sent in the `Cookie` header, as name and value
+    pub(crate) cookies: Vec<(String, String)>,
+}
+
+/// Credentials sent with the requests, read from the config, an environment variable
+/// or a file.
 #[derive(Debug, PartialEq, Clone)]
+pub(crate) enum HttpAuth {
+    Basic {
+        username: String,
+        password: Option<String>,
+    },
+    Bearer(String),
+}
+
+#[repr(u8)]
+#[derive(Debug, Default, PartialEq, Clone)]
 pub(crate) enum ContentMode {
     /// Default content in the field
+    #[default]
     Default,
     /// No content, only keep the title
     No,
@@ -90,6 +220,25 @@ pub(crate) enum ContentMode {
     LinksOnly,
 }
 
+impl TryFrom<&str> for ContentMode {
+    type Error = String;
+
+    /// Parse the `content_mode` value used in the YAML configuration.
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "default" => Ok(Self::Default),
+            "no" => Ok(Self::No),
+            "brief" => Ok(Self::Brief),
+            "force" => Ok(Self::Force),
+            "links_only" => Ok(Self::LinksOnly),
+            _ => Err(format!(
+                "unknown content mode '{}' (expected one of: default, no, brief, force, links_only)",
+                value
+            )),
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 pub(crate) struct Feed {
     pub(crate) title: String,
@@ -104,11 +253,13 @@ pub(crate) struct Feed {
     // /// Identify group by its hash
     // pub(crate) group: Option<u64>,
     /// Applied filter, from the first in the list to the last
-    pub(crate) filters: Vec<u64>,
-    /// Output file
-    pub(crate) output: String,
+    pub(crate) filters: Vec<FilterRule>,
+    /// Output file of the feed alone, written in addition to the group aggregate
+    pub(crate) output: Option<String>,
     /// Article retention in days
     pub(crate) retention: u16,
+    /// Minimum time in seconds between two checks of a feed
+    pub(crate) min_refresh_time: i64,
     /// Download media assets (images, audio, video) to `media/<xxh3>.<ext>`
     pub(crate) media: bool,
     /// Maximum asset size in bytes to download (0 = no limit)
@@ -119,11 +270,42 @@ pub(crate) struct Feed {
     pub(crate) enrichment_prepend: Option<String>,
     /// Mustache-style template injected after each article's content at export time.
     pub(crate) enrichment_append: Option<String>,
+    /// HTTP settings of the feed fetch and page scraping
+    pub(crate) http: HttpSettings,
 }
 
-/// Filter structure. The name is not kept because it is only used during filter loading in order to help the user to find errors quickly.
+impl Feed {
+    /// Name of the feed in logs and pages: its title, or else its URL.
+    pub(crate) fn name(&self) -> &str {
+        [&self.title, &self.url, &self.page_url]
+            .into_iter()
+            .find(|s| !s.is_empty())
+            .map_or("", String::as_str)
+    }
+
+    /// Fill in the title, page URL and discovered URL the configuration leaves out
+    /// with the ones learnt from the feed document and kept in its state.
+    pub(crate) fn complete_from_state(&mut self, state: &FeedState) {
+        let fields = [
+            (&mut self.title, &state.title),
+            (&mut self.page_url, &state.page_url),
+            (&mut self.url, &state.discovered_url),
+        ];
+        for (field, learnt) in fields {
+            if field.is_empty()
+                && let Some(learnt) = learnt
+            {
+                field.clone_from(learnt);
+            }
+        }
+    }
+}
+
+/// Filter structure, referenced by the XXH3 hash of its slug in groups and feeds.
 #[derive(Debug, Clone)]
 pub(crate) struct Filter {
+    /// Name of the filter, kept to tell which filter dropped an article
+    pub(crate) slug: String,
     /// Text or regex.
     ///
     /// If `expressions=["Elon Musk", "Tesla"]`, it will search the exact `Elon Musk` then `Tesla`. It will not be `Elon`, `Musk` and `Tesla`.
@@ -133,20 +315,123 @@ pub(crate) struct Filter {
     pub(crate) is_regex: bool,
     /// if all sentences and regexes must match, default `false
     pub(crate) must_match_all: bool,
+    /// Conditions on the entry data other than its text, combined with the expressions
+    /// according to `must_match_all`
+    pub(crate) predicates: Vec<Predicate>,
     // scopes
     pub(crate) filter_in_title: bool,
     pub(crate) filter_in_summary: bool,
     pub(crate) filter_in_content: bool,
-    /// `true` to only keep article matching otherwise `false` to exclude
+    /// `true` to only keep article matching otherwise `false` to exclude (`is_include` in the
+    /// config file is an alias). When several keep filters are chained, an article is kept
+    /// as soon as it matches one of them; exclude filters always win over keep filters.
     pub(crate) keep: bool,
 }
 
+/// Entry data a [`Predicate`] can test.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub(crate) enum PredicateField {
+    /// Names of the authors
+    Author,
+    /// Terms and labels of the categories/tags
+    Category,
+    /// Host of the entry links
+    LinkDomain,
+    /// MIME type of the enclosures
+    EnclosureType,
+    /// Days since the entry was published (or updated)
+    Age,
+    /// Number of characters of the content (or summary when there is no content)
+    ContentLength,
+    /// Publication (or update) date, as a UNIX timestamp
+    Published,
+}
+
+impl PredicateField {
+    /// Numeric fields are compared with numbers, the others with (case insensitive) text.
+    pub(crate) fn is_numeric(&self) -> bool {
+        matches!(self, Self::Age | Self::ContentLength | Self::Published)
+    }
+}
+
+impl TryFrom<&str> for PredicateField {
+    type Error = String;
+
+    /// Parse the `field` value of a predicate in the YAML configuration.
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "author" => Ok(Self::Author),
+            "category" => Ok(Self::Category),
+            "link_domain" => Ok(Self::LinkDomain),
+            "enclosure_type" => Ok(Self::EnclosureType),
+            "age" => Ok(Self::Age),
+            "content_length" => Ok(Self::ContentLength),
+            "published" => Ok(Self::Published),
+            _ => Err(format!(
+                "unknown predicate field '{}' (expected one of: author, category, link_domain, enclosure_type, age, content_length, published)",
+                value
+            )),
+        }
+    }
+}
+
+/// Comparison done by a [`Predicate`]. Text values are lowercased.
+#[derive(Debug, Clone)]
+pub(crate) enum PredicateOp {
+    Equals(String),
+    In(Vec<String>),
+    StartsWith(String),
+    Regex(Regex),
+    NumberEquals(i64),
+    GreaterThan(i64),
+    LessThan(i64),
+}
+
+/// Condition on the entry data, e.g. `{ field: author, op: not_in, value: [a, b] }`.
+///
+/// Fields with several values (authors, categories…) match when one of the values
+/// satisfies the operator. Negated operators (`not_equals`, `not_in`, `not_starts_with`)
+/// match when none does, including when the entry has no value at all.
+#[derive(Debug, Clone)]
+pub(crate) struct Predicate {
+    pub(crate) field: PredicateField,
+    pub(crate) op: PredicateOp,
+    pub(crate) negate: bool,
+}
+
+/// Boolean combination of named filters. The `keep` flag of the filters is not used
+/// here: only whether they match the entry.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) enum FilterExpr {
+    /// Matches when the filter with this id (xxh3 of its slug) matches
+    Filter(u64),
+    /// Matches when at least one sub-expression matches
+    Any(Vec<FilterExpr>),
+    /// Matches when every sub-expression matches
+    All(Vec<FilterExpr>),
+    /// Matches when the sub-expression does not
+    Not(Box<FilterExpr>),
+}
+
+/// An item of the `filters` list of a group or a feed.
+#[derive(Debug, PartialEq, Clone)]
+pub(crate) enum FilterRule {
+    /// Filter referenced by its slug: it keeps or excludes according to its `keep` flag
+    Named(u64),
+    /// `keep: <expression>`, only the entries matching the expression are kept
+    Keep(FilterExpr),
+    /// `exclude: <expression>`, the entries matching the expression are dropped
+    Exclude(FilterExpr),
+}
+
 impl Default for Filter {
     fn default() -> Self {
         Self {
+            slug: String::new(),
             expressions: Vec::new(),
             is_regex: false,
             must_match_all: false,
+            predicates: Vec::new(),
             filter_in_title: true,
             filter_in_summary: true,
             filter_in_content: true,
@@ -186,8 +471,9 @@ pub(crate) struct Enclosure {
     pub(crate) length: Option<u64>,
 }
 
-#[derive(Archive, Deserialize, Serialize, Debug, Clone)]
+#[derive(Archive, Deserialize, Serialize, Debug, Clone, Default, PartialEq)]
 // rkyv needs this to handle byte alignment and validation
+// Stored with a schema version, see `storage::STATE_VERSION`
 pub(crate) struct FeedState {
     pub(crate) last_etag: Option<String>,
     // rkyv works best with fixed-size types or its own primitives
@@ -195,16 +481,112 @@ pub(crate) struct FeedState {
     pub(crate) last_check_ts: Option<i64>,
     pub(crate) last_modified_ts: Option<i64>,
     pub(crate) last_http_status: Option<u16>,
+    /// Failed checks since the last successful one
+    pub(crate) consecutive_failures: u32,
+    /// Message of the last failure, cleared on success
+    pub(crate) last_error: Option<String>,
+    pub(crate) last_success_ts: Option<i64>,
+    /// The feed is not checked before this time (backoff after failures)
+    pub(crate) next_check_ts: Option<i64>,
+    /// Retries needed by the last check
+    pub(crate) retries: u32,
+    /// Feed URL found on the page of a feed declared with a `page_url` only
+    pub(crate) discovered_url: Option<String>,
+    /// Title of the feed document, used when the configuration has none
+    pub(crate) title: Option<String>,
+    /// `<link>` of the feed document, used when the configuration has no `page_url`
+    pub(crate) page_url: Option<String>,
 }
 
-#[derive(Debug, Clone)]
+/// Entry dropped by the retention or the filters, remembered so that it is skipped
+/// on the next runs.
+#[derive(Archive, Deserialize, Serialize, Debug, Clone, PartialEq)]
+pub(crate) struct RejectedArticle {
+    /// XXH3 of the entry id
+    pub(crate) id: u64,
+    pub(crate) feed_id: u64,
+    pub(crate) title: String,
+    pub(crate) url: String,
+    /// Entry date (UNIX timestamp), or `rejected_at` when the entry has none
+    pub(crate) timestamp: i64,
+    /// UNIX timestamp of the run that dropped the entry
+    pub(crate) rejected_at: i64,
+    /// `expired`, `excluded` or `not_kept`
+    pub(crate) reason: String,
+    /// Slug (or expression) of the filter(s) that dropped the entry
+    pub(crate) filter: Option<String>,
+    /// Fingerprint of the retention and filters of the feed that dropped the entry: it is
+    /// evaluated again once they change
+    pub(crate) filters_hash: u64,
+}
+
+#[derive(Debug, Default, PartialEq, Clone, Copy)]
 pub(crate) enum ExportStrategy {
     /// One file containing all articles of the group (Ideal for EPUB/RSS)
+    #[default]
     Monolithic,
     /// One file per article (Ideal for Markdown/Knowledge bases)
     Individual,
     /// One file per day (Good compromise for Journaling)
     Daily,
+    /// One file per ISO week
+    Weekly,
+    /// One file per month (e.g. a monthly EPUB issue)
+    Monthly,
+}
+
+impl TryFrom<&str> for ExportStrategy {
+    type Error = String;
+
+    /// Parse the `strategy` value used in the YAML configuration.
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value {
+            "monolithic" => Ok(Self::Monolithic),
+            "individual" => Ok(Self::Individual),
+            "daily" => Ok(Self::Daily),
+            "weekly" => Ok(Self::Weekly),
+            "monthly" => Ok(Self::Monthly),
+            _ => Err(format!(
+                "unknown strategy '{}' (expected one of: monolithic, individual, daily, weekly, monthly)",
+                value
+            )),
+        }
+    }
+}
+
+/// Channel-level metadata of an output file carried to exporters at runtime (not stored).
+#[derive(Debug, Default, Clone)]
+pub(crate) struct Channel {
+    /// Title of the group, or of the feed exported alone
+    pub(crate) title: String,
+    /// Canonical URL of the output
+    pub(crate) link: String,
+    /// Absolute URL the output is served at, known when the app has a `base_url`
+    pub(crate) self_url: Option<String>,
+    pub(crate) description: Option<String>,
+    /// Language code, e.g. `fr` or `en-US`
+    pub(crate) language: Option<String>,
+    /// URL of the channel image
+    pub(crate) icon: Option<String>,
+    pub(crate) author: Option<String>,
+    /// Home page of the website, the output `link` is used when missing
+    pub(crate) site_url: Option<String>,
+}
+
+impl Channel {
+    /// Channel with a title and a link only.
+    pub(crate) fn new(title: &str, link: &str) -> Self {
+        Self {
+            title: title.to_string(),
+            link: link.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Home page of the channel: the site URL, or else the output link.
+    pub(crate) fn home_page(&self) -> &str {
+        self.site_url.as_deref().unwrap_or(&self.link)
+    }
 }
 
 /// Feed-level enrichment context carried to exporters at runtime (not stored).
@@ -233,13 +233,13 @@ pub(crate) fn write_opml<W: std::io::Write>(
         writer.write_event(Event::Start(group_outline))?;
 
         let mut feeds: Vec<_> = group.feeds.values().collect();
-        feeds.sort_by(|a, b| a.title.cmp(&b.title));
+        feeds.sort_by(|a, b| a.name().cmp(b.name()));
 
         for feed in feeds {
             let mut feed_outline = BytesStart::new("outline");
             feed_outline.push_attribute(("type", "rss"));
-            feed_outline.push_attribute(("text", feed.title.as_str()));
-            feed_outline.push_attribute(("title", feed.title.as_str()));
+            feed_outline.push_attribute(("text", feed.name()));
+            feed_outline.push_attribute(("title", feed.name()));
             feed_outline.push_attribute(("xmlUrl", feed.url.as_str()));
             if !feed.page_url.is_empty() {
                 feed_outline.push_attribute(("htmlUrl", feed.page_url.as_str()));
@@ -262,7 +262,7 @@ mod tests {
     use quick_xml::Writer;
 
     use super::{build_yaml, parse_opml_str, write_opml};
-    use crate::model::{App, ContentMode, Feed, Group};
+    use crate::model::{App, ContentMode, Feed, Group, HttpSettings};
 
     fn make_feed(title: &str, url: &str, page_url: &str) -> Feed {
         Feed {
@@ -273,12 +273,14 @@ mod tests {
             content_mode: ContentMode::Default,
             selector: None,
             filters: Vec::new(),
-            output: String::new(),
+            output: None,
             retention: 0,
+            min_refresh_time: 600,
             media: false,
             media_max_size: 0,
             enrichment_prepend: None,
             enrichment_append: None,
+            http: HttpSettings::default(),
         }
     }
 
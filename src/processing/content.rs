@@ -1,8 +1,8 @@
 use htmd::HtmlToMarkdown;
-use mediatype::MediaTypeBuf;
-use reqwest::Client;
 use scraper::{Html, Selector};
+use url::Url;
 
+use super::fetch::Fetcher;
 use crate::model::ContentMode;
 
 /// Merge entries from `entries` into `base`, skipping any whose ID already exists.
@@ -23,11 +23,11 @@ pub(super) fn merge_feeds_by_id(
 /// Fetch a URL and return the inner HTML of the first element matching `selector`.
 #[allow(dead_code)]
 pub(super) async fn get_link_data(
-    client: &Client,
+    fetcher: &Fetcher,
     url: &str,
     selector: &str,
 ) -> Result<String, Box<dyn std::error::Error + Send + Sync>> {
-    match client.get(url).send().await {
+    match fetcher.send(fetcher.get(url)).await {
         Ok(response) => match response.text().await {
             Ok(data) => {
                 let document = Html::parse_document(&data);
@@ -44,20 +44,128 @@ pub(super) async fn get_link_data(
     Ok(String::new())
 }
 
+/// Collect the unique `<a href>` targets found in `html` as `(text, absolute URL)` pairs.
+///
+/// When `selector` matches an element, only the links inside it are kept. Relative
+/// links are resolved against `base`; anchors and `javascript:` links are ignored.
+pub(super) fn extract_links(
+    html: &str,
+    selector: Option<&str>,
+    base: Option<&Url>,
+) -> Vec<(String, String)> {
+    let document = Html::parse_document(html);
+    let link_sel = Selector::parse("a[href]").unwrap();
+    let scope = selector
+        .and_then(|s| Selector::parse(s).ok())
+        .and_then(|sel| document.select(&sel).next())
+        .unwrap_or_else(|| document.root_element());
+
+    let mut seen = std::collections::HashSet::new();
+    let mut links = Vec::new();
+    for a in scope.select(&link_sel) {
+        let href = a.value().attr("href").unwrap_or("").trim();
+        if href.is_empty() || href.starts_with('#') || href.starts_with("javascript:") {
+            continue;
+        }
+        let absolute = match base {
+            Some(b) => match b.join(href) {
+                Ok(u) => u.to_string(),
+                Err(_) => continue,
+            },
+            None => href.to_string(),
+        };
+        if !seen.insert(absolute.clone()) {
+            continue;
+        }
+        let text = a.text().collect::<Vec<_>>().join(" ");
+        let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        links.push((text, absolute));
+    }
+    links
+}
+
+/// Render links as a Markdown bullet list, using the URL when the link has no text.
+fn links_to_markdown(links: &[(String, String)]) -> String {
+    links
+        .iter()
+        .map(|(text, href)| {
+            let label = if text.is_empty() { href } else { text };
+            format!("- [{}]({})", label, href)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Body of the page at `url`, `None` when it cannot be fetched or is an error page.
+async fn fetch_page(fetcher: &Fetcher, url: &str) -> Option<String> {
+    let response = match fetcher.send(fetcher.get(url)).await {
+        Ok(response) => response,
+        Err(e) => {
+            tracing::warn!("Cannot fetch page {}: {}", url, e);
+            return None;
+        }
+    };
+    if !response.status().is_success() {
+        tracing::warn!(
+            "Cannot fetch page {}: HTTP status {}",
+            url,
+            response.status()
+        );
+        return None;
+    }
+    response.text().await.ok()
+}
+
 /// Adjust entry content based on the configured `ContentMode`.
+///
+/// Returns `false` when the page of the entry could not be scraped, the entry then
+/// keeps the content of the feed.
 pub(super) async fn apply_content_mode(
     entry: &mut feed_rs::model::Entry,
     mode: &ContentMode,
-    client: &Client,
+    fetcher: &Fetcher,
     selector_str: &Option<String>,
-) {
+) -> bool {
     let converter = HtmlToMarkdown::new();
+    let mut scraped = true;
 
     match mode {
-        ContentMode::No | ContentMode::LinksOnly => {
+        ContentMode::No => {
             entry.content = None;
             entry.summary = None;
         }
+        ContentMode::LinksOnly => {
+            entry.summary = None;
+            let page_url = entry.links.first().map(|l| l.href.clone());
+            let base = page_url.as_deref().and_then(|u| Url::parse(u).ok());
+
+            // Prefer the links of the article page, fall back to the ones in the feed content
+            let mut links = Vec::new();
+            if let Some(url) = &page_url {
+                match fetch_page(fetcher, url).await {
+                    Some(html_content) => {
+                        links =
+                            extract_links(&html_content, selector_str.as_deref(), base.as_ref());
+                    }
+                    None => scraped = false,
+                }
+            }
+            if links.is_empty()
+                && let Some(body) = entry.content.as_ref().and_then(|c| c.body.as_deref())
+            {
+                links = extract_links(body, None, base.as_ref());
+            }
+
+            entry.content = if links.is_empty() {
+                None
+            } else {
+                // Default content type: text/plain
+                Some(feed_rs::model::Content {
+                    body: Some(links_to_markdown(&links)),
+                    ..Default::default()
+                })
+            };
+        }
         ContentMode::Default => {
             // Convert existing HTML content to Markdown in place
             if let Some(content) = &mut entry.content
@@ -74,15 +182,17 @@ pub(super) async fn apply_content_mode(
             // Clear feed-provided summary; the scraped page becomes the content
             entry.summary = None;
 
-            if let Some(link) = entry.links.first()
-                && let Ok(resp) = client.get(&link.href).send().await
-                && let Ok(html_content) = resp.text().await
-            {
+            let Some(link) = entry.links.first() else {
+                return true;
+            };
+            scraped = false;
+            if let Some(html_content) = fetch_page(fetcher, &link.href).await {
                 let document = Html::parse_document(&html_content);
                 let selector = selector_str.as_deref().unwrap_or("article, main, .content");
                 if let Ok(sel) = Selector::parse(selector)
                     && let Some(element) = document.select(&sel).next()
                 {
+                    scraped = true;
                     let inner_html = element.inner_html();
                     let markdown = converter
                         .convert(&inner_html)
@@ -92,9 +202,7 @@ pub(super) async fn apply_content_mode(
                         None => {
                             entry.content = Some(feed_rs::model::Content {
                                 body: Some(markdown),
-                                content_type: "text/plain".parse::<MediaTypeBuf>().unwrap(),
-                                length: None,
-                                src: None,
+                                ..Default::default()
                             });
                         }
                     }
@@ -102,11 +210,22 @@ pub(super) async fn apply_content_mode(
             }
         }
     }
+    scraped
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::processing::fetch::RetryPolicy;
+
+    fn make_fetcher() -> Fetcher {
+        Fetcher::new(
+            reqwest::Client::new(),
+            1,
+            std::time::Duration::ZERO,
+            RetryPolicy::default(),
+        )
+    }
 
     /// Parse a minimal RSS document with the given entry GUIDs.
     fn parse_feed(ids: &[&str]) -> feed_rs::model::Feed {
@@ -125,21 +244,24 @@ mod tests {
         feed_rs::parser::parse(xml.as_bytes()).unwrap()
     }
 
+    // feed-rs types hold its own `mediatype` version: build them through serde
     fn make_text(content: &str) -> feed_rs::model::Text {
-        feed_rs::model::Text {
-            content_type: "text/plain".parse::<MediaTypeBuf>().unwrap(),
-            src: None,
-            content: content.to_string(),
-        }
+        serde_json::from_value(serde_json::json!({
+            "content_type": "text/plain",
+            "src": null,
+            "content": content,
+        }))
+        .unwrap()
     }
 
-    fn make_content(body: &str) -> feed_rs::model::Content {
-        feed_rs::model::Content {
-            body: Some(body.to_string()),
-            content_type: "text/plain".parse::<MediaTypeBuf>().unwrap(),
-            length: None,
-            src: None,
-        }
+    fn make_content(body: &str, content_type: &str) -> feed_rs::model::Content {
+        serde_json::from_value(serde_json::json!({
+            "body": body,
+            "content_type": content_type,
+            "length": null,
+            "src": null,
+        }))
+        .unwrap()
     }
 
     // --- merge_feeds_by_id ---
@@ -183,12 +305,12 @@ mod tests {
 
     #[tokio::test]
     async fn test_content_mode_no_clears_content_and_summary() {
-        let client = Client::new();
+        let fetcher = make_fetcher();
         let mut entry = parse_feed(&["1"]).entries.remove(0);
         entry.summary = Some(make_text("summary"));
-        entry.content = Some(make_content("body"));
+        entry.content = Some(make_content("body", "text/plain"));
 
-        apply_content_mode(&mut entry, &ContentMode::No, &client, &None).await;
+        apply_content_mode(&mut entry, &ContentMode::No, &fetcher, &None).await;
 
         assert!(entry.content.is_none());
         assert!(entry.summary.is_none());
@@ -196,25 +318,75 @@ mod tests {
 
     #[tokio::test]
     async fn test_content_mode_links_only_clears_content_and_summary() {
-        let client = Client::new();
+        let fetcher = make_fetcher();
         let mut entry = parse_feed(&["1"]).entries.remove(0);
         entry.summary = Some(make_text("summary"));
-        entry.content = Some(make_content("body"));
+        entry.content = Some(make_content("body", "text/plain"));
 
-        apply_content_mode(&mut entry, &ContentMode::LinksOnly, &client, &None).await;
+        apply_content_mode(&mut entry, &ContentMode::LinksOnly, &fetcher, &None).await;
 
         assert!(entry.content.is_none());
         assert!(entry.summary.is_none());
     }
 
+    #[tokio::test]
+    async fn test_content_mode_links_only_extracts_content_links() {
+        let fetcher = make_fetcher();
+        let mut entry = parse_feed(&["1"]).entries.remove(0);
+        entry.summary = Some(make_text("summary"));
+        entry.content = Some(make_content(
+            r#"<p>Get <a href="https://example.com/a.zip">the archive</a>
+            or <a href="https://example.com/b.pdf"></a></p>"#,
+            "text/html",
+        ));
+
+        apply_content_mode(&mut entry, &ContentMode::LinksOnly, &fetcher, &None).await;
+
+        assert!(entry.summary.is_none());
+        let body = entry.content.unwrap().body.unwrap();
+        assert_eq!(
+            body,
+            "- [the archive](https://example.com/a.zip)\n\
+             - [https://example.com/b.pdf](https://example.com/b.pdf)"
+        );
+    }
+
+    // --- extract_links ---
+
+    #[test]
+    fn test_extract_links_resolves_relative_and_deduplicates() {
+        let base = Url::parse("https://example.com/post/1").unwrap();
+        let html = r##"<a href="/dl/file.zip">File</a>
+            <a href="https://example.com/dl/file.zip">Again</a>
+            <a href="#top">Top</a>
+            <a href="javascript:void(0)">JS</a>"##;
+        let links = extract_links(html, None, Some(&base));
+        assert_eq!(
+            links,
+            vec![(
+                "File".to_string(),
+                "https://example.com/dl/file.zip".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn test_extract_links_restricted_to_selector() {
+        let html = r#"<nav><a href="https://example.com/menu">Menu</a></nav>
+            <article><a href="https://example.com/post">Post</a></article>"#;
+        let links = extract_links(html, Some("article"), None);
+        assert_eq!(links.len(), 1);
+        assert_eq!(links[0].1, "https://example.com/post");
+    }
+
     #[tokio::test]
     async fn test_content_mode_brief_keeps_summary_drops_content() {
-        let client = Client::new();
+        let fetcher = make_fetcher();
         let mut entry = parse_feed(&["1"]).entries.remove(0);
         entry.summary = Some(make_text("my summary"));
-        entry.content = Some(make_content("full body"));
+        entry.content = Some(make_content("full body", "text/plain"));
 
-        apply_content_mode(&mut entry, &ContentMode::Brief, &client, &None).await;
+        apply_content_mode(&mut entry, &ContentMode::Brief, &fetcher, &None).await;
 
         assert!(entry.content.is_none());
         assert!(entry.summary.is_some());
@@ -222,18 +394,74 @@ mod tests {
 
     #[tokio::test]
     async fn test_content_mode_default_preserves_content() {
-        let client = Client::new();
+        let fetcher = make_fetcher();
         let mut entry = parse_feed(&["1"]).entries.remove(0);
-        entry.content = Some(feed_rs::model::Content {
-            body: Some("<p>Hello</p>".into()),
-            content_type: "text/html".parse::<MediaTypeBuf>().unwrap(),
-            length: None,
-            src: None,
-        });
+        entry.content = Some(make_content("<p>Hello</p>", "text/html"));
 
-        apply_content_mode(&mut entry, &ContentMode::Default, &client, &None).await;
+        apply_content_mode(&mut entry, &ContentMode::Default, &fetcher, &None).await;
 
         // Content should still be present (converted to MD)
         assert!(entry.content.is_some());
     }
+
+    /// Entry linking to `url`, with `body` as feed content.
+    fn entry_linking_to(url: &str, body: &str) -> feed_rs::model::Entry {
+        let xml = format!(
+            r#"<?xml version="1.0"?>
+            <rss version="2.0" xmlns:content="http://purl.org/rss/1.0/modules/content/">
+            <channel><title>T</title><item><guid>1</guid><title>T</title><link>{url}</link>
+            <content:encoded><![CDATA[{body}]]></content:encoded></item></channel></rss>"#
+        );
+        feed_rs::parser::parse(xml.as_bytes())
+            .unwrap()
+            .entries
+            .remove(0)
+    }
+
+    fn http_response(status: &str, body: &str) -> String {
+        format!(
+            "HTTP/1.1 {status}\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        )
+    }
+
+    #[tokio::test]
+    async fn test_content_mode_force_reports_failed_scrape() {
+        let fetcher = make_fetcher();
+        let page = "<html><body><article><p>Scraped</p></article></body></html>";
+        let (ok_url, _) = crate::processing::fetch::serve(http_response("200 OK", page)).await;
+        let mut entry = entry_linking_to(&format!("{ok_url}/a"), "<p>Snippet</p>");
+        assert!(apply_content_mode(&mut entry, &ContentMode::Force, &fetcher, &None).await);
+        assert_eq!(entry.content.unwrap().body.as_deref(), Some("Scraped"));
+
+        // an error page is not the article
+        let error_page = "<html><body><article>Not found</article></body></html>";
+        let (error_url, _) =
+            crate::processing::fetch::serve(http_response("404 Not Found", error_page)).await;
+        let mut entry = entry_linking_to(&format!("{error_url}/a"), "<p>Snippet</p>");
+        assert!(!apply_content_mode(&mut entry, &ContentMode::Force, &fetcher, &None).await);
+        assert_eq!(
+            entry.content.unwrap().body.as_deref(),
+            Some("<p>Snippet</p>")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_content_mode_links_only_ignores_error_page() {
+        let fetcher = make_fetcher();
+        let error_page = r#"<html><body><a href="/home">Home</a></body></html>"#;
+        let (url, _) =
+            crate::processing::fetch::serve(http_response("503 Service Unavailable", error_page))
+                .await;
+        let mut entry = entry_linking_to(
+            &format!("{url}/a"),
+            r#"<a href="https://example.com/review">Review</a>"#,
+        );
+
+        assert!(!apply_content_mode(&mut entry, &ContentMode::LinksOnly, &fetcher, &None).await);
+        assert_eq!(
+            entry.content.unwrap().body.as_deref(),
+            Some("- [Review](https://example.com/review)")
+        );
+    }
 }
@@ -0,0 +1,178 @@
+use feed_rs::parser;
+use scraper::{Html, Selector};
+use url::Url;
+
+use super::fetch::Fetcher;
+use crate::error::FrustError;
+use crate::opml::ParsedFeed;
+
+/// Paths tried on the site when the page does not announce any feed
+const COMMON_FEED_PATHS: &[&str] = &["/feed", "/rss.xml", "/atom.xml"];
+
+/// MIME types of the `<link rel="alternate">` elements announcing a feed
+const FEED_TYPES: &[&str] = &[
+    "application/rss+xml",
+    "application/atom+xml",
+    "application/feed+json",
+];
+
+/// Feeds found for a web page.
+pub(crate) struct Discovery {
+    /// `<title>` of the page
+    pub(crate) page_title: String,
+    /// Candidates, the best one first
+    pub(crate) feeds: Vec<ParsedFeed>,
+}
+
+/// Title of the page and the feeds it announces with `<link rel="alternate">`, in
+/// document order, as `(title, absolute URL)`.
+fn announced_feeds(html: &str, base: &Url) -> (String, Vec<(String, String)>) {
+    let document = Html::parse_document(html);
+    let title_sel = Selector::parse("title").unwrap();
+    let page_title = document
+        .select(&title_sel)
+        .next()
+        .map(|t| t.text().collect::<String>().trim().to_string())
+        .unwrap_or_default();
+
+    let link_sel = Selector::parse("link[rel][href][type]").unwrap();
+    let mut feeds: Vec<(String, String)> = Vec::new();
+    for link in document.select(&link_sel) {
+        let link = link.value();
+        let is_alternate = link.attr("rel").is_some_and(|rel| {
+            rel.split_whitespace()
+                .any(|r| r.eq_ignore_ascii_case("alternate"))
+        });
+        let is_feed = link.attr("type").is_some_and(|t| {
+            FEED_TYPES.contains(
+                &t.split(';')
+                    .next()
+                    .unwrap_or("")
+                    .trim()
+                    .to_ascii_lowercase()
+                    .as_str(),
+            )
+        });
+        if !is_alternate || !is_feed {
+            continue;
+        }
+        let Some(url) = link
+            .attr("href")
+            .and_then(|href| base.join(href.trim()).ok())
+        else {
+            continue;
+        };
+        if feeds.iter().all(|(_, u)| u != url.as_str()) {
+            let title = link.attr("title").unwrap_or_default().trim().to_string();
+            feeds.push((title, url.to_string()));
+        }
+    }
+    (page_title, feeds)
+}
+
+/// Title of the feed served at `url`, none when it does not serve a feed.
+async fn probe_feed(fetcher: &Fetcher, url: &str) -> Option<String> {
+    let response = fetcher.send(fetcher.get(url)).await.ok()?;
+    if !response.status().is_success() {
+        return None;
+    }
+    let bytes = response.bytes().await.ok()?;
+    let feed = parser::parse(bytes.as_ref()).ok()?;
+    Some(feed.title.map(|t| t.content).unwrap_or_default())
+}
+
+/// Find the feeds of the web page at `page_url`: the ones it announces with
+/// `<link rel="alternate">`, or else the common feed paths of the site that serve a feed.
+pub(crate) async fn discover(fetcher: &Fetcher, page_url: &str) -> Result<Discovery, FrustError> {
+    let base = Url::parse(page_url)
+        .map_err(|e| FrustError::Discovery(format!("invalid URL '{}': {}", page_url, e)))?;
+    let response = fetcher.send(fetcher.get(page_url)).await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(FrustError::HttpStatus(status.as_u16()));
+    }
+    let html = response.text().await?;
+    let (page_title, announced) = announced_feeds(&html, &base);
+
+    let mut feeds: Vec<ParsedFeed> = announced
+        .into_iter()
+        .map(|(title, url)| ParsedFeed {
+            title: if title.is_empty() {
+                page_title.clone()
+            } else {
+                title
+            },
+            url,
+            page_url: page_url.to_string(),
+        })
+        .collect();
+    if feeds.is_empty() {
+        for path in COMMON_FEED_PATHS {
+            let Ok(url) = base.join(path) else {
+                continue;
+            };
+            if let Some(title) = probe_feed(fetcher, url.as_str()).await {
+                feeds.push(ParsedFeed {
+                    title: if title.is_empty() {
+                        page_title.clone()
+                    } else {
+                        title
+                    },
+                    url: url.to_string(),
+                    page_url: page_url.to_string(),
+                });
+            }
+        }
+    }
+    if feeds.is_empty() {
+        return Err(FrustError::Discovery(format!(
+            "no feed found for {}",
+            page_url
+        )));
+    }
+    tracing::debug!("Discovered {} feed(s) for {}", feeds.len(), page_url);
+    Ok(Discovery { page_title, feeds })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_announced_feeds_in_document_order() {
+        let html = r#"<html><head>
+            <title> My Blog </title>
+            <link rel="stylesheet" type="text/css" href="/style.css">
+            <link rel="alternate" type="application/atom+xml" title="Atom" href="/atom.xml">
+            <link rel="alternate" type="application/rss+xml; charset=utf-8" href="https://cdn.example.org/rss">
+            <link rel="Alternate" type="application/feed+json" title="JSON" href="feed.json">
+            <link rel="alternate" type="text/html" hreflang="fr" href="/fr/">
+            <link rel="alternate" type="application/atom+xml" href="/atom.xml">
+        </head><body></body></html>"#;
+        let base = Url::parse("https://example.org/blog/").unwrap();
+        let (title, feeds) = announced_feeds(html, &base);
+        assert_eq!(title, "My Blog");
+        assert_eq!(
+            feeds,
+            vec![
+                (
+                    "Atom".to_string(),
+                    "https://example.org/atom.xml".to_string()
+                ),
+                (String::new(), "https://cdn.example.org/rss".to_string()),
+                (
+                    "JSON".to_string(),
+                    "https://example.org/blog/feed.json".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_page_without_feed_links() {
+        let base = Url::parse("https://example.org/").unwrap();
+        let (title, feeds) = announced_feeds("<p>Hello</p>", &base);
+        assert!(title.is_empty());
+        assert!(feeds.is_empty());
+    }
+}
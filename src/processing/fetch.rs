@@ -1,53 +1,660 @@
-/// Save the filtered feed to the specified output file.
-// TODO: This will be wired in when per-feed export (non-group mode) is implemented.
-#[allow(dead_code)]
-pub(super) async fn save_feed_to_disk(
-    feed: &feed_rs::model::Feed,
-    path: &str,
-) -> Result<(), std::io::Error> {
-    // Note: converting feed_rs model back to RSS/Atom XML requires 'rss' or
-    // 'atom_syndication' crates. This is a placeholder until export is wired up.
-    tracing::debug!(
-        "Writing {} filtered entries to {}",
-        feed.entries.len(),
-        path
-    );
-    // TODO: Implementation placeholder for serialization logic
-    Ok(())
+use std::{
+    collections::HashMap,
+    fmt,
+    ops::Deref,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+use chrono::{DateTime, Utc};
+use reqwest::{Client, RequestBuilder, StatusCode, header, redirect};
+use tokio::{
+    sync::{OwnedSemaphorePermit, Semaphore},
+    time::Instant,
+};
+use twox_hash::XxHash3_64;
+use url::Url;
+
+use crate::error::FrustError;
+use crate::model::{HttpAuth, HttpSettings};
+
+/// User agent of the requests when the config does not set one
+const DEFAULT_USER_AGENT: &str = concat!("frust/", env!("CARGO_PKG_VERSION"));
+
+/// Redirects followed by a request, as many as the default policy of reqwest
+const MAX_REDIRECTS: usize = 10;
+
+/// Longest wait before a retry, even when the server asks for more with `Retry-After`
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// Slot of a host: how many requests may run at once, and when the last one started.
+struct Host {
+    semaphore: Arc<Semaphore>,
+    last_request: tokio::sync::Mutex<Option<Instant>>,
+}
+
+/// Limits the requests sent to a same host: at most `max_per_host` at once, started at
+/// least `delay` apart.
+struct HostLimiter {
+    max_per_host: usize,
+    delay: Duration,
+    hosts: Mutex<HashMap<String, Arc<Host>>>,
+}
+
+impl HostLimiter {
+    fn host(&self, name: &str) -> Arc<Host> {
+        let mut hosts = self.hosts.lock().unwrap_or_else(|e| e.into_inner());
+        Arc::clone(hosts.entry(name.to_string()).or_insert_with(|| {
+            Arc::new(Host {
+                semaphore: Arc::new(Semaphore::new(self.max_per_host.max(1))),
+                last_request: tokio::sync::Mutex::new(None),
+            })
+        }))
+    }
+
+    /// Wait for a free slot of the host of `url`, then for the politeness delay.
+    /// The slot is released when the returned permit is dropped.
+    async fn acquire(&self, url: &Url) -> OwnedSemaphorePermit {
+        let host = self.host(url.host_str().unwrap_or_default());
+        let permit = Arc::clone(&host.semaphore)
+            .acquire_owned()
+            .await
+            .expect("host semaphores are never closed");
+        if !self.delay.is_zero() {
+            let mut last_request = host.last_request.lock().await;
+            if let Some(last) = *last_request {
+                tokio::time::sleep_until(last + self.delay).await;
+            }
+            *last_request = Some(Instant::now());
+        }
+        permit
+    }
+}
+
+/// How transient failures are retried. The default does not retry.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct RetryPolicy {
+    /// Retries after the first attempt
+    pub(crate) retries: u32,
+    /// Wait before the first retry, doubled for each following one
+    pub(crate) base_delay: Duration,
+}
+
+impl RetryPolicy {
+    /// Wait before the retry number `retry` (0 for the first one): the exponential
+    /// backoff with a random jitter of up to half of it, or what the server asked for.
+    fn delay(&self, retry: u32, retry_after: Option<Duration>) -> Duration {
+        if let Some(wait) = retry_after {
+            return wait.min(MAX_RETRY_DELAY);
+        }
+        let backoff = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(MAX_RETRY_DELAY);
+        let nanos = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        let jitter = XxHash3_64::oneshot(&nanos.to_le_bytes()) % 1000;
+        backoff / 2 + backoff / 2 * jitter as u32 / 1000
+    }
+}
+
+/// Whether the status is worth a retry: rate limited or a gateway failure.
+fn is_transient_status(status: StatusCode) -> bool {
+    matches!(
+        status,
+        StatusCode::TOO_MANY_REQUESTS
+            | StatusCode::BAD_GATEWAY
+            | StatusCode::SERVICE_UNAVAILABLE
+            | StatusCode::GATEWAY_TIMEOUT
+    )
+}
+
+/// `Retry-After` header, either a number of seconds or an HTTP date.
+fn retry_after(headers: &header::HeaderMap, now: DateTime<Utc>) -> Option<Duration> {
+    let value = headers.get(header::RETRY_AFTER)?.to_str().ok()?.trim();
+    if let Ok(secs) = value.parse::<u64>() {
+        return Some(Duration::from_secs(secs));
+    }
+    let date = DateTime::parse_from_rfc2822(value).ok()?;
+    date.with_timezone(&Utc)
+        .signed_duration_since(now)
+        .to_std()
+        .ok()
+}
+
+/// HTTP client shared by the feed fetches, the page scraping and the media downloads,
+/// so that they all respect the per-host limits and the retry policy.
+#[derive(Clone)]
+pub(crate) struct Fetcher {
+    client: Client,
+    limiter: Arc<HostLimiter>,
+    retry: RetryPolicy,
+    /// Settings of the feed the requests are made for
+    settings: Arc<HttpSettings>,
+    /// Host of the feed, the only one receiving its headers, credentials and cookies
+    feed_host: Option<String>,
+}
+
+/// Whether `a` and `b` are on the same host and port.
+fn same_origin(a: &Url, b: &Url) -> bool {
+    a.host_str() == b.host_str() && a.port_or_known_default() == b.port_or_known_default()
+}
+
+/// Build a client using `proxy` when set. The user agent is the default one, the
+/// settings of each feed are applied to its requests by [`Fetcher::get`].
+///
+/// Redirects to another host are not followed by the client, which would send them
+/// the custom headers of the feed: [`Fetcher::send`] follows them anonymously.
+pub(crate) fn build_client(timeout: Duration, proxy: Option<&str>) -> Result<Client, FrustError> {
+    let policy = redirect::Policy::custom(|attempt| {
+        if attempt.previous().len() > MAX_REDIRECTS {
+            attempt.error("too many redirects")
+        } else if same_origin(&attempt.previous()[0], attempt.url()) {
+            attempt.follow()
+        } else {
+            attempt.stop()
+        }
+    });
+    let mut builder = Client::builder()
+        .timeout(timeout)
+        .user_agent(DEFAULT_USER_AGENT)
+        .redirect(policy);
+    if let Some(proxy) = proxy {
+        builder = builder.proxy(reqwest::Proxy::all(proxy)?);
+    }
+    Ok(builder.build()?)
+}
+
+/// Response holding its host slot until its body has been read.
+pub(crate) struct Response {
+    inner: reqwest::Response,
+    _permit: OwnedSemaphorePermit,
+    /// Retries needed to get the response
+    pub(crate) retries: u32,
+}
+
+impl Deref for Response {
+    type Target = reqwest::Response;
+
+    fn deref(&self) -> &Self::Target {
+        &self.inner
+    }
+}
+
+impl Response {
+    pub(crate) async fn bytes(self) -> Result<impl AsRef<[u8]> + use<>, reqwest::Error> {
+        self.inner.bytes().await
+    }
+
+    pub(crate) async fn text(self) -> Result<String, reqwest::Error> {
+        self.inner.text().await
+    }
+}
+
+/// Request that failed, after `retries` retries.
+#[derive(Debug)]
+pub(crate) struct FetchError {
+    pub(crate) source: reqwest::Error,
+    pub(crate) retries: u32,
+}
+
+impl fmt::Display for FetchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.retries == 0 {
+            write!(f, "{}", self.source)
+        } else {
+            write!(f, "{} (after {} retries)", self.source, self.retries)
+        }
+    }
+}
+
+impl From<FetchError> for FrustError {
+    fn from(e: FetchError) -> Self {
+        Self::Http(e.source)
+    }
+}
+
+impl Fetcher {
+    pub(crate) fn new(
+        client: Client,
+        max_per_host: usize,
+        delay: Duration,
+        retry: RetryPolicy,
+    ) -> Self {
+        Self {
+            client,
+            limiter: Arc::new(HostLimiter {
+                max_per_host,
+                delay,
+                hosts: Mutex::new(HashMap::new()),
+            }),
+            retry,
+            settings: Arc::new(HttpSettings::default()),
+            feed_host: None,
+        }
+    }
+
+    /// Fetcher for the requests of the feed at `feed_url`, sharing the host limits of
+    /// `self`. `client` must have been built with the proxy of the settings.
+    pub(crate) fn for_feed(&self, client: Client, settings: &HttpSettings, feed_url: &str) -> Self {
+        Self {
+            client,
+            limiter: Arc::clone(&self.limiter),
+            retry: self.retry.clone(),
+            settings: Arc::new(settings.clone()),
+            feed_host: Url::parse(feed_url)
+                .ok()
+                .and_then(|u| u.host_str().map(str::to_string)),
+        }
+    }
+
+    /// GET request with the user agent, headers, credentials and cookies of the feed.
+    /// Requests to another host than the feed one, such as the links of its articles,
+    /// are anonymous so that a private feed does not leak its secrets.
+    pub(crate) fn get(&self, url: &str) -> RequestBuilder {
+        let host = Url::parse(url)
+            .ok()
+            .and_then(|u| u.host_str().map(str::to_string));
+        if host.is_none() || host != self.feed_host {
+            return self.get_anonymous(url);
+        }
+        let settings = &self.settings;
+        let mut request = self.get_anonymous(url);
+        for (name, value) in &settings.headers {
+            request = request.header(name, value);
+        }
+        request = match &settings.auth {
+            Some(HttpAuth::Basic { username, password }) => {
+                request.basic_auth(username, password.as_ref())
+            }
+            Some(HttpAuth::Bearer(token)) => request.bearer_auth(token),
+            None => request,
+        };
+        if !settings.cookies.is_empty() {
+            let cookies: Vec<String> = settings
+                .cookies
+                .iter()
+                .map(|(name, value)| format!("{}={}", name, value))
+                .collect();
+            request = request.header(header::COOKIE, cookies.join("; "));
+        }
+        request
+    }
+
+    /// GET request with only the user agent of the feed, for third-party resources
+    /// such as media files that must not receive its credentials.
+    pub(crate) fn get_anonymous(&self, url: &str) -> RequestBuilder {
+        let request = self.client.get(url);
+        match &self.settings.user_agent {
+            Some(user_agent) => request.header(header::USER_AGENT, user_agent),
+            None => request,
+        }
+    }
+
+    /// Send a request once its host has a free slot. Idempotent requests are retried
+    /// on connection failures, timeouts and transient statuses (429, 502, 503, 504).
+    /// When the retries are exhausted, the last response is returned whatever its status.
+    ///
+    /// Redirects to another host are followed with anonymous requests, so that the
+    /// headers, credentials and cookies of the feed stay on its host.
+    pub(crate) async fn send(&self, request: RequestBuilder) -> Result<Response, FetchError> {
+        let mut response = self.send_with_retries(request).await?;
+        for _ in 0..MAX_REDIRECTS {
+            let Some(location) = cross_host_redirect(&response) else {
+                break;
+            };
+            let retries = response.retries;
+            drop(response);
+            tracing::debug!("Following the redirect to {}", location);
+            response = self
+                .send_with_retries(self.get_anonymous(location.as_str()))
+                .await
+                .map_err(|e| FetchError {
+                    retries: e.retries + retries,
+                    ..e
+                })?;
+            response.retries += retries;
+        }
+        Ok(response)
+    }
+
+    async fn send_with_retries(&self, request: RequestBuilder) -> Result<Response, FetchError> {
+        let request = request
+            .build()
+            .map_err(|source| FetchError { source, retries: 0 })?;
+        let mut retries = 0;
+        loop {
+            let attempt = request
+                .try_clone()
+                .filter(|_| retries < self.retry.retries && request.method().is_idempotent());
+            let Some(attempt) = attempt else {
+                // Last attempt
+                let permit = self.limiter.acquire(request.url()).await;
+                return match self.client.execute(request).await {
+                    Ok(inner) => Ok(Response {
+                        inner,
+                        _permit: permit,
+                        retries,
+                    }),
+                    Err(source) => Err(FetchError { source, retries }),
+                };
+            };
+
+            let permit = self.limiter.acquire(attempt.url()).await;
+            let (reason, wait) = match self.client.execute(attempt).await {
+                Ok(inner) if is_transient_status(inner.status()) => (
+                    format!("HTTP status {}", inner.status().as_u16()),
+                    retry_after(inner.headers(), Utc::now()),
+                ),
+                Ok(inner) => {
+                    return Ok(Response {
+                        inner,
+                        _permit: permit,
+                        retries,
+                    });
+                }
+                Err(e) if e.is_connect() || e.is_timeout() => (e.to_string(), None),
+                Err(source) => return Err(FetchError { source, retries }),
+            };
+            drop(permit);
+
+            let delay = self.retry.delay(retries, wait);
+            retries += 1;
+            tracing::warn!(
+                "Retrying {} in {:?} ({}), retry {}/{}",
+                request.url(),
+                delay,
+                reason,
+                retries,
+                self.retry.retries
+            );
+            tokio::time::sleep(delay).await;
+        }
+    }
+}
+
+/// Target of a redirect to another host, which the client did not follow.
+fn cross_host_redirect(response: &Response) -> Option<Url> {
+    if !response.status().is_redirection() {
+        return None;
+    }
+    let location = response.headers().get(header::LOCATION)?.to_str().ok()?;
+    let target = response.url().join(location).ok()?;
+    (!same_origin(response.url(), &target)).then_some(target)
+}
+
+/// Local HTTP server answering `response` (a raw HTTP response) to every request, for
+/// the tests. Returns its base URL and the raw requests received.
+#[cfg(test)]
+pub(super) async fn serve(
+    response: String,
+) -> (String, tokio::sync::mpsc::UnboundedReceiver<String>) {
+    serve_on("127.0.0.1", response).await
+}
+
+/// [`serve`] on the loopback address `ip`, e.g. `127.0.0.2` for another host.
+#[cfg(test)]
+pub(super) async fn serve_on(
+    ip: &str,
+    response: String,
+) -> (String, tokio::sync::mpsc::UnboundedReceiver<String>) {
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    let listener = tokio::net::TcpListener::bind((ip, 0)).await.unwrap();
+    let base_url = format!("http://{}", listener.local_addr().unwrap());
+    let (requests, received) = tokio::sync::mpsc::unbounded_channel();
+    tokio::spawn(async move {
+        while let Ok((mut socket, _)) = listener.accept().await {
+            let mut request = Vec::new();
+            let mut buf = [0; 1024];
+            while !request.ends_with(b"\r\n\r\n") {
+                match socket.read(&mut buf).await {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => request.extend_from_slice(&buf[..n]),
+                }
+            }
+            let _ = requests.send(String::from_utf8_lossy(&request).into_owned());
+            let _ = socket.write_all(response.as_bytes()).await;
+        }
+    });
+    (base_url, received)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    fn parse_feed(ids: &[&str]) -> feed_rs::model::Feed {
-        let items: String = ids
-            .iter()
-            .map(|id| format!("<item><guid>{id}</guid><title>T</title></item>"))
-            .collect::<Vec<_>>()
-            .join("\n");
-        let xml = format!(
-            r#"<?xml version="1.0" encoding="UTF-8"?>
-            <rss version="2.0"><channel>
-                <title>Test</title><link>https://example.com</link>
-                {items}
-            </channel></rss>"#
+    fn limiter(max_per_host: usize, delay: Duration) -> HostLimiter {
+        HostLimiter {
+            max_per_host,
+            delay,
+            hosts: Mutex::new(HashMap::new()),
+        }
+    }
+
+    fn url(s: &str) -> Url {
+        Url::parse(s).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_requests_per_host_are_limited() {
+        let limiter = limiter(1, Duration::ZERO);
+        let first = limiter.acquire(&url("https://a.example.com/feed")).await;
+
+        // Same host: waits for the first request
+        let same_host = tokio::time::timeout(
+            Duration::from_millis(50),
+            limiter.acquire(&url("https://a.example.com/page")),
+        )
+        .await;
+        assert!(same_host.is_err());
+
+        // Other host: not blocked
+        let other_host = tokio::time::timeout(
+            Duration::from_millis(50),
+            limiter.acquire(&url("https://b.example.com/feed")),
+        )
+        .await;
+        assert!(other_host.is_ok());
+
+        drop(first);
+        let same_host = tokio::time::timeout(
+            Duration::from_millis(50),
+            limiter.acquire(&url("https://a.example.com/page")),
+        )
+        .await;
+        assert!(same_host.is_ok());
+    }
+
+    #[test]
+    fn test_retry_delay_is_exponential_with_jitter() {
+        let policy = RetryPolicy {
+            retries: 5,
+            base_delay: Duration::from_secs(2),
+        };
+        for (retry, backoff) in [(0, 2), (1, 4), (2, 8), (10, 60)] {
+            let delay = policy.delay(retry, None);
+            let backoff = Duration::from_secs(backoff);
+            assert!(delay >= backoff / 2 && delay <= backoff, "{:?}", delay);
+        }
+        // The server decides, within reason
+        let wait = Some(Duration::from_secs(30));
+        assert_eq!(policy.delay(0, wait), Duration::from_secs(30));
+        let wait = Some(Duration::from_secs(3600));
+        assert_eq!(policy.delay(0, wait), MAX_RETRY_DELAY);
+    }
+
+    #[test]
+    fn test_retry_after_header() {
+        let now = DateTime::parse_from_rfc3339("2026-01-01T12:00:00Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        let mut headers = header::HeaderMap::new();
+        assert_eq!(retry_after(&headers, now), None);
+
+        headers.insert(header::RETRY_AFTER, "120".parse().unwrap());
+        assert_eq!(retry_after(&headers, now), Some(Duration::from_secs(120)));
+
+        headers.insert(
+            header::RETRY_AFTER,
+            "Thu, 01 Jan 2026 12:00:30 GMT".parse().unwrap(),
+        );
+        assert_eq!(retry_after(&headers, now), Some(Duration::from_secs(30)));
+
+        // Date in the past
+        headers.insert(
+            header::RETRY_AFTER,
+            "Thu, 01 Jan 2026 11:00:00 GMT".parse().unwrap(),
         );
-        feed_rs::parser::parse(xml.as_bytes()).unwrap()
+        assert_eq!(retry_after(&headers, now), None);
+    }
+
+    #[test]
+    fn test_feed_settings_are_applied() {
+        let fetcher = Fetcher::new(Client::new(), 1, Duration::ZERO, RetryPolicy::default());
+        let settings = HttpSettings {
+            user_agent: Some("Mozilla/5.0".to_string()),
+            headers: vec![("Accept-Language".to_string(), "fr".to_string())],
+            auth: Some(HttpAuth::Bearer("t0k3n".to_string())),
+            proxy: None,
+            cookies: vec![
+                ("session".to_string(), "abc".to_string()),
+                ("consent".to_string(), "yes".to_string()),
+            ],
+        };
+        let fetcher = fetcher.for_feed(Client::new(), &settings, "https://example.com/feed");
+
+        let request = fetcher.get("https://example.com/feed").build().unwrap();
+        let headers = request.headers();
+        assert_eq!(headers[header::USER_AGENT], "Mozilla/5.0");
+        assert_eq!(headers["accept-language"], "fr");
+        assert_eq!(headers[header::AUTHORIZATION], "Bearer t0k3n");
+        assert_eq!(headers[header::COOKIE], "session=abc; consent=yes");
+
+        // Only the user agent for third-party resources
+        let request = fetcher
+            .get_anonymous("https://cdn.example.net/image.png")
+            .build()
+            .unwrap();
+        let headers = request.headers();
+        assert_eq!(headers[header::USER_AGENT], "Mozilla/5.0");
+        assert!(!headers.contains_key(header::AUTHORIZATION));
+        assert!(!headers.contains_key(header::COOKIE));
+        assert!(!headers.contains_key("accept-language"));
+    }
+
+    #[test]
+    fn test_feed_secrets_are_not_sent_to_other_hosts() {
+        let settings = HttpSettings {
+            headers: vec![("X-Api-Key".to_string(), "k3y".to_string())],
+            auth: Some(HttpAuth::Bearer("t0k3n".to_string())),
+            cookies: vec![("session".to_string(), "abc".to_string())],
+            ..HttpSettings::default()
+        };
+        let fetcher = Fetcher::new(Client::new(), 1, Duration::ZERO, RetryPolicy::default())
+            .for_feed(Client::new(), &settings, "https://private.example.com/feed");
+
+        // A page of the feed site
+        let request = fetcher
+            .get("https://private.example.com/article/1")
+            .build()
+            .unwrap();
+        assert_eq!(request.headers()[header::AUTHORIZATION], "Bearer t0k3n");
+
+        // A link of an article, on another host
+        for url in [
+            "https://evil.example.net/collect",
+            "https://example.com/page",
+            "http://private.example.com.evil.net/",
+        ] {
+            let request = fetcher.get(url).build().unwrap();
+            let headers = request.headers();
+            assert!(!headers.contains_key(header::AUTHORIZATION), "{url}");
+            assert!(!headers.contains_key(header::COOKIE), "{url}");
+            assert!(!headers.contains_key("x-api-key"), "{url}");
+        }
     }
 
     #[tokio::test]
-    async fn test_save_feed_to_disk_returns_ok() {
-        let feed = parse_feed(&[]);
-        let result = save_feed_to_disk(&feed, "/tmp/test_feed_output.xml").await;
-        assert!(result.is_ok());
+    async fn test_feed_secrets_do_not_follow_redirects_to_other_hosts() {
+        let (other_url, mut other_requests) = serve_on(
+            "127.0.0.2",
+            "HTTP/1.1 200 OK\r\nContent-Length: 2\r\nConnection: close\r\n\r\nok".to_string(),
+        )
+        .await;
+        let (feed_url, mut feed_requests) = serve(format!(
+            "HTTP/1.1 302 Found\r\nLocation: {other_url}/moved\r\nContent-Length: 0\r\nConnection: close\r\n\r\n"
+        ))
+        .await;
+        let settings = HttpSettings {
+            headers: vec![("X-Api-Key".to_string(), "k3y".to_string())],
+            auth: Some(HttpAuth::Bearer("t0k3n".to_string())),
+            cookies: vec![("session".to_string(), "abc".to_string())],
+            ..HttpSettings::default()
+        };
+        let client = build_client(Duration::from_secs(10), None).unwrap();
+        let fetcher = Fetcher::new(client.clone(), 1, Duration::ZERO, RetryPolicy::default())
+            .for_feed(client, &settings, &format!("{feed_url}/feed"));
+
+        let response = fetcher
+            .send(fetcher.get(&format!("{feed_url}/feed")))
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.url().as_str(), format!("{other_url}/moved"));
+
+        let feed_request = feed_requests.recv().await.unwrap().to_lowercase();
+        assert!(feed_request.contains("x-api-key: k3y"));
+        let other_request = other_requests.recv().await.unwrap().to_lowercase();
+        assert!(other_request.starts_with("get /moved "));
+        for secret in ["x-api-key", "authorization", "cookie"] {
+            assert!(!other_request.contains(secret), "{other_request}");
+        }
+    }
+
+    #[test]
+    fn test_basic_auth_and_proxy_client() {
+        let settings = HttpSettings {
+            auth: Some(HttpAuth::Basic {
+                username: "me".to_string(),
+                password: Some("secret".to_string()),
+            }),
+            ..HttpSettings::default()
+        };
+        let client = build_client(Duration::from_secs(10), Some("socks5h://127.0.0.1:1080"));
+        assert!(client.is_ok());
+        let fetcher = Fetcher::new(Client::new(), 1, Duration::ZERO, RetryPolicy::default())
+            .for_feed(client.unwrap(), &settings, "https://example.com/feed");
+        let request = fetcher.get("https://example.com/feed").build().unwrap();
+        // base64("me:secret")
+        assert_eq!(
+            request.headers()[header::AUTHORIZATION],
+            "Basic bWU6c2VjcmV0"
+        );
+    }
+
+    #[test]
+    fn test_transient_statuses() {
+        for status in [429, 502, 503, 504] {
+            assert!(is_transient_status(StatusCode::from_u16(status).unwrap()));
+        }
+        for status in [200, 304, 404, 500, 501] {
+            assert!(!is_transient_status(StatusCode::from_u16(status).unwrap()));
+        }
     }
 
     #[tokio::test]
-    async fn test_save_feed_with_entries_returns_ok() {
-        let feed = parse_feed(&["entry-1", "entry-2"]);
-        assert_eq!(feed.entries.len(), 2);
-        let result = save_feed_to_disk(&feed, "/tmp/test_feed_with_entries.xml").await;
-        assert!(result.is_ok());
+    async fn test_requests_to_a_host_are_spaced() {
+        let delay = Duration::from_millis(100);
+        let limiter = limiter(4, delay);
+        let start = Instant::now();
+        let _first = limiter.acquire(&url("https://a.example.com/1")).await;
+        let _other = limiter.acquire(&url("https://b.example.com/1")).await;
+        assert!(start.elapsed() < delay);
+
+        let _second = limiter.acquire(&url("https://a.example.com/2")).await;
+        assert!(start.elapsed() >= delay);
     }
 }
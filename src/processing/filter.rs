@@ -5,10 +5,14 @@ use twox_hash::XxHash3_64;
 
 use crate::{
     START_TIME,
-    model::{Feed, Filter},
+    model::{
+        App, ContentMode, Feed, Filter, FilterExpr, FilterRule, Predicate, PredicateField,
+        PredicateOp, RejectedArticle,
+    },
 };
 
 use super::content::apply_content_mode;
+use super::fetch::Fetcher;
 
 /// Returns `true` when the article is older than the retention window.
 /// A retention of `0` means "keep forever".
@@ -22,6 +26,45 @@ pub(super) fn is_article_expired(entry_date: DateTime<Utc>, retention_days: u16)
         >= retention_days as i64
 }
 
+/// Fingerprint of everything deciding whether an entry of `feed` is dropped: its
+/// retention, content mode, selector, filter rules and the definitions of the filters
+/// they reference.
+pub(crate) fn filters_fingerprint(feed: &Feed, global_filters: &HashMap<u64, Filter>) -> u64 {
+    fn referenced(expr: &FilterExpr, ids: &mut Vec<u64>) {
+        match expr {
+            FilterExpr::Filter(id) => ids.push(*id),
+            FilterExpr::Any(exprs) | FilterExpr::All(exprs) => {
+                exprs.iter().for_each(|e| referenced(e, ids))
+            }
+            FilterExpr::Not(expr) => referenced(expr, ids),
+        }
+    }
+    let mut ids = Vec::new();
+    for rule in &feed.filters {
+        match rule {
+            FilterRule::Named(id) => ids.push(*id),
+            FilterRule::Keep(expr) | FilterRule::Exclude(expr) => referenced(expr, &mut ids),
+        }
+    }
+    ids.sort_unstable();
+    ids.dedup();
+    let definitions: Vec<Option<&Filter>> = ids.iter().map(|id| global_filters.get(id)).collect();
+    let description = format!(
+        "{}|{:?}|{:?}|{:?}|{:?}",
+        feed.retention, feed.content_mode, feed.selector, feed.filters, definitions
+    );
+    XxHash3_64::oneshot(description.as_bytes())
+}
+
+/// [`filters_fingerprint`] of every feed of the app, by feed id.
+pub(crate) fn feed_fingerprints(app: &App) -> HashMap<u64, u64> {
+    app.groups
+        .values()
+        .flat_map(|g| g.feeds.iter())
+        .map(|(id, feed)| (*id, filters_fingerprint(feed, &app.filters)))
+        .collect()
+}
+
 /// Returns `true` when `text` satisfies the filter's regex or plain-text rules.
 pub(super) fn check_text_match(text: &str, filter: &Filter) -> bool {
     if filter.is_regex {
@@ -50,82 +93,428 @@ pub(super) fn check_text_match(text: &str, filter: &Filter) -> bool {
 /// Apply content-mode transformation, retention policy and include/exclude filters
 /// to all entries in `fetched_feed`, mutating it in place.
 ///
-/// `existing_ids` is a set of XXH3-hashed entry IDs already in storage; matching
-/// entries are dropped before any expensive content enrichment takes place.
+/// `existing_ids` is a set of XXH3-hashed entry IDs already in storage (or rejected
+/// earlier); matching entries are dropped before any expensive content enrichment
+/// takes place.
+///
+/// When the content is replaced by the fetched page (`Force` and `LinksOnly` modes),
+/// the filters are run in two passes: the rules that do not depend on the content
+/// before fetching the pages, then all of them on the final Markdown body. An entry
+/// whose page could not be scraped is only dropped for this run when the final pass
+/// rejects it, so that its page is tried again on the next run.
+///
+/// Returns the dropped entries, which should be remembered so that they are skipped
+/// (and their page not fetched again) on the next runs.
 pub(super) async fn apply_filters_and_retention(
     fetched_feed: &mut feed_rs::model::Feed,
+    feed_id: u64,
     feed_config: &Feed,
     global_filters: &HashMap<u64, Filter>,
-    client: &reqwest::Client,
+    fetcher: &Fetcher,
     selector: Option<String>,
     existing_ids: &HashSet<u64>,
-) {
+) -> Vec<RejectedArticle> {
     // 0. Skip entries already stored — do this before content enrichment to avoid
     //    unnecessary HTTP requests (especially costly for ContentMode::Force).
     fetched_feed
         .entries
         .retain(|entry| !existing_ids.contains(&XxHash3_64::oneshot(entry.id.as_bytes())));
 
-    // 1. Adjust content according to the configured mode
-    for entry in &mut fetched_feed.entries {
-        apply_content_mode(entry, &feed_config.content_mode, client, &selector).await;
-    }
+    let scraped = matches!(
+        feed_config.content_mode,
+        ContentMode::Force | ContentMode::LinksOnly
+    );
+
+    let mut rejected = Vec::new();
+    let filters_hash = filters_fingerprint(feed_config, global_filters);
 
-    // 2. Retention and filter pass
+    // 1. Retention pass, along with the filters that can already be checked
     fetched_feed.entries.retain(|entry| {
-        // A. Retention check
         let entry_date = entry
             .updated
             .or(entry.published)
             .unwrap_or(*START_TIME.get().unwrap());
-        if is_article_expired(entry_date, feed_config.retention) {
+        let rejection = if is_article_expired(entry_date, feed_config.retention) {
+            Some(Rejection::Expired)
+        } else if scraped {
+            find_rejection(
+                entry,
+                feed_config,
+                global_filters,
+                FilterPass::BeforeScraping,
+            )
+        } else {
+            None
+        };
+        keep_or_reject(
+            entry,
+            feed_id,
+            feed_config,
+            filters_hash,
+            rejection,
+            &mut rejected,
+        )
+    });
+
+    // 2. Adjust content according to the configured mode. `Force` drops the feed
+    //    summary, it is still used by the filters.
+    let mut summaries = Vec::with_capacity(fetched_feed.entries.len());
+    let mut scrape_failures = Vec::with_capacity(fetched_feed.entries.len());
+    for entry in &mut fetched_feed.entries {
+        summaries.push(entry.summary.clone());
+        let scraped =
+            apply_content_mode(entry, &feed_config.content_mode, fetcher, &selector).await;
+        scrape_failures.push(!scraped);
+    }
+
+    // 3. Filter pass on the final content
+    let mut summaries = summaries.into_iter();
+    let mut scrape_failures = scrape_failures.into_iter();
+    fetched_feed.entries.retain_mut(|entry| {
+        let final_summary = std::mem::replace(&mut entry.summary, summaries.next().flatten());
+        let rejection = find_rejection(entry, feed_config, global_filters, FilterPass::Final);
+        entry.summary = final_summary;
+        if scrape_failures.next().unwrap_or(false) && rejection.is_some() {
+            tracing::info!(
+                "Skipping entry '{}' until its page can be scraped: {:?}",
+                entry.id,
+                rejection
+            );
             return false;
         }
+        keep_or_reject(
+            entry,
+            feed_id,
+            feed_config,
+            filters_hash,
+            rejection,
+            &mut rejected,
+        )
+    });
+    rejected
+}
 
-        // B. Filter check (inherited group + feed filters are already merged into feed_config.filters)
-        for filter_id in &feed_config.filters {
-            if let Some(filter) = global_filters.get(filter_id) {
-                let mut is_match = false;
-
-                if filter.filter_in_title
-                    && let Some(title) = &entry.title
-                    && check_text_match(&title.content, filter)
-                {
-                    is_match = true;
-                }
-
-                if !is_match
-                    && filter.filter_in_summary
-                    && let Some(summary) = &entry.summary
-                    && check_text_match(&summary.content, filter)
-                {
-                    is_match = true;
-                }
-
-                // Skip content matching when a CSS selector is configured: the feed
-                // content will be replaced by the scraped page, so it is not the
-                // final text to filter on.
-                if !is_match
-                    && filter.filter_in_content
-                    && feed_config.selector.is_none()
-                    && let Some(content) = &entry.content
-                    && let Some(body) = &content.body
-                    && check_text_match(body, filter)
-                {
-                    is_match = true;
-                }
-
-                if filter.keep {
-                    if !is_match {
-                        return false;
-                    }
-                } else if is_match {
-                    return false;
-                }
+/// Returns `true` when the entry is not rejected. Otherwise the reason is logged and
+/// the entry added to `rejected`.
+fn keep_or_reject(
+    entry: &feed_rs::model::Entry,
+    feed_id: u64,
+    feed_config: &Feed,
+    filters_hash: u64,
+    rejection: Option<Rejection>,
+    rejected: &mut Vec<RejectedArticle>,
+) -> bool {
+    let Some(reason) = rejection else {
+        return true;
+    };
+    let title = entry
+        .title
+        .as_ref()
+        .map(|t| t.content.clone())
+        .unwrap_or_default();
+    tracing::debug!(
+        "Dropped '{}' ({}) from feed '{}': {}",
+        title,
+        entry.id,
+        feed_config.slug,
+        reason
+    );
+    let rejected_at = START_TIME.get().unwrap().timestamp();
+    rejected.push(RejectedArticle {
+        id: XxHash3_64::oneshot(entry.id.as_bytes()),
+        feed_id,
+        title,
+        url: entry
+            .links
+            .first()
+            .map(|l| l.href.clone())
+            .unwrap_or_else(|| entry.id.clone()),
+        timestamp: entry
+            .updated
+            .or(entry.published)
+            .map_or(rejected_at, |d| d.timestamp()),
+        rejected_at,
+        reason: reason.code().to_string(),
+        filter: reason.filter(),
+        filters_hash,
+    });
+    false
+}
+
+/// When a filter pass runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum FilterPass {
+    /// Before the content is replaced by the fetched page: the rules depending on the
+    /// content are not checked yet
+    BeforeScraping,
+    /// On the final entry
+    Final,
+}
+
+/// Why an entry was dropped by [`apply_filters_and_retention`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Rejection {
+    /// Older than the retention window
+    Expired,
+    /// Matched the exclude filter with this slug, or this exclude expression
+    Excluded(String),
+    /// Matched none of these keep filters or expressions
+    NotKept(Vec<String>),
+}
+
+impl Rejection {
+    /// Short identifier of the reason, as stored in [`RejectedArticle::reason`].
+    pub(crate) fn code(&self) -> &'static str {
+        match self {
+            Rejection::Expired => "expired",
+            Rejection::Excluded(_) => "excluded",
+            Rejection::NotKept(_) => "not_kept",
+        }
+    }
+
+    /// Filter(s) responsible for the rejection, if any.
+    pub(crate) fn filter(&self) -> Option<String> {
+        match self {
+            Rejection::Expired => None,
+            Rejection::Excluded(slug) => Some(slug.clone()),
+            Rejection::NotKept(slugs) => Some(slugs.join(", ")),
+        }
+    }
+}
+
+impl std::fmt::Display for Rejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rejection::Expired => write!(f, "older than the retention"),
+            Rejection::Excluded(slug) => write!(f, "excluded by filter '{}'", slug),
+            Rejection::NotKept(slugs) => {
+                write!(f, "not matched by keep filter(s) '{}'", slugs.join("', '"))
             }
         }
-        true
-    });
+    }
+}
+
+/// Returns `true` when the entry matches the filter: its expressions in one of its
+/// scopes and/or its predicates, depending on `must_match_all`.
+fn entry_matches(entry: &feed_rs::model::Entry, filter: &Filter) -> bool {
+    let has_text = !filter.expressions.is_empty();
+    let text_match = has_text && text_matches(entry, filter);
+    if filter.predicates.is_empty() {
+        return text_match;
+    }
+    let mut predicates = filter.predicates.iter();
+    if filter.must_match_all {
+        (!has_text || text_match) && predicates.all(|p| predicate_matches(entry, p))
+    } else {
+        text_match || predicates.any(|p| predicate_matches(entry, p))
+    }
+}
+
+/// Values of the entry for a text predicate field, lowercased.
+fn text_values(entry: &feed_rs::model::Entry, field: PredicateField) -> Vec<String> {
+    let values: Vec<String> = match field {
+        PredicateField::Author => entry.authors.iter().map(|a| a.name.clone()).collect(),
+        PredicateField::Category => entry
+            .categories
+            .iter()
+            .flat_map(|c| std::iter::once(c.term.clone()).chain(c.label.clone()))
+            .collect(),
+        PredicateField::LinkDomain => entry
+            .links
+            .iter()
+            .filter_map(|l| {
+                url::Url::parse(&l.href)
+                    .ok()?
+                    .host_str()
+                    .map(str::to_string)
+            })
+            .collect(),
+        PredicateField::EnclosureType => entry
+            .media
+            .iter()
+            .flat_map(|m| m.content.iter())
+            .filter_map(|c| c.content_type.as_ref().map(|t| t.to_string()))
+            .chain(
+                entry
+                    .links
+                    .iter()
+                    .filter(|l| l.rel.as_deref() == Some("enclosure"))
+                    .filter_map(|l| l.media_type.clone()),
+            )
+            .collect(),
+        PredicateField::Age | PredicateField::ContentLength | PredicateField::Published => {
+            Vec::new()
+        }
+    };
+    values.into_iter().map(|v| v.to_lowercase()).collect()
+}
+
+/// Value of the entry for a numeric predicate field, `None` when the entry has no date.
+fn number_value(entry: &feed_rs::model::Entry, field: PredicateField) -> Option<i64> {
+    let date = entry.published.or(entry.updated);
+    match field {
+        PredicateField::Age => date.map(|d| {
+            START_TIME
+                .get()
+                .unwrap()
+                .signed_duration_since(d)
+                .num_days()
+        }),
+        PredicateField::Published => date.map(|d| d.timestamp()),
+        PredicateField::ContentLength => {
+            let text = match entry.content.as_ref().and_then(|c| c.body.as_deref()) {
+                Some(body) => body,
+                None => entry.summary.as_ref().map_or("", |s| s.content.as_str()),
+            };
+            Some(text.chars().count() as i64)
+        }
+        _ => None,
+    }
+}
+
+/// Returns `true` when the entry satisfies the predicate.
+pub(super) fn predicate_matches(entry: &feed_rs::model::Entry, predicate: &Predicate) -> bool {
+    let is_match = match &predicate.op {
+        PredicateOp::Equals(v) => text_values(entry, predicate.field).contains(v),
+        PredicateOp::In(values) => text_values(entry, predicate.field)
+            .iter()
+            .any(|v| values.contains(v)),
+        PredicateOp::StartsWith(prefix) => text_values(entry, predicate.field)
+            .iter()
+            .any(|v| v.starts_with(prefix.as_str())),
+        PredicateOp::Regex(re) => text_values(entry, predicate.field)
+            .iter()
+            .any(|v| re.is_match(v)),
+        PredicateOp::NumberEquals(n) => number_value(entry, predicate.field) == Some(*n),
+        PredicateOp::GreaterThan(n) => number_value(entry, predicate.field).is_some_and(|v| v > *n),
+        PredicateOp::LessThan(n) => number_value(entry, predicate.field).is_some_and(|v| v < *n),
+    };
+    is_match != predicate.negate
+}
+
+/// Returns `true` when the expressions of the filter match the entry in one of its scopes.
+fn text_matches(entry: &feed_rs::model::Entry, filter: &Filter) -> bool {
+    if filter.filter_in_title
+        && let Some(title) = &entry.title
+        && check_text_match(&title.content, filter)
+    {
+        return true;
+    }
+
+    if filter.filter_in_summary
+        && let Some(summary) = &entry.summary
+        && check_text_match(&summary.content, filter)
+    {
+        return true;
+    }
+
+    filter.filter_in_content
+        && entry
+            .content
+            .as_ref()
+            .and_then(|c| c.body.as_deref())
+            .is_some_and(|body| check_text_match(body, filter))
+}
+
+/// Returns `true` when the entry satisfies the filter expression. Unknown filter ids
+/// never match.
+fn expr_matches(
+    entry: &feed_rs::model::Entry,
+    expr: &FilterExpr,
+    global_filters: &HashMap<u64, Filter>,
+) -> bool {
+    match expr {
+        FilterExpr::Filter(id) => global_filters
+            .get(id)
+            .is_some_and(|f| entry_matches(entry, f)),
+        FilterExpr::Any(exprs) => exprs.iter().any(|e| expr_matches(entry, e, global_filters)),
+        FilterExpr::All(exprs) => exprs.iter().all(|e| expr_matches(entry, e, global_filters)),
+        FilterExpr::Not(e) => !expr_matches(entry, e, global_filters),
+    }
+}
+
+/// Returns `true` when a filter used by the expression looks at the entry content.
+fn expr_uses_content(expr: &FilterExpr, global_filters: &HashMap<u64, Filter>) -> bool {
+    match expr {
+        FilterExpr::Filter(id) => global_filters.get(id).is_some_and(|f| {
+            (f.filter_in_content && !f.expressions.is_empty())
+                || f.predicates
+                    .iter()
+                    .any(|p| p.field == PredicateField::ContentLength)
+        }),
+        FilterExpr::Any(exprs) | FilterExpr::All(exprs) => {
+            exprs.iter().any(|e| expr_uses_content(e, global_filters))
+        }
+        FilterExpr::Not(e) => expr_uses_content(e, global_filters),
+    }
+}
+
+/// Human readable form of a filter expression, e.g. `all(metal, not(sponsored))`.
+fn describe_expr(expr: &FilterExpr, global_filters: &HashMap<u64, Filter>) -> String {
+    let list = |exprs: &[FilterExpr]| {
+        exprs
+            .iter()
+            .map(|e| describe_expr(e, global_filters))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+    match expr {
+        FilterExpr::Filter(id) => global_filters
+            .get(id)
+            .map_or_else(|| format!("#{:x}", id), |f| f.slug.clone()),
+        FilterExpr::Any(exprs) => format!("any({})", list(exprs)),
+        FilterExpr::All(exprs) => format!("all({})", list(exprs)),
+        FilterExpr::Not(e) => format!("not({})", describe_expr(e, global_filters)),
+    }
+}
+
+/// Run the filter chain of the feed (inherited group filters are already merged into
+/// `feed_config.filters`) on an entry.
+///
+/// Exclude rules drop the entry as soon as one of them matches, whatever the keep
+/// rules say. When the chain has keep rules, the entry must match at least one of
+/// them. Named filters with an unknown id are ignored.
+///
+/// Before scraping, the rules depending on the content are skipped, and the entry is
+/// not rejected for missing keep rules while some of them are still to be checked.
+pub(super) fn find_rejection(
+    entry: &feed_rs::model::Entry,
+    feed_config: &Feed,
+    global_filters: &HashMap<u64, Filter>,
+    pass: FilterPass,
+) -> Option<Rejection> {
+    let mut keep_rules: Vec<String> = Vec::new();
+    let mut kept = false;
+    let mut keep_pending = false;
+    for rule in &feed_config.filters {
+        let (keep, expr) = match rule {
+            FilterRule::Named(id) => {
+                let Some(filter) = global_filters.get(id) else {
+                    continue;
+                };
+                (filter.keep, FilterExpr::Filter(*id))
+            }
+            FilterRule::Keep(expr) => (true, expr.clone()),
+            FilterRule::Exclude(expr) => (false, expr.clone()),
+        };
+        if pass == FilterPass::BeforeScraping && expr_uses_content(&expr, global_filters) {
+            keep_pending |= keep;
+            continue;
+        }
+        if keep {
+            if !kept {
+                kept = expr_matches(entry, &expr, global_filters);
+                keep_rules.push(describe_expr(&expr, global_filters));
+            }
+        } else if expr_matches(entry, &expr, global_filters) {
+            return Some(Rejection::Excluded(describe_expr(&expr, global_filters)));
+        }
+    }
+    if keep_rules.is_empty() || kept || keep_pending {
+        None
+    } else {
+        Some(Rejection::NotKept(keep_rules))
+    }
 }
 
 #[cfg(test)]
@@ -133,14 +522,17 @@ mod tests {
     use regex::RegexSet;
 
     use super::*;
-    use crate::model::Filter;
+    use crate::model::{Filter, HttpSettings};
+    use crate::processing::fetch::RetryPolicy;
 
     fn make_plain_filter(expressions: &[&str], must_match_all: bool, keep: bool) -> Filter {
         Filter {
+            slug: expressions.join("-"),
             expressions: expressions.iter().map(|s| s.to_lowercase()).collect(),
             regexes: RegexSet::empty(),
             is_regex: false,
             must_match_all,
+            predicates: Vec::new(),
             filter_in_title: true,
             filter_in_summary: true,
             filter_in_content: true,
@@ -150,10 +542,12 @@ mod tests {
 
     fn make_regex_filter(patterns: &[&str], must_match_all: bool) -> Filter {
         Filter {
+            slug: patterns.join("-"),
             expressions: vec![],
             regexes: RegexSet::new(patterns).unwrap(),
             is_regex: true,
             must_match_all,
+            predicates: Vec::new(),
             filter_in_title: true,
             filter_in_summary: true,
             filter_in_content: true,
@@ -258,4 +652,518 @@ mod tests {
         assert!(check_text_match("Rust is fast", &f));
         assert!(!check_text_match("Rust is slow", &f));
     }
+
+    // ---- find_rejection ----
+
+    fn make_entry(title: &str) -> feed_rs::model::Entry {
+        let xml = format!(
+            r#"<?xml version="1.0"?><rss version="2.0"><channel><title>T</title>
+            <item><title>{title}</title><guid>1</guid></item></channel></rss>"#
+        );
+        feed_rs::parser::parse(xml.as_bytes())
+            .unwrap()
+            .entries
+            .remove(0)
+    }
+
+    /// Builds a feed using `filters` in this order, and the map of the filters by id.
+    fn make_chain(filters: Vec<Filter>) -> (Feed, HashMap<u64, Filter>) {
+        let mut feed = Feed {
+            title: "F".to_string(),
+            slug: "f".to_string(),
+            url: "https://example.com/feed.xml".to_string(),
+            page_url: String::new(),
+            content_mode: crate::model::ContentMode::Default,
+            selector: None,
+            filters: Vec::new(),
+            output: None,
+            retention: 0,
+            min_refresh_time: 600,
+            media: false,
+            media_max_size: 0,
+            enrichment_prepend: None,
+            enrichment_append: None,
+            http: HttpSettings::default(),
+        };
+        let mut map = HashMap::new();
+        for f in filters {
+            let id = XxHash3_64::oneshot(f.slug.as_bytes());
+            feed.filters.push(FilterRule::Named(id));
+            map.insert(id, f);
+        }
+        (feed, map)
+    }
+
+    #[test]
+    fn test_no_filter_keeps_entry() {
+        let (feed, filters) = make_chain(vec![]);
+        assert_eq!(
+            find_rejection(&make_entry("Anything"), &feed, &filters, FilterPass::Final),
+            None
+        );
+    }
+
+    #[test]
+    fn test_exclude_filter_records_its_slug() {
+        let (feed, filters) = make_chain(vec![make_plain_filter(&["sponsored"], false, false)]);
+        assert_eq!(
+            find_rejection(
+                &make_entry("Sponsored: buy this"),
+                &feed,
+                &filters,
+                FilterPass::Final
+            ),
+            Some(Rejection::Excluded("sponsored".to_string()))
+        );
+        assert_eq!(
+            find_rejection(&make_entry("A review"), &feed, &filters, FilterPass::Final),
+            None
+        );
+    }
+
+    #[test]
+    fn test_keep_filters_are_alternatives() {
+        let (feed, filters) = make_chain(vec![
+            make_plain_filter(&["metal"], false, true),
+            make_plain_filter(&["jazz"], false, true),
+        ]);
+        assert_eq!(
+            find_rejection(
+                &make_entry("Metal news"),
+                &feed,
+                &filters,
+                FilterPass::Final
+            ),
+            None
+        );
+        assert_eq!(
+            find_rejection(&make_entry("Jazz news"), &feed, &filters, FilterPass::Final),
+            None
+        );
+        assert_eq!(
+            find_rejection(&make_entry("Pop news"), &feed, &filters, FilterPass::Final),
+            Some(Rejection::NotKept(vec![
+                "metal".to_string(),
+                "jazz".to_string()
+            ]))
+        );
+    }
+
+    #[test]
+    fn test_exclude_wins_over_keep_in_any_order() {
+        for reversed in [false, true] {
+            let mut chain = vec![
+                make_plain_filter(&["metal"], false, true),
+                make_plain_filter(&["sponsored"], false, false),
+            ];
+            if reversed {
+                chain.reverse();
+            }
+            let (feed, filters) = make_chain(chain);
+            assert_eq!(
+                find_rejection(
+                    &make_entry("Metal album"),
+                    &feed,
+                    &filters,
+                    FilterPass::Final
+                ),
+                None
+            );
+            assert_eq!(
+                find_rejection(
+                    &make_entry("Sponsored metal album"),
+                    &feed,
+                    &filters,
+                    FilterPass::Final
+                ),
+                Some(Rejection::Excluded("sponsored".to_string()))
+            );
+            assert_eq!(
+                find_rejection(
+                    &make_entry("Sponsored pop album"),
+                    &feed,
+                    &filters,
+                    FilterPass::Final
+                ),
+                Some(Rejection::Excluded("sponsored".to_string()))
+            );
+            assert_eq!(
+                find_rejection(&make_entry("Pop album"), &feed, &filters, FilterPass::Final),
+                Some(Rejection::NotKept(vec!["metal".to_string()]))
+            );
+        }
+    }
+
+    #[test]
+    fn test_unknown_filter_id_is_ignored() {
+        let (mut feed, filters) = make_chain(vec![make_plain_filter(&["metal"], false, true)]);
+        feed.filters.insert(0, FilterRule::Named(42));
+        assert_eq!(
+            find_rejection(&make_entry("Metal"), &feed, &filters, FilterPass::Final),
+            None
+        );
+    }
+
+    // ---- filter expressions ----
+
+    fn named(slug: &str) -> FilterExpr {
+        FilterExpr::Filter(XxHash3_64::oneshot(slug.as_bytes()))
+    }
+
+    #[test]
+    fn test_keep_expression_unless_excluded() {
+        // "keep metal articles unless they are sponsored", with exclude-flagged filters
+        let (mut feed, filters) = make_chain(vec![
+            make_plain_filter(&["metal"], false, false),
+            make_plain_filter(&["sponsored"], false, false),
+        ]);
+        feed.filters = vec![FilterRule::Keep(FilterExpr::All(vec![
+            named("metal"),
+            FilterExpr::Not(Box::new(named("sponsored"))),
+        ]))];
+        assert_eq!(
+            find_rejection(
+                &make_entry("Metal album"),
+                &feed,
+                &filters,
+                FilterPass::Final
+            ),
+            None
+        );
+        assert_eq!(
+            find_rejection(
+                &make_entry("Sponsored metal album"),
+                &feed,
+                &filters,
+                FilterPass::Final
+            ),
+            Some(Rejection::NotKept(vec![
+                "all(metal, not(sponsored))".to_string()
+            ]))
+        );
+        assert!(
+            find_rejection(
+                &make_entry("Jazz album"),
+                &feed,
+                &filters,
+                FilterPass::Final
+            )
+            .is_some()
+        );
+    }
+
+    #[test]
+    fn test_exclude_nested_expression() {
+        let (mut feed, filters) = make_chain(vec![
+            make_plain_filter(&["trumpet"], false, true),
+            make_plain_filter(&["drum"], false, true),
+            make_plain_filter(&["solo"], false, true),
+        ]);
+        feed.filters = vec![FilterRule::Exclude(FilterExpr::All(vec![
+            FilterExpr::Any(vec![named("trumpet"), named("drum")]),
+            FilterExpr::Not(Box::new(named("solo"))),
+        ]))];
+        assert_eq!(
+            find_rejection(
+                &make_entry("Drum lesson"),
+                &feed,
+                &filters,
+                FilterPass::Final
+            ),
+            Some(Rejection::Excluded(
+                "all(any(trumpet, drum), not(solo))".to_string()
+            ))
+        );
+        assert_eq!(
+            find_rejection(&make_entry("Drum solo"), &feed, &filters, FilterPass::Final),
+            None
+        );
+        assert_eq!(
+            find_rejection(
+                &make_entry("Guitar lesson"),
+                &feed,
+                &filters,
+                FilterPass::Final
+            ),
+            None
+        );
+    }
+
+    // ---- predicates ----
+
+    /// An entry with an author, two categories, a link, an MP3 enclosure, a date
+    /// `days_old` days before now and a 12 characters description.
+    fn make_rich_entry(days_old: i64) -> feed_rs::model::Entry {
+        init_start_time();
+        let date = (*START_TIME.get().unwrap() - chrono::Duration::days(days_old)).to_rfc2822();
+        let xml = format!(
+            r#"<?xml version="1.0"?><rss version="2.0"><channel><title>T</title>
+            <item><title>Episode</title><guid>1</guid>
+            <author>jane@example.com (Jane Doe)</author>
+            <category>Rust</category><category>Podcast</category>
+            <link>https://www.example.com/episode</link>
+            <enclosure url="https://cdn.example.com/e.mp3" type="audio/mpeg" length="1"/>
+            <pubDate>{date}</pubDate>
+            <description>Twelve chars</description>
+            </item></channel></rss>"#
+        );
+        feed_rs::parser::parse(xml.as_bytes())
+            .unwrap()
+            .entries
+            .remove(0)
+    }
+
+    fn predicate(field: PredicateField, op: PredicateOp, negate: bool) -> Predicate {
+        Predicate { field, op, negate }
+    }
+
+    #[test]
+    fn test_predicate_text_fields() {
+        let entry = make_rich_entry(1);
+        let author = entry.authors[0].name.to_lowercase();
+        let cases = [
+            (PredicateField::Author, PredicateOp::Equals(author)),
+            (
+                PredicateField::Category,
+                PredicateOp::In(vec!["go".to_string(), "rust".to_string()]),
+            ),
+            (
+                PredicateField::LinkDomain,
+                PredicateOp::StartsWith("www.example".to_string()),
+            ),
+            (
+                PredicateField::EnclosureType,
+                PredicateOp::Regex(regex::Regex::new("^audio/").unwrap()),
+            ),
+        ];
+        for (field, op) in cases {
+            assert!(predicate_matches(
+                &entry,
+                &predicate(field, op.clone(), false)
+            ));
+            assert!(!predicate_matches(&entry, &predicate(field, op, true)));
+        }
+        let other = PredicateOp::Equals("video/mp4".to_string());
+        assert!(!predicate_matches(
+            &entry,
+            &predicate(PredicateField::EnclosureType, other.clone(), false)
+        ));
+        assert!(predicate_matches(
+            &entry,
+            &predicate(PredicateField::EnclosureType, other, true)
+        ));
+    }
+
+    #[test]
+    fn test_predicate_numeric_fields() {
+        let entry = make_rich_entry(10);
+        let age = |op| predicate_matches(&entry, &predicate(PredicateField::Age, op, false));
+        assert!(age(PredicateOp::GreaterThan(7)));
+        assert!(!age(PredicateOp::LessThan(7)));
+        assert!(age(PredicateOp::NumberEquals(10)));
+        let length =
+            |op| predicate_matches(&entry, &predicate(PredicateField::ContentLength, op, false));
+        assert!(length(PredicateOp::NumberEquals(12)));
+        assert!(length(PredicateOp::LessThan(100)));
+        let published = entry.published.unwrap().timestamp();
+        assert!(predicate_matches(
+            &entry,
+            &predicate(
+                PredicateField::Published,
+                PredicateOp::GreaterThan(published - 1),
+                false
+            )
+        ));
+    }
+
+    #[test]
+    fn test_predicate_on_entry_without_date() {
+        let entry = make_entry("No date");
+        let op = PredicateOp::GreaterThan(0);
+        assert!(!predicate_matches(
+            &entry,
+            &predicate(PredicateField::Age, op.clone(), false)
+        ));
+        assert!(predicate_matches(
+            &entry,
+            &predicate(PredicateField::Age, op, true)
+        ));
+    }
+
+    #[test]
+    fn test_predicates_combined_with_expressions() {
+        let entry = make_rich_entry(1);
+        let is_podcast = predicate(
+            PredicateField::Category,
+            PredicateOp::Equals("podcast".to_string()),
+            false,
+        );
+
+        // predicates only
+        let mut f = make_plain_filter(&[], false, false);
+        f.predicates = vec![is_podcast.clone()];
+        assert!(entry_matches(&entry, &f));
+
+        // any: the expression does not match but the predicate does
+        let mut f = make_plain_filter(&["guitar"], false, false);
+        f.predicates = vec![is_podcast.clone()];
+        assert!(entry_matches(&entry, &f));
+
+        // all: both are required
+        f.must_match_all = true;
+        assert!(!entry_matches(&entry, &f));
+        let mut f = make_plain_filter(&["episode"], true, false);
+        f.predicates = vec![is_podcast];
+        assert!(entry_matches(&entry, &f));
+    }
+
+    // ---- scraped content ----
+
+    fn title_filter(expression: &str) -> Filter {
+        let mut f = make_plain_filter(&[expression], false, false);
+        f.filter_in_content = false;
+        f
+    }
+
+    #[test]
+    fn test_content_rules_wait_for_scraping() {
+        let mut on_content = make_plain_filter(&["sponsor"], false, false);
+        on_content.filter_in_title = false;
+        on_content.filter_in_summary = false;
+        let mut keep_on_content = make_plain_filter(&["metal"], false, true);
+        keep_on_content.filter_in_title = false;
+        keep_on_content.filter_in_summary = false;
+        let (feed, filters) = make_chain(vec![on_content, keep_on_content, title_filter("advert")]);
+        let pass = FilterPass::BeforeScraping;
+        // the content is not known yet: the keep rule is pending
+        assert_eq!(
+            find_rejection(&make_entry("Pop news"), &feed, &filters, pass),
+            None
+        );
+        // rules on the title are already applied
+        assert_eq!(
+            find_rejection(&make_entry("An advert"), &feed, &filters, pass),
+            Some(Rejection::Excluded("advert".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_scraped_content_filtered_and_rejections_remembered() {
+        init_start_time();
+        let xml = r#"<?xml version="1.0"?>
+            <rss version="2.0" xmlns:content="http://purl.org/rss/1.0/modules/content/">
+            <channel><title>T</title>
+            <item><guid>kept</guid><title>Kept</title>
+              <content:encoded><![CDATA[<a href="https://example.com/a">Review</a>]]></content:encoded></item>
+            <item><guid>scraped</guid><title>Scraped</title>
+              <content:encoded><![CDATA[<a href="https://example.com/b">Sponsor</a>]]></content:encoded></item>
+            <item><guid>title</guid><title>An advert</title>
+              <content:encoded><![CDATA[<a href="https://example.com/c">Sponsor</a>]]></content:encoded></item>
+            </channel></rss>"#;
+        let mut fetched = feed_rs::parser::parse(xml.as_bytes()).unwrap();
+        let mut on_content = make_plain_filter(&["sponsor"], false, false);
+        on_content.filter_in_title = false;
+        let (mut feed, filters) = make_chain(vec![on_content, title_filter("advert")]);
+        // links_only replaces the content by the links found in it (no HTTP request
+        // as the entries have no link)
+        feed.content_mode = crate::model::ContentMode::LinksOnly;
+
+        let rejected = apply_filters_and_retention(
+            &mut fetched,
+            42,
+            &feed,
+            &filters,
+            &Fetcher::new(
+                reqwest::Client::new(),
+                1,
+                std::time::Duration::ZERO,
+                RetryPolicy::default(),
+            ),
+            None,
+            &HashSet::new(),
+        )
+        .await;
+
+        let ids: Vec<&str> = fetched.entries.iter().map(|e| e.id.as_str()).collect();
+        assert_eq!(ids, vec!["kept"]);
+        // both rejected entries are remembered, with the filter that dropped them
+        let rejected: Vec<(u64, &str, Option<&str>)> = rejected
+            .iter()
+            .map(|r| (r.id, r.reason.as_str(), r.filter.as_deref()))
+            .collect();
+        assert_eq!(
+            rejected,
+            vec![
+                (XxHash3_64::oneshot(b"title"), "excluded", Some("advert")),
+                (XxHash3_64::oneshot(b"scraped"), "excluded", Some("sponsor")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_filters_fingerprint_follows_the_effective_filters() {
+        let (feed, mut filters) = make_chain(vec![make_plain_filter(&["metal"], false, true)]);
+        let fingerprint = filters_fingerprint(&feed, &filters);
+
+        // an unrelated filter does not matter
+        filters.insert(7, make_plain_filter(&["jazz"], false, true));
+        assert_eq!(filters_fingerprint(&feed, &filters), fingerprint);
+
+        // a loosened definition of a referenced filter does
+        let id = XxHash3_64::oneshot(b"metal");
+        filters.insert(id, make_plain_filter(&["metal", "rock"], false, true));
+        let loosened = filters_fingerprint(&feed, &filters);
+        assert_ne!(loosened, fingerprint);
+
+        // as do the rules and the retention of the feed
+        let mut other_rules = feed.clone();
+        other_rules.filters.push(FilterRule::Named(7));
+        assert_ne!(filters_fingerprint(&other_rules, &filters), loosened);
+        let mut other_retention = feed.clone();
+        other_retention.retention = 30;
+        assert_ne!(filters_fingerprint(&other_retention, &filters), loosened);
+    }
+
+    #[tokio::test]
+    async fn test_failed_scrape_is_not_remembered_as_rejected() {
+        init_start_time();
+        let (base_url, _) = crate::processing::fetch::serve(
+            "HTTP/1.1 503 Service Unavailable\r\nContent-Length: 7\r\nConnection: close\r\n\r\nSponsor"
+                .to_string(),
+        )
+        .await;
+        let xml = format!(
+            r#"<?xml version="1.0"?>
+            <rss version="2.0" xmlns:content="http://purl.org/rss/1.0/modules/content/">
+            <channel><title>T</title>
+            <item><guid>snippet</guid><title>Snippet</title><link>{base_url}/a</link>
+              <content:encoded><![CDATA[Read more from our sponsor]]></content:encoded></item>
+            </channel></rss>"#
+        );
+        let mut fetched = feed_rs::parser::parse(xml.as_bytes()).unwrap();
+        let mut on_content = make_plain_filter(&["sponsor"], false, false);
+        on_content.filter_in_title = false;
+        let (mut feed, filters) = make_chain(vec![on_content]);
+        feed.content_mode = crate::model::ContentMode::Force;
+
+        let rejected = apply_filters_and_retention(
+            &mut fetched,
+            42,
+            &feed,
+            &filters,
+            &Fetcher::new(
+                reqwest::Client::new(),
+                1,
+                std::time::Duration::ZERO,
+                RetryPolicy::default(),
+            ),
+            None,
+            &HashSet::new(),
+        )
+        .await;
+
+        // dropped for this run, its page is tried again on the next one
+        assert!(fetched.entries.is_empty());
+        assert!(rejected.is_empty());
+    }
 }
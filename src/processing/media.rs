@@ -1,15 +1,21 @@
-// download_asset and rewrite_inline_images are WIP — not yet wired into the main pipeline
-#![allow(dead_code)]
-
 use std::{
     collections::HashSet,
-    path::{Path, PathBuf},
+    path::{Component, Path, PathBuf},
+    sync::LazyLock,
 };
 
-use reqwest::{Client, header};
+use regex::Regex;
+use reqwest::header;
 use scraper::{Html, Selector};
 use twox_hash::XxHash3_64;
 
+use super::fetch::Fetcher;
+use crate::model::{App, Article};
+
+/// Markdown inline image: `![alt](https://host/path.png "optional title")`
+static MD_IMAGE_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"!\[[^\]]*\]\((https?://[^)\s]+)"#).unwrap());
+
 /// Map a MIME content-type string to a file extension.
 fn mime_to_ext(content_type: &str) -> &'static str {
     match content_type.split(';').next().unwrap_or("").trim() {
@@ -50,12 +56,12 @@ fn ext_from_url(url: &str) -> Option<&str> {
 /// Download a single asset, deduplicate by XXH3 hash, and write to `media_dir/<hash>.<ext>`.
 /// Returns the local path on success, `None` if skipped (size limit) or on error.
 pub(crate) async fn download_asset(
-    client: &Client,
+    fetcher: &Fetcher,
     url: &str,
     media_dir: &Path,
     max_size: u64,
 ) -> Option<PathBuf> {
-    let resp = client.get(url).send().await.ok()?;
+    let resp = fetcher.send(fetcher.get_anonymous(url)).await.ok()?;
 
     // Reject early based on Content-Length if available and a limit is set
     if max_size > 0
@@ -79,6 +85,7 @@ pub(crate) async fn download_asset(
         .to_string();
 
     let bytes = resp.bytes().await.ok()?;
+    let bytes = bytes.as_ref();
 
     // Reject after download if actual size exceeds limit (Content-Length may be absent)
     if max_size > 0 && bytes.len() as u64 > max_size {
@@ -91,14 +98,14 @@ pub(crate) async fn download_asset(
         return None;
     }
 
-    let hash = XxHash3_64::oneshot(&bytes);
+    let hash = XxHash3_64::oneshot(bytes);
     let ext = ext_from_url(url).unwrap_or_else(|| mime_to_ext(&content_type));
     let filename = format!("{:016x}.{}", hash, ext);
     let path = media_dir.join(&filename);
 
     // Skip write if already on disk (same hash = same content)
     if !tokio::fs::try_exists(&path).await.unwrap_or(false)
-        && let Err(e) = tokio::fs::write(&path, &bytes).await
+        && let Err(e) = tokio::fs::write(&path, bytes).await
     {
         tracing::error!("Cannot write asset {}: {}", path.display(), e);
         return None;
@@ -107,10 +114,48 @@ pub(crate) async fn download_asset(
     Some(path)
 }
 
-/// Find all external `<img src="...">` in an HTML fragment, download them, and rewrite
-/// their `src` to the local `media/<hash>.<ext>` path. Returns the rewritten HTML.
+/// Collect the unique external image URLs of a content body, either HTML `<img src="...">`
+/// or Markdown `![alt](...)` (content is converted to Markdown before storage).
+fn collect_image_urls(content: &str) -> HashSet<String> {
+    let document = Html::parse_fragment(content);
+    let img_sel = Selector::parse("img").unwrap();
+
+    let mut srcs: HashSet<String> = document
+        .select(&img_sel)
+        .filter_map(|img| img.value().attr("src"))
+        .filter(|src| src.starts_with("http://") || src.starts_with("https://"))
+        .map(|s| s.to_string())
+        .collect();
+    srcs.extend(
+        MD_IMAGE_RE
+            .captures_iter(content)
+            .map(|cap| cap[1].to_string()),
+    );
+    srcs
+}
+
+/// Replace every HTML `src` attribute and Markdown image target pointing to `src` by `local`.
+fn replace_image_url(content: &str, src: &str, local: &str) -> String {
+    content
+        .replace(&format!("src=\"{}\"", src), &format!("src=\"{}\"", local))
+        .replace(&format!("src='{}'", src), &format!("src='{}'", local))
+        .replace(&format!("]({})", src), &format!("]({})", local))
+        .replace(&format!("]({} ", src), &format!("]({} ", local))
+}
+
+/// Local asset referenced by an HTML `src` attribute or a Markdown image target.
+static LOCAL_MEDIA_RE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r#"(src="|src='|\]\()media/"#).unwrap());
+
+/// Relative path of a downloaded asset as referenced from exported files.
+fn local_media_path(path: &Path) -> String {
+    format!("media/{}", path.file_name().unwrap().to_string_lossy())
+}
+
+/// Find all external images in an HTML or Markdown fragment, download them, and rewrite
+/// their URL to the local `media/<hash>.<ext>` path. Returns the rewritten content.
 pub(crate) async fn rewrite_inline_images(
-    client: &Client,
+    fetcher: &Fetcher,
     html: &str,
     media_dir: &Path,
     max_size: u64,
@@ -120,32 +165,111 @@ pub(crate) async fn rewrite_inline_images(
         return html.to_string();
     }
 
-    let document = Html::parse_fragment(html);
-    let img_sel = Selector::parse("img").unwrap();
+    let mut result = html.to_string();
+    for src in collect_image_urls(html) {
+        if let Some(path) = download_asset(fetcher, &src, media_dir, max_size).await {
+            result = replace_image_url(&result, &src, &local_media_path(&path));
+        }
+    }
+    result
+}
 
-    // Collect unique external image URLs (HashSet deduplicates)
-    let srcs: HashSet<String> = document
-        .select(&img_sel)
-        .filter_map(|img| img.value().attr("src"))
-        .filter(|src| src.starts_with("http://") || src.starts_with("https://"))
-        .map(|s| s.to_string())
+/// URL of the media directory for the files exported in `export_dir`: `{base_url}/media`,
+/// or the path of `{output}/media` relative to `export_dir` without `base_url`. `None`
+/// when the stored `media/<hash>.<ext>` paths already resolve from `export_dir`.
+pub(crate) fn media_url(app: &App, export_dir: &Path) -> Option<String> {
+    if let Some(base_url) = &app.base_url {
+        return Some(format!("{}/media", base_url));
+    }
+    let from = normalized_path(export_dir);
+    let to = normalized_path(Path::new(&app.output));
+    let common = from
+        .components()
+        .zip(to.components())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let mut parts: Vec<String> = from
+        .components()
+        .skip(common)
+        .map(|_| "..".to_string())
         .collect();
+    if parts.is_empty() && to.components().count() == common {
+        return None;
+    }
+    parts.extend(
+        to.components()
+            .skip(common)
+            .map(|c| c.as_os_str().to_string_lossy().into_owned()),
+    );
+    parts.push("media".to_string());
+    Some(parts.join("/"))
+}
 
-    let mut result = html.to_string();
-    for src in srcs {
-        if let Some(path) = download_asset(client, &src, media_dir, max_size).await {
-            let filename = path.file_name().unwrap().to_string_lossy();
-            let local = format!("media/{}", filename);
-            result = result.replace(&format!("src=\"{}\"", src), &format!("src=\"{}\"", local));
-            result = result.replace(&format!("src='{}'", src), &format!("src='{}'", local));
+/// Absolute `path` without its `.` and `..` components, the file system is not read.
+fn normalized_path(path: &Path) -> PathBuf {
+    let path = std::path::absolute(path).unwrap_or_else(|_| path.to_path_buf());
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                normalized.pop();
+            }
+            c => normalized.push(c),
         }
     }
-    result
+    normalized
+}
+
+/// Point the local `media/<hash>.<ext>` enclosures and inline images of `article` to
+/// `{media_url}/<hash>.<ext>` (see [`media_url`]), so readers of an export written
+/// elsewhere than the output directory can resolve them.
+pub(crate) fn rebase_media_urls(article: &mut Article, media_url: &str) {
+    for enclosure in &mut article.enclosures {
+        if let Some(file) = enclosure.url.strip_prefix("media/") {
+            enclosure.url = format!("{}/{}", media_url, file);
+        }
+    }
+    let replacement = format!("${{1}}{}/", media_url);
+    article.content = LOCAL_MEDIA_RE
+        .replace_all(&article.content, replacement.as_str())
+        .into_owned();
+}
+
+/// Download the enclosures and inline images of `article` into `media_dir`, then point
+/// `Enclosure::url` and `Article::content` to the local `media/<hash>.<ext>` copies.
+///
+/// Assets that cannot be downloaded (error or above `max_size`) keep their remote URL.
+pub(crate) async fn localize_article_media(
+    fetcher: &Fetcher,
+    article: &mut Article,
+    media_dir: &Path,
+    max_size: u64,
+) {
+    if let Err(e) = tokio::fs::create_dir_all(media_dir).await {
+        tracing::error!("Cannot create media directory: {}", e);
+        return;
+    }
+
+    for enclosure in &mut article.enclosures {
+        if !(enclosure.url.starts_with("http://") || enclosure.url.starts_with("https://")) {
+            continue;
+        }
+        if let Some(path) = download_asset(fetcher, &enclosure.url, media_dir, max_size).await {
+            enclosure.url = local_media_path(&path);
+        }
+    }
+
+    if !article.content.is_empty() {
+        article.content =
+            rewrite_inline_images(fetcher, &article.content, media_dir, max_size).await;
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::model::Enclosure;
 
     // --- mime_to_ext ---
 
@@ -218,4 +342,104 @@ mod tests {
     fn test_ext_from_url_empty_extension() {
         assert_eq!(ext_from_url("https://example.com/file."), None);
     }
+
+    // --- collect_image_urls / replace_image_url ---
+
+    #[test]
+    fn test_collect_image_urls_html_and_markdown() {
+        let content = r#"<img src="https://example.com/a.png"> <img src="media/local.png">
+            ![alt](https://example.com/b.jpg "title") ![](https://example.com/a.png)"#;
+        let urls = collect_image_urls(content);
+        assert_eq!(urls.len(), 2);
+        assert!(urls.contains("https://example.com/a.png"));
+        assert!(urls.contains("https://example.com/b.jpg"));
+    }
+
+    #[test]
+    fn test_replace_image_url_html_and_markdown() {
+        let content = r#"<img src="https://example.com/a.png"> ![x](https://example.com/a.png) ![y](https://example.com/a.png "t")"#;
+        let out = replace_image_url(
+            content,
+            "https://example.com/a.png",
+            "media/0123456789abcdef.png",
+        );
+        assert!(!out.contains("https://example.com/a.png"));
+        assert_eq!(out.matches("media/0123456789abcdef.png").count(), 3);
+    }
+
+    #[test]
+    fn test_rebase_media_urls() {
+        let mut article = Article {
+            id: 1,
+            feed_id: 1,
+            title: String::new(),
+            url: "https://example.com/1".to_string(),
+            summary: None,
+            timestamp: 0,
+            added_at: 0,
+            is_full_content: false,
+            content: r#"<img src="media/0123456789abcdef.png"> ![a](media/0123456789abcdef.png)
+<img src='https://example.com/media/a.png'> media/0123456789abcdef.png"#
+                .to_string(),
+            enclosures: vec![
+                Enclosure {
+                    url: "media/fedcba9876543210.mp3".to_string(),
+                    mime_type: "audio/mpeg".to_string(),
+                    length: None,
+                },
+                Enclosure {
+                    url: "https://example.com/b.mp3".to_string(),
+                    mime_type: "audio/mpeg".to_string(),
+                    length: None,
+                },
+            ],
+        };
+        rebase_media_urls(&mut article, "https://rss.example.org/media");
+        assert_eq!(
+            article.content,
+            r#"<img src="https://rss.example.org/media/0123456789abcdef.png"> ![a](https://rss.example.org/media/0123456789abcdef.png)
+<img src='https://example.com/media/a.png'> media/0123456789abcdef.png"#
+        );
+        assert_eq!(
+            article.enclosures[0].url,
+            "https://rss.example.org/media/fedcba9876543210.mp3"
+        );
+        assert_eq!(article.enclosures[1].url, "https://example.com/b.mp3");
+    }
+
+    #[test]
+    fn test_media_url_relative_to_export_dir() {
+        let app = App {
+            output: "/srv/rss".to_string(),
+            ..App::default()
+        };
+        assert_eq!(media_url(&app, Path::new("/srv/rss")), None);
+        assert_eq!(
+            media_url(&app, Path::new("/srv/rss/music/journal")).as_deref(),
+            Some("../../media")
+        );
+        assert_eq!(
+            media_url(&app, Path::new("/srv/rss/music/../news")).as_deref(),
+            Some("../media")
+        );
+        assert_eq!(
+            media_url(&app, Path::new("/var/www")).as_deref(),
+            Some("../../srv/rss/media")
+        );
+
+        let app = App {
+            base_url: Some("https://rss.example.org".to_string()),
+            ..app
+        };
+        assert_eq!(
+            media_url(&app, Path::new("/srv/rss/music")).as_deref(),
+            Some("https://rss.example.org/media")
+        );
+    }
+
+    #[test]
+    fn test_local_media_path() {
+        let path = Path::new("/var/www/rss/media/0123456789abcdef.jpg");
+        assert_eq!(local_media_path(path), "media/0123456789abcdef.jpg");
+    }
 }
@@ -14,23 +14,41 @@ use tracing::{debug, info};
 use crate::{
     START_TIME,
     error::FrustError,
-    export::{AtomExporter, EpubExporter, Exporter, JsonExporter, MarkdownExporter, RssExporter},
-    model::{App, Article, Enrichment, ExportStrategy, Feed, FeedState, Group},
+    export::{
+        AtomExporter, EpubExporter, Exporter, JsonExporter, MarkdownExporter, RssExporter,
+        SplitExporter,
+        index::{self, FeedReport, IndexData},
+    },
+    model::{
+        App, Article, Channel, Enrichment, ExportStrategy, Feed, FeedState, Group, RejectedArticle,
+    },
     storage::Storage,
-    utils::is_refresh_required,
+    utils::{backoff_delay, is_refresh_required},
 };
+use fetch::{Fetcher, RetryPolicy};
+use schedule::ServerHints;
+
+/// Delay before checking a feed again after its first failure, doubled on each
+/// following failure
+const BACKOFF_BASE_SECS: i64 = 30 * 60;
+/// Longest delay between two checks of a failing feed
+const BACKOFF_MAX_SECS: i64 = 24 * 60 * 60;
 
 type StatesMap = Arc<HashMap<u64, FeedState>>;
 
 pub(crate) mod content;
 pub(crate) mod convert;
+pub(crate) mod discovery;
 pub(crate) mod fetch;
 pub(crate) mod filter;
 pub(crate) mod media;
+mod schedule;
 
 struct FeedResult {
     feed_id: u64,
     articles: Vec<Article>,
+    /// Entries dropped by the retention or the filters
+    rejected: Vec<RejectedArticle>,
     state: FeedState,
 }
 
@@ -38,10 +56,16 @@ struct FeedResult {
 /// filters/retention, persists new articles, then exports per-group output files.
 pub(crate) async fn start(app: &App) -> Result<(), FrustError> {
     debug!("Creating HTTP client");
-    let client = Client::builder()
-        .timeout(std::time::Duration::from_secs(app.timeout as u64))
-        .user_agent("frust/0.1.0")
-        .build()?;
+    let timeout = std::time::Duration::from_secs(app.timeout as u64);
+    let fetcher = Fetcher::new(
+        fetch::build_client(timeout, None)?,
+        app.max_per_host,
+        std::time::Duration::from_millis(app.host_delay_ms),
+        RetryPolicy {
+            retries: app.retries,
+            base_delay: std::time::Duration::from_millis(app.retry_delay_ms),
+        },
+    );
 
     let now = *START_TIME
         .get()
@@ -53,8 +77,12 @@ pub(crate) async fn start(app: &App) -> Result<(), FrustError> {
     let storage = Storage::new(&articles_path, &states_path)?;
 
     let existing_ids: Arc<HashSet<u64>> = Arc::new(match storage.load_article_ids() {
-        Ok(ids) => {
+        Ok(mut ids) => {
             tracing::info!("Loaded {} known article IDs", ids.len());
+            match storage.load_rejected_ids(&filter::feed_fingerprints(app)) {
+                Ok(rejected) => ids.extend(rejected),
+                Err(e) => tracing::warn!("Could not load rejected article IDs: {}", e),
+            }
             ids
         }
         Err(e) => {
@@ -89,125 +117,248 @@ pub(crate) async fn start(app: &App) -> Result<(), FrustError> {
         app.workers
     );
 
+    // One client per proxy, shared by the feeds using it
+    let mut clients: HashMap<Option<String>, Client> = HashMap::new();
+    for (_, feed) in &feeds_to_process {
+        if !clients.contains_key(&feed.http.proxy) {
+            let client = fetch::build_client(timeout, feed.http.proxy.as_deref())?;
+            clients.insert(feed.http.proxy.clone(), client);
+        }
+    }
+
     let filters = &app.filters;
 
     // Phase 1: fetch → filter → convert to Articles (runs concurrently)
-    let results: Vec<FeedResult> = stream::iter(feeds_to_process)
-        .map(|(feed_id, feed)| {
-            let client = client.clone();
-            let min_refresh = app.min_refresh_time;
-            let existing_ids = Arc::clone(&existing_ids);
-            let states = Arc::clone(&states);
-
-            async move {
-                let stored_state = states.get(&feed_id);
-
-                let last_check = stored_state
-                    .and_then(|s| s.last_check_ts)
-                    .and_then(|ts| DateTime::from_timestamp(ts, 0));
-                if !is_refresh_required(last_check, now, min_refresh) {
-                    info!("Refresh not needed for {}", feed.title);
-                    return Ok(None);
-                }
-
-                let mut req = client.get(&feed.url);
-                if let Some(etag) = stored_state.and_then(|s| s.last_etag.as_deref()) {
-                    req = req.header(header::IF_NONE_MATCH, etag);
-                }
-                if let Some(last_mod) = stored_state
-                    .and_then(|s| s.last_modified_ts)
-                    .and_then(|ts| DateTime::from_timestamp(ts, 0))
-                {
-                    req = req.header(header::IF_MODIFIED_SINCE, last_mod.to_rfc2822());
-                }
-
-                debug!("Sending request for {} to {}", feed.title, feed.url);
-                let response = req.send().await?;
-                let http_status = response.status().as_u16();
-
-                if response.status() == reqwest::StatusCode::NOT_MODIFIED {
-                    tracing::info!("Feed '{}' not modified (304)", feed.title);
-                    return Ok(Some(FeedResult {
-                        feed_id,
-                        articles: vec![],
-                        state: FeedState {
-                            last_etag: stored_state.and_then(|s| s.last_etag.clone()),
+    // (feed id, retries of the feed request, outcome)
+    let outcomes: Vec<(u64, u32, Result<Option<FeedResult>, FrustError>)> =
+        stream::iter(feeds_to_process)
+            .map(|(feed_id, feed)| {
+                let client = clients[&feed.http.proxy].clone();
+                let fetcher = fetcher.clone();
+                let existing_ids = Arc::clone(&existing_ids);
+                let states = Arc::clone(&states);
+
+                async move {
+                    let mut retries = 0;
+                    let outcome = async {
+                        let stored_state = states.get(&feed_id);
+
+                        let last_check = stored_state
+                            .and_then(|s| s.last_check_ts)
+                            .and_then(|ts| DateTime::from_timestamp(ts, 0));
+                        if !is_refresh_required(last_check, now, feed.min_refresh_time) {
+                            info!("Refresh not needed for {}", feed.name());
+                            return Ok(None);
+                        }
+                        if let Some(state) = stored_state
+                            && let Some(next_check) = state.next_check_ts
+                            && now_ts < next_check
+                        {
+                            let next_check =
+                                DateTime::from_timestamp(next_check, 0).unwrap_or_default();
+                            if state.consecutive_failures > 0 {
+                                info!(
+                                    "Feed '{}' failed {} time(s), backing off until {}",
+                                    feed.name(),
+                                    state.consecutive_failures,
+                                    next_check
+                                );
+                            } else {
+                                info!("Feed '{}' not due before {}", feed.name(), next_check);
+                            }
+                            return Ok(None);
+                        }
+
+                        let (feed_url, fetcher) =
+                            resolve_feed_url(&fetcher, client, &feed, stored_state).await?;
+                        let discovered_url = feed.url.is_empty().then(|| feed_url.clone());
+
+                        let mut req = fetcher.get(&feed_url);
+                        if let Some(etag) = stored_state.and_then(|s| s.last_etag.as_deref()) {
+                            req = req.header(header::IF_NONE_MATCH, etag);
+                        }
+                        if let Some(last_mod) = stored_state
+                            .and_then(|s| s.last_modified_ts)
+                            .and_then(|ts| DateTime::from_timestamp(ts, 0))
+                        {
+                            req = req.header(header::IF_MODIFIED_SINCE, last_mod.to_rfc2822());
+                        }
+
+                        debug!("Sending request for {} to {}", feed.name(), feed_url);
+                        let response = match fetcher.send(req).await {
+                            Ok(response) => response,
+                            Err(e) => {
+                                retries = e.retries;
+                                return Err(e.into());
+                            }
+                        };
+                        retries = response.retries;
+                        let http_status = response.status().as_u16();
+                        if response.status().is_client_error()
+                            || response.status().is_server_error()
+                        {
+                            return Err(FrustError::HttpStatus(http_status));
+                        }
+                        let mut hints = ServerHints::from_headers(response.headers(), now);
+
+                        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+                            tracing::info!("Feed '{}' not modified (304)", feed.name());
+                            return Ok(Some(FeedResult {
+                                feed_id,
+                                articles: vec![],
+                                rejected: vec![],
+                                state: FeedState {
+                                    last_etag: stored_state.and_then(|s| s.last_etag.clone()),
+                                    last_check_ts: Some(now_ts),
+                                    last_modified_ts: stored_state.and_then(|s| s.last_modified_ts),
+                                    last_http_status: Some(http_status),
+                                    last_success_ts: Some(now_ts),
+                                    next_check_ts: Some(
+                                        hints.next_check(now, feed.min_refresh_time).timestamp(),
+                                    ),
+                                    retries,
+                                    discovered_url,
+                                    title: stored_state.and_then(|s| s.title.clone()),
+                                    page_url: stored_state.and_then(|s| s.page_url.clone()),
+                                    ..FeedState::default()
+                                },
+                            }));
+                        }
+
+                        let new_etag = response
+                            .headers()
+                            .get(header::ETAG)
+                            .and_then(|v| v.to_str().ok())
+                            .map(|s| s.to_string());
+
+                        let new_last_mod = response
+                            .headers()
+                            .get(header::LAST_MODIFIED)
+                            .and_then(|v| v.to_str().ok())
+                            .and_then(|s| DateTime::parse_from_rfc2822(s).ok())
+                            .map(|dt| dt.with_timezone(&Utc));
+
+                        let bytes = response.bytes().await?;
+                        let mut fetched_feed = parser::Builder::new()
+                            .sanitize_content(true)
+                            .build()
+                            .parse(bytes.as_ref())
+                            .map_err(|e| FrustError::FeedParse(e.to_string()))?;
+                        hints.read_feed(bytes.as_ref(), fetched_feed.ttl);
+                        let (title, page_url) = feed_metadata(&fetched_feed);
+
+                        let rejected = filter::apply_filters_and_retention(
+                            &mut fetched_feed,
+                            feed_id,
+                            &feed,
+                            filters,
+                            &fetcher,
+                            feed.selector.clone(),
+                            &existing_ids,
+                        )
+                        .await;
+
+                        let mut articles: Vec<Article> = fetched_feed
+                            .entries
+                            .iter()
+                            .map(|entry| convert::entry_to_article(entry, feed_id, now_ts))
+                            .collect();
+
+                        if feed.media {
+                            let media_dir = Path::new(&app.output).join("media");
+                            for article in &mut articles {
+                                media::localize_article_media(
+                                    &fetcher,
+                                    article,
+                                    &media_dir,
+                                    feed.media_max_size,
+                                )
+                                .await;
+                            }
+                        }
+
+                        tracing::info!(
+                            "Feed '{}': {} new article(s) after filtering",
+                            feed.name(),
+                            articles.len()
+                        );
+
+                        let state = FeedState {
+                            last_etag: new_etag,
                             last_check_ts: Some(now_ts),
-                            last_modified_ts: stored_state.and_then(|s| s.last_modified_ts),
+                            last_modified_ts: new_last_mod.map(|dt| dt.timestamp()),
                             last_http_status: Some(http_status),
-                        },
-                    }));
+                            last_success_ts: Some(now_ts),
+                            next_check_ts: Some(
+                                hints.next_check(now, feed.min_refresh_time).timestamp(),
+                            ),
+                            retries,
+                            discovered_url,
+                            title,
+                            page_url,
+                            ..FeedState::default()
+                        };
+
+                        Ok::<_, FrustError>(Some(FeedResult {
+                            feed_id,
+                            articles,
+                            rejected,
+                            state,
+                        }))
+                    }
+                    .await;
+                    (feed_id, retries, outcome)
                 }
-
-                let new_etag = response
-                    .headers()
-                    .get(header::ETAG)
-                    .and_then(|v| v.to_str().ok())
-                    .map(|s| s.to_string());
-
-                let new_last_mod = response
-                    .headers()
-                    .get(header::LAST_MODIFIED)
-                    .and_then(|v| v.to_str().ok())
-                    .and_then(|s| DateTime::parse_from_rfc2822(s).ok())
-                    .map(|dt| dt.with_timezone(&Utc));
-
-                let bytes = response.bytes().await?;
-                let mut fetched_feed = parser::Builder::new()
-                    .sanitize_content(true)
-                    .build()
-                    .parse(bytes.as_ref())
-                    .map_err(|e| FrustError::FeedParse(e.to_string()))?;
-
-                filter::apply_filters_and_retention(
-                    &mut fetched_feed,
-                    &feed,
-                    filters,
-                    &client,
-                    feed.selector.clone(),
-                    &existing_ids,
-                )
-                .await;
-
-                let articles: Vec<Article> = fetched_feed
-                    .entries
-                    .iter()
-                    .map(|entry| convert::entry_to_article(entry, feed_id, now_ts))
-                    .collect();
-
-                tracing::info!(
-                    "Feed '{}': {} new article(s) after filtering",
-                    feed.title,
-                    articles.len()
-                );
-
-                let state = FeedState {
-                    last_etag: new_etag,
-                    last_check_ts: Some(now_ts),
-                    last_modified_ts: new_last_mod.map(|dt| dt.timestamp()),
-                    last_http_status: Some(http_status),
-                };
-
-                Ok::<_, FrustError>(Some(FeedResult {
+            })
+            .buffer_unordered(app.workers)
+            .collect()
+            .await;
+
+    let mut reports: HashMap<u64, FeedReport> = HashMap::with_capacity(outcomes.len());
+    let mut results: Vec<FeedResult> = Vec::with_capacity(outcomes.len());
+    let mut failed_states: Vec<(u64, FeedState)> = Vec::new();
+    for (feed_id, retries, outcome) in outcomes {
+        match outcome {
+            Ok(Some(r)) => {
+                reports.insert(
                     feed_id,
-                    articles,
-                    state,
-                }))
+                    FeedReport {
+                        new_articles: r.articles.len(),
+                        rejected: r.rejected.len(),
+                        error: None,
+                    },
+                );
+                results.push(r);
             }
-        })
-        .buffer_unordered(app.workers)
-        .filter_map(|res| async move {
-            match res {
-                Ok(Some(r)) => Some(r),
-                Ok(None) => None,
-                Err(e) => {
-                    tracing::error!("Worker error: {}", e);
-                    None
+            Ok(None) => {}
+            Err(e) => {
+                let title = app
+                    .groups
+                    .values()
+                    .find_map(|g| g.feeds.get(&feed_id))
+                    .map_or("?", |f| f.name());
+                if retries > 0 {
+                    tracing::error!(
+                        "Worker error for feed '{}' after {} retries: {}",
+                        title,
+                        retries,
+                        e
+                    );
+                } else {
+                    tracing::error!("Worker error for feed '{}': {}", title, e);
                 }
+                let state = failed_state(states.get(&feed_id), &e, retries, now_ts);
+                failed_states.push((feed_id, state));
+                reports.insert(
+                    feed_id,
+                    FeedReport {
+                        error: Some(e.to_string()),
+                        ..FeedReport::default()
+                    },
+                );
             }
-        })
-        .collect()
-        .await;
+        }
+    }
 
     // Phase 2: persist articles and feed states
     let all_articles: Vec<Article> = results.iter().flat_map(|r| r.articles.clone()).collect();
@@ -217,36 +368,137 @@ pub(crate) async fn start(app: &App) -> Result<(), FrustError> {
         tracing::info!("Persisted {} new article(s)", new_count);
     }
 
-    for result in &results {
-        if let Err(e) = storage.save_feed_state(result.feed_id, &result.state) {
-            tracing::warn!(
-                "Could not save feed state for feed {}: {}",
-                result.feed_id,
-                e
-            );
+    let rejected: Vec<RejectedArticle> = results
+        .iter()
+        .flat_map(|r| r.rejected.iter().cloned())
+        .collect();
+    if !rejected.is_empty() {
+        match storage.save_rejected(&rejected) {
+            Ok(()) => tracing::info!("Remembered {} rejected article(s)", rejected.len()),
+            Err(e) => tracing::warn!("Could not save rejected articles: {}", e),
         }
     }
 
-    // Phase 3: export per-group output files
-    run_group_exports(app, &storage)?;
+    let saved_states = results
+        .iter()
+        .map(|r| (r.feed_id, &r.state))
+        .chain(failed_states.iter().map(|(id, state)| (*id, state)));
+    for (feed_id, state) in saved_states {
+        if let Err(e) = storage.save_feed_state(feed_id, state) {
+            tracing::warn!("Could not save feed state for feed {}: {}", feed_id, e);
+        }
+    }
+
+    // Phase 3: export per-group output files, then the feeds declaring their own output,
+    // with the titles and URLs missing from the configuration taken from the states
+    let stored_states = storage.load_all_states().unwrap_or_else(|e| {
+        tracing::warn!("Could not load feed states, exporting without them: {}", e);
+        HashMap::new()
+    });
+    let mut app = app.clone();
+    app.complete_feeds(&stored_states);
+    run_group_exports(&app, &storage)?;
+    run_feed_exports(&app, &storage)?;
+
+    // Phase 4: landing page of the generated outputs
+    if let Err(e) = run_index_export(&app, &storage, &stored_states, &reports) {
+        tracing::error!("Index page generation failed: {}", e);
+    }
 
     Ok(())
 }
 
-/// Pick an exporter based on the destination file extension.
-/// Defaults to RSS for unknown or `.xml` extensions.
-fn select_exporter(dest: &Path) -> Box<dyn Exporter> {
-    match dest.extension().and_then(|e| e.to_str()) {
+/// Title and page URL (the first `<link>` that is not `self` or another relation) of
+/// a fetched feed, kept in its state.
+fn feed_metadata(fetched: &feed_rs::model::Feed) -> (Option<String>, Option<String>) {
+    let title = fetched
+        .title
+        .as_ref()
+        .map(|t| t.content.trim().to_string())
+        .filter(|t| !t.is_empty());
+    let page_url = fetched
+        .links
+        .iter()
+        .find(|l| l.rel.as_deref().is_none_or(|rel| rel == "alternate"))
+        .map(|l| l.href.clone());
+    (title, page_url)
+}
+
+/// State of a feed whose check failed. The cache headers of the previous state are
+/// kept, and the next check is delayed according to the number of consecutive failures.
+fn failed_state(
+    previous: Option<&FeedState>,
+    error: &FrustError,
+    retries: u32,
+    now_ts: i64,
+) -> FeedState {
+    let mut state = previous.cloned().unwrap_or_default();
+    state.retries = retries;
+    state.consecutive_failures = state.consecutive_failures.saturating_add(1);
+    state.last_check_ts = Some(now_ts);
+    if let FrustError::HttpStatus(status) = error {
+        state.last_http_status = Some(*status);
+        // The discovered feed is gone: discover it again on the next check
+        if matches!(status, 404 | 410) {
+            state.discovered_url = None;
+        }
+    }
+    state.last_error = Some(error.to_string());
+    state.next_check_ts = Some(
+        now_ts
+            + backoff_delay(
+                state.consecutive_failures,
+                BACKOFF_BASE_SECS,
+                BACKOFF_MAX_SECS,
+            ),
+    );
+    state
+}
+
+/// Write `{output}/index.html` from the stored states and rejected entries, and the
+/// reports of the current run.
+fn run_index_export(
+    app: &App,
+    storage: &Storage,
+    states: &HashMap<u64, FeedState>,
+    reports: &HashMap<u64, FeedReport>,
+) -> Result<(), FrustError> {
+    let rejected = storage.load_rejected()?;
+    index::write_index(
+        &IndexData {
+            app,
+            states,
+            reports,
+            rejected: &rejected,
+        },
+        &Path::new(&app.output).join("index.html"),
+    )
+}
+
+/// Pick an exporter based on the format (file extension) and the strategy of an output.
+/// Defaults to RSS for unknown or `xml` formats. RSS, Atom and EPUB write a single
+/// document, split by [`SplitExporter`] for the non-monolithic strategies.
+fn select_exporter(format: Option<&str>, strategy: ExportStrategy) -> Box<dyn Exporter> {
+    let document: Box<dyn Exporter> = match format {
+        Some("json") => return Box::new(JsonExporter { strategy }),
+        Some("md") => return Box::new(MarkdownExporter { strategy }),
         Some("atom") => Box::new(AtomExporter),
-        Some("json") => Box::new(JsonExporter {
-            strategy: ExportStrategy::Monolithic,
-        }),
         Some("epub") => Box::new(EpubExporter),
-        Some("md") => Box::new(MarkdownExporter {
-            strategy: ExportStrategy::Monolithic,
-        }),
         _ => Box::new(RssExporter),
+    };
+    if strategy == ExportStrategy::Monolithic {
+        return document;
     }
+    Box::new(SplitExporter {
+        inner: document,
+        strategy,
+        extension: format.unwrap_or("xml").to_string(),
+    })
+}
+
+/// Format of an output file: its extension.
+fn file_format(dest: &Path) -> Option<&str> {
+    dest.extension().and_then(|e| e.to_str())
 }
 
 /// Build a per-feed enrichment map for a group (keyed by `feed_id`).
@@ -260,7 +512,7 @@ fn build_enrichment_map(group: &Group) -> HashMap<u64, Enrichment> {
 
 fn feed_to_enrichment(feed: &Feed) -> Enrichment {
     Enrichment {
-        feed_title: feed.title.clone(),
+        feed_title: feed.name().to_string(),
         feed_url: feed.url.clone(),
         feed_slug: feed.slug.clone(),
         feed_page_url: feed.page_url.clone(),
@@ -269,6 +521,41 @@ fn feed_to_enrichment(feed: &Feed) -> Enrichment {
     }
 }
 
+/// URL of the feed, and the fetcher sending the settings of the feed to its host.
+///
+/// Feeds declared with a page URL only are discovered once, then the URL cached in
+/// their state is used. The page is fetched with the settings of the feed too.
+async fn resolve_feed_url(
+    fetcher: &Fetcher,
+    client: Client,
+    feed: &Feed,
+    stored_state: Option<&FeedState>,
+) -> Result<(String, Fetcher), FrustError> {
+    let feed_url = if !feed.url.is_empty() {
+        feed.url.clone()
+    } else if let Some(url) = stored_state.and_then(|s| s.discovered_url.clone()) {
+        url
+    } else {
+        let page_fetcher = fetcher.for_feed(client.clone(), &feed.http, &feed.page_url);
+        let discovery = discovery::discover(&page_fetcher, &feed.page_url).await?;
+        let url = discovery.feeds[0].url.clone();
+        info!("Feed '{}' discovered at {}", feed.name(), url);
+        url
+    };
+    let fetcher = fetcher.for_feed(client, &feed.http, &feed_url);
+    Ok((feed_url, fetcher))
+}
+
+/// Resolve a configured output path: absolute paths are kept, relative ones are
+/// placed under the app output directory.
+fn output_path(app: &App, output: &str) -> PathBuf {
+    if Path::new(output).is_absolute() {
+        PathBuf::from(output)
+    } else {
+        Path::new(&app.output).join(output)
+    }
+}
+
 /// For each group, load its articles from storage and write the output file.
 fn run_group_exports(app: &App, storage: &Storage) -> Result<(), FrustError> {
     for group in app.groups.values() {
@@ -291,14 +578,28 @@ fn run_group_exports(app: &App, storage: &Storage) -> Result<(), FrustError> {
             continue;
         }
 
-        let dest = if Path::new(&group.output).is_absolute() {
-            PathBuf::from(&group.output)
-        } else {
-            Path::new(&app.output).join(&group.output)
+        let dest = output_path(app, &group.output);
+        // The files of the other strategies are written in `dest`, a directory
+        let export_dir = match group.strategy {
+            ExportStrategy::Monolithic => dest.parent().unwrap_or(&dest),
+            _ => &dest,
         };
+        if let Some(media_url) = media::media_url(app, export_dir) {
+            articles
+                .iter_mut()
+                .for_each(|a| media::rebase_media_urls(a, &media_url));
+        }
 
-        let exporter = select_exporter(&dest);
-        let link = format!("/{}", group.slug);
+        let format = group.format.as_deref().or_else(|| file_format(&dest));
+        let exporter = select_exporter(format, group.strategy);
+        let self_url = app.public_url(&group.output);
+        let link = self_url
+            .clone()
+            .unwrap_or_else(|| format!("/{}", group.slug));
+        let channel = Channel {
+            self_url,
+            ..group.channel(&link)
+        };
         let enrichments = build_enrichment_map(group);
 
         tracing::info!(
@@ -308,9 +609,262 @@ fn run_group_exports(app: &App, storage: &Storage) -> Result<(), FrustError> {
             dest.display()
         );
 
-        if let Err(e) = exporter.generate(&articles, &group.title, &link, &dest, &enrichments) {
+        if let Err(e) = exporter.generate(&articles, &channel, &dest, &enrichments) {
             tracing::error!("Export failed for group '{}': {}", group.slug, e);
         }
     }
     Ok(())
 }
+
+/// For each feed declaring its own `output`, load its articles from storage and write
+/// a dedicated file besides the group aggregate.
+fn run_feed_exports(app: &App, storage: &Storage) -> Result<(), FrustError> {
+    for group in app.groups.values() {
+        for (feed_id, feed) in &group.feeds {
+            let Some(output) = &feed.output else {
+                continue;
+            };
+            let mut articles = match storage.load_articles_for_feed(*feed_id) {
+                Ok(articles) => articles,
+                Err(e) => {
+                    tracing::warn!("Could not load articles for feed '{}': {}", feed.slug, e);
+                    continue;
+                }
+            };
+
+            if articles.is_empty() {
+                tracing::debug!("Feed '{}' has no articles, skipping export", feed.slug);
+                continue;
+            }
+
+            let dest = output_path(app, output);
+            if let Some(media_url) = media::media_url(app, dest.parent().unwrap_or(&dest)) {
+                articles
+                    .iter_mut()
+                    .for_each(|a| media::rebase_media_urls(a, &media_url));
+            }
+            let exporter = select_exporter(file_format(&dest), ExportStrategy::Monolithic);
+            let self_url = app.public_url(output);
+            let link = self_url
+                .clone()
+                .unwrap_or_else(|| format!("/{}", feed.slug));
+            let enrichments = HashMap::from([(*feed_id, feed_to_enrichment(feed))]);
+            // The language, author and icon of the group also apply to its feeds
+            let channel = Channel {
+                title: feed.name().to_string(),
+                description: None,
+                site_url: Some(feed.page_url.clone()).filter(|u| !u.is_empty()),
+                self_url,
+                ..group.channel(&link)
+            };
+
+            tracing::info!(
+                "Exporting {} article(s) for feed '{}' → {}",
+                articles.len(),
+                feed.slug,
+                dest.display()
+            );
+
+            if let Err(e) = exporter.generate(&articles, &channel, &dest, &enrichments) {
+                tracing::error!("Export failed for feed '{}': {}", feed.slug, e);
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_failed_state_backs_off_and_keeps_cache_headers() {
+        let previous = FeedState {
+            last_etag: Some("etag".to_string()),
+            last_check_ts: Some(1_000),
+            last_http_status: Some(200),
+            last_success_ts: Some(1_000),
+            ..FeedState::default()
+        };
+
+        let first = failed_state(Some(&previous), &FrustError::HttpStatus(503), 2, 10_000);
+        assert_eq!(first.consecutive_failures, 1);
+        assert_eq!(first.retries, 2);
+        assert_eq!(first.last_etag.as_deref(), Some("etag"));
+        assert_eq!(first.last_success_ts, Some(1_000));
+        assert_eq!(first.last_http_status, Some(503));
+        assert_eq!(first.last_error.as_deref(), Some("HTTP status 503"));
+        assert_eq!(first.next_check_ts, Some(10_000 + BACKOFF_BASE_SECS));
+
+        let error = FrustError::FeedParse("bad xml".to_string());
+        let second = failed_state(Some(&first), &error, 0, 20_000);
+        assert_eq!(second.consecutive_failures, 2);
+        assert_eq!(second.retries, 0);
+        assert_eq!(second.last_http_status, Some(503));
+        assert_eq!(second.last_error, Some(error.to_string()));
+        assert_eq!(second.next_check_ts, Some(20_000 + 2 * BACKOFF_BASE_SECS));
+    }
+
+    #[test]
+    fn test_feed_metadata() {
+        let xml = br#"<feed xmlns="http://www.w3.org/2005/Atom">
+  <title> Example </title>
+  <link rel="self" href="https://example.com/atom.xml"/>
+  <link href="https://example.com/"/>
+</feed>"#;
+        let fetched = parser::parse(&xml[..]).unwrap();
+        assert_eq!(
+            feed_metadata(&fetched),
+            (
+                Some("Example".to_string()),
+                Some("https://example.com/".to_string())
+            )
+        );
+    }
+
+    #[test]
+    fn test_failed_state_forgets_vanished_discovered_feed() {
+        let previous = FeedState {
+            discovered_url: Some("https://example.com/feed".to_string()),
+            ..FeedState::default()
+        };
+        let state = failed_state(Some(&previous), &FrustError::HttpStatus(503), 0, 10_000);
+        assert!(state.discovered_url.is_some());
+        let state = failed_state(Some(&previous), &FrustError::HttpStatus(404), 0, 10_000);
+        assert_eq!(state.discovered_url, None);
+    }
+
+    #[test]
+    fn test_failed_state_without_previous_state() {
+        let state = failed_state(None, &FrustError::HttpStatus(404), 0, 10_000);
+        assert_eq!(state.consecutive_failures, 1);
+        assert_eq!(state.last_success_ts, None);
+        assert_eq!(state.last_check_ts, Some(10_000));
+    }
+
+    fn make_feed(slug: &str, output: Option<&str>) -> Feed {
+        Feed {
+            title: slug.to_string(),
+            slug: slug.to_string(),
+            url: format!("https://{}.example.com/feed.xml", slug),
+            page_url: String::new(),
+            content_mode: crate::model::ContentMode::Default,
+            selector: None,
+            filters: Vec::new(),
+            output: output.map(str::to_string),
+            retention: 0,
+            min_refresh_time: 600,
+            media: false,
+            media_max_size: 0,
+            enrichment_prepend: None,
+            enrichment_append: None,
+            http: crate::model::HttpSettings::default(),
+        }
+    }
+
+    fn make_article(id: u64, feed_id: u64, title: &str) -> Article {
+        Article {
+            id,
+            feed_id,
+            title: title.to_string(),
+            url: format!("https://example.com/{}", id),
+            content: String::new(),
+            summary: None,
+            timestamp: 1_705_320_000,
+            added_at: 1_705_320_000,
+            is_full_content: false,
+            enclosures: vec![],
+        }
+    }
+
+    #[test]
+    fn test_feed_with_output_is_exported_alongside_its_group() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let output = dir.path().join("out");
+        let storage = Storage::new(
+            dir.path().join("articles.redb").to_str().unwrap(),
+            dir.path().join("states.redb").to_str().unwrap(),
+        )
+        .unwrap();
+        storage
+            .upsert_articles(vec![
+                make_article(10, 1, "From alpha"),
+                make_article(20, 2, "From beta"),
+            ])
+            .unwrap();
+        let group = Group {
+            slug: "music".to_string(),
+            output: "music.atom".to_string(),
+            feeds: HashMap::from([
+                (1, make_feed("alpha", Some("feeds/alpha.rss"))),
+                (2, make_feed("beta", None)),
+            ]),
+            ..Group::default()
+        };
+        let app = App {
+            output: output.display().to_string(),
+            groups: HashMap::from([(7, group)]),
+            ..App::default()
+        };
+
+        run_group_exports(&app, &storage).unwrap();
+        run_feed_exports(&app, &storage).unwrap();
+
+        let group_xml = std::fs::read_to_string(output.join("music.atom")).unwrap();
+        assert!(group_xml.contains("From alpha") && group_xml.contains("From beta"));
+        let feed_xml = std::fs::read_to_string(output.join("feeds/alpha.rss")).unwrap();
+        assert!(feed_xml.contains("From alpha"));
+        assert!(!feed_xml.contains("From beta"));
+        // the feed without `output` only appears in the group output
+        let mut files: Vec<String> = std::fs::read_dir(&output)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        files.sort();
+        assert_eq!(files, vec!["feeds", "music.atom"]);
+        assert_eq!(std::fs::read_dir(output.join("feeds")).unwrap().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_discovered_feed_on_another_host_gets_the_credentials() {
+        let (feed_host, _) = fetch::serve_on(
+            "127.0.0.2",
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\nConnection: close\r\n\r\n".to_string(),
+        )
+        .await;
+        let page = format!(
+            r#"<html><head><link rel="alternate" type="application/rss+xml" href="{feed_host}/rss"></head></html>"#
+        );
+        let (page_host, _) = fetch::serve(format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/html\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{page}",
+            page.len()
+        ))
+        .await;
+        let mut feed = make_feed("blog", None);
+        feed.url = String::new();
+        feed.page_url = format!("{page_host}/");
+        feed.http.auth = Some(crate::model::HttpAuth::Bearer("t0k3n".to_string()));
+        let fetcher = Fetcher::new(
+            Client::new(),
+            1,
+            std::time::Duration::ZERO,
+            RetryPolicy::default(),
+        );
+
+        for state in [
+            None,
+            Some(FeedState {
+                discovered_url: Some(format!("{feed_host}/rss")),
+                ..FeedState::default()
+            }),
+        ] {
+            let (feed_url, feed_fetcher) =
+                resolve_feed_url(&fetcher, Client::new(), &feed, state.as_ref())
+                    .await
+                    .unwrap();
+            assert_eq!(feed_url, format!("{feed_host}/rss"));
+            let request = feed_fetcher.get(&feed_url).build().unwrap();
+            assert_eq!(request.headers()[header::AUTHORIZATION], "Bearer t0k3n");
+        }
+    }
+}
@@ -0,0 +1,280 @@
+use chrono::{DateTime, Datelike, Duration, DurationRound, Timelike, Utc, Weekday};
+use quick_xml::Reader;
+use quick_xml::events::Event;
+use reqwest::header::{self, HeaderMap};
+
+/// Longest interval a server hint can impose between two checks
+const MAX_HINT_SECS: i64 = 24 * 60 * 60;
+
+/// How often the server wants the feed to be checked, from the HTTP headers and the
+/// feed itself. The hints can only lengthen the configured refresh time.
+#[derive(Debug, Default, PartialEq)]
+pub(super) struct ServerHints {
+    /// Freshness lifetime in seconds from `Cache-Control: max-age` or `Expires`
+    pub(super) max_age: Option<i64>,
+    /// RSS `<ttl>`, in seconds
+    pub(super) ttl: Option<i64>,
+    /// `sy:updatePeriod` divided by `sy:updateFrequency`, in seconds
+    pub(super) update_period: Option<i64>,
+    /// RSS `<skipHours>`, GMT hours when the feed should not be checked
+    pub(super) skip_hours: Vec<u32>,
+    /// RSS `<skipDays>`
+    pub(super) skip_days: Vec<Weekday>,
+}
+
+impl ServerHints {
+    /// Read the freshness lifetime of the response. `Cache-Control` wins over
+    /// `Expires`, which is relative to the `Date` header when the server sent one.
+    pub(super) fn from_headers(headers: &HeaderMap, now: DateTime<Utc>) -> Self {
+        let header_value = |name| headers.get(name).and_then(|v| v.to_str().ok());
+        let max_age = match header_value(header::CACHE_CONTROL) {
+            Some(cache_control) => parse_max_age(cache_control),
+            None => header_value(header::EXPIRES)
+                .and_then(|expires| DateTime::parse_from_rfc2822(expires).ok())
+                .map(|expires| {
+                    let date = header_value(header::DATE)
+                        .and_then(|d| DateTime::parse_from_rfc2822(d).ok())
+                        .map_or(now, |d| d.with_timezone(&Utc));
+                    expires
+                        .with_timezone(&Utc)
+                        .signed_duration_since(date)
+                        .num_seconds()
+                })
+                .filter(|secs| *secs > 0),
+        };
+        Self {
+            max_age,
+            ..Self::default()
+        }
+    }
+
+    /// Read the hints the feed document carries: the `<ttl>` already parsed by
+    /// feed-rs, and the elements it does not keep (`<skipHours>`, `<skipDays>` and
+    /// the syndication module). Scanning stops at the first item.
+    pub(super) fn read_feed(&mut self, bytes: &[u8], ttl: Option<u32>) {
+        self.ttl = ttl.map(|minutes| i64::from(minutes) * 60);
+
+        let mut reader = Reader::from_reader(bytes);
+        reader.config_mut().trim_text(true);
+        let mut buf = Vec::new();
+        let mut parents: Vec<String> = Vec::new();
+        let mut period: Option<i64> = None;
+        let mut frequency: i64 = 1;
+
+        loop {
+            match reader.read_event_into(&mut buf) {
+                Ok(Event::Start(e)) => {
+                    let name = String::from_utf8_lossy(e.local_name().as_ref()).to_string();
+                    if name == "item" || name == "entry" {
+                        break;
+                    }
+                    parents.push(name);
+                }
+                Ok(Event::End(_)) => {
+                    parents.pop();
+                }
+                Ok(Event::Text(t)) => {
+                    let Ok(text) = t.unescape() else {
+                        continue;
+                    };
+                    let text = text.trim();
+                    match parents.as_slice() {
+                        [.., parent, name] if parent == "skipHours" && name == "hour" => {
+                            if let Ok(hour) = text.parse::<u32>()
+                                && hour < 24
+                            {
+                                self.skip_hours.push(hour);
+                            }
+                        }
+                        [.., parent, name] if parent == "skipDays" && name == "day" => {
+                            if let Ok(day) = text.parse::<Weekday>() {
+                                self.skip_days.push(day);
+                            }
+                        }
+                        [.., name] if name == "updatePeriod" => {
+                            period = match text {
+                                "hourly" => Some(60 * 60),
+                                "daily" => Some(24 * 60 * 60),
+                                "weekly" => Some(7 * 24 * 60 * 60),
+                                "monthly" => Some(30 * 24 * 60 * 60),
+                                "yearly" => Some(365 * 24 * 60 * 60),
+                                _ => None,
+                            };
+                        }
+                        [.., name] if name == "updateFrequency" => {
+                            frequency = text.parse().ok().filter(|f| *f > 0).unwrap_or(1);
+                        }
+                        _ => {}
+                    }
+                }
+                Ok(Event::Eof) | Err(_) => break,
+                _ => {}
+            }
+            buf.clear();
+        }
+        self.update_period = period.map(|p| p / frequency);
+    }
+
+    /// Seconds to wait before the next check: the configured refresh time, lengthened
+    /// by the longest hint (itself capped to a day).
+    pub(super) fn interval(&self, min_refresh: i64) -> i64 {
+        [self.max_age, self.ttl, self.update_period]
+            .into_iter()
+            .flatten()
+            .map(|secs| secs.min(MAX_HINT_SECS))
+            .fold(min_refresh, i64::max)
+    }
+
+    /// Time of the next check, moved past the skipped hours and days.
+    pub(super) fn next_check(&self, now: DateTime<Utc>, min_refresh: i64) -> DateTime<Utc> {
+        let mut next = now + Duration::seconds(self.interval(min_refresh));
+        // A week of hours at most, in case every hour is skipped
+        for _ in 0..7 * 24 {
+            if !self.skip_hours.contains(&next.hour()) && !self.skip_days.contains(&next.weekday())
+            {
+                break;
+            }
+            next = next.duration_trunc(Duration::hours(1)).unwrap_or(next) + Duration::hours(1);
+        }
+        next
+    }
+}
+
+/// `max-age` of a `Cache-Control` header, none when the response must not be cached.
+fn parse_max_age(cache_control: &str) -> Option<i64> {
+    let mut max_age = None;
+    for directive in cache_control.split(',').map(str::trim) {
+        let directive = directive.to_ascii_lowercase();
+        if directive == "no-cache" || directive == "no-store" {
+            return None;
+        }
+        if let Some(value) = directive.strip_prefix("max-age=") {
+            max_age = value.trim_matches('"').parse::<i64>().ok();
+        }
+    }
+    max_age.filter(|secs| *secs > 0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use reqwest::header::HeaderValue;
+
+    fn at(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_cache_control_max_age() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::CACHE_CONTROL,
+            HeaderValue::from_static("public, max-age=3600"),
+        );
+        headers.insert(
+            header::EXPIRES,
+            HeaderValue::from_static("Thu, 01 Jan 2026 12:00:00 GMT"),
+        );
+        let hints = ServerHints::from_headers(&headers, at("2026-01-01T00:00:00Z"));
+        assert_eq!(hints.max_age, Some(3600));
+
+        assert_eq!(parse_max_age("no-cache, max-age=3600"), None);
+        assert_eq!(parse_max_age("max-age=0"), None);
+        assert_eq!(parse_max_age("private"), None);
+    }
+
+    #[test]
+    fn test_expires_relative_to_date_header() {
+        let mut headers = HeaderMap::new();
+        headers.insert(
+            header::EXPIRES,
+            HeaderValue::from_static("Thu, 01 Jan 2026 12:00:00 GMT"),
+        );
+        let now = at("2026-01-01T11:00:00Z");
+        assert_eq!(ServerHints::from_headers(&headers, now).max_age, Some(3600));
+
+        headers.insert(
+            header::DATE,
+            HeaderValue::from_static("Thu, 01 Jan 2026 11:30:00 GMT"),
+        );
+        assert_eq!(ServerHints::from_headers(&headers, now).max_age, Some(1800));
+
+        // Already expired
+        let later = at("2026-01-01T13:00:00Z");
+        headers.remove(header::DATE);
+        assert_eq!(ServerHints::from_headers(&headers, later).max_age, None);
+    }
+
+    #[test]
+    fn test_read_feed_hints() {
+        let xml = br#"<?xml version="1.0"?>
+<rss version="2.0" xmlns:sy="http://purl.org/rss/1.0/modules/syndication/">
+  <channel>
+    <title>T</title>
+    <sy:updatePeriod>daily</sy:updatePeriod>
+    <sy:updateFrequency>4</sy:updateFrequency>
+    <skipHours><hour>0</hour><hour>1</hour><hour>42</hour></skipHours>
+    <skipDays><day>Sunday</day></skipDays>
+    <item><title>I</title><skipHours><hour>5</hour></skipHours></item>
+  </channel>
+</rss>"#;
+        let mut hints = ServerHints::default();
+        hints.read_feed(xml, Some(90));
+        assert_eq!(
+            hints,
+            ServerHints {
+                max_age: None,
+                ttl: Some(90 * 60),
+                update_period: Some(6 * 60 * 60),
+                skip_hours: vec![0, 1],
+                skip_days: vec![Weekday::Sun],
+            }
+        );
+    }
+
+    #[test]
+    fn test_hints_only_lengthen_the_interval() {
+        let hints = ServerHints {
+            max_age: Some(300),
+            ttl: Some(3600),
+            ..ServerHints::default()
+        };
+        assert_eq!(hints.interval(600), 3600);
+        assert_eq!(hints.interval(7200), 7200);
+        assert_eq!(ServerHints::default().interval(600), 600);
+
+        let hints = ServerHints {
+            update_period: Some(365 * 24 * 60 * 60),
+            ..ServerHints::default()
+        };
+        assert_eq!(hints.interval(600), MAX_HINT_SECS);
+    }
+
+    #[test]
+    fn test_next_check_skips_hours_and_days() {
+        let hints = ServerHints {
+            skip_hours: vec![23, 0, 1],
+            ..ServerHints::default()
+        };
+        // Saturday 22:55 + 10 minutes lands in a skipped hour
+        let now = at("2026-01-03T22:55:00Z");
+        assert_eq!(hints.next_check(now, 600), at("2026-01-04T02:00:00Z"));
+        assert_eq!(
+            hints.next_check(at("2026-01-03T12:00:00Z"), 600),
+            at("2026-01-03T12:10:00Z")
+        );
+
+        let hints = ServerHints {
+            skip_days: vec![Weekday::Sun],
+            ..hints
+        };
+        assert_eq!(hints.next_check(now, 600), at("2026-01-05T02:00:00Z"));
+
+        let hints = ServerHints {
+            skip_hours: (0..24).collect(),
+            ..ServerHints::default()
+        };
+        // Every hour skipped: the hint is ignored after a week
+        assert!(hints.next_check(now, 600) <= now + Duration::days(8));
+    }
+}
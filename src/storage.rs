@@ -1,11 +1,209 @@
 use crate::error::FrustError;
-use crate::model::{Article, FeedState};
+use crate::model::{Article, Enclosure, FeedState, RejectedArticle};
 use redb::{Database, ReadableDatabase, ReadableTable, TableDefinition};
 use regex::Regex;
+use rkyv::{Archive, Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
+use std::sync::LazyLock;
+
+/// Articles stored whole and compressed before the metadata/body split, split on open
+const LEGACY_ARTICLES_TABLE: TableDefinition<u64, &[u8]> = TableDefinition::new("articles");
+/// Uncompressed metadata of the articles, see `ArticleMeta`
+const ARTICLE_META_TABLE: TableDefinition<u64, &[u8]> = TableDefinition::new("article_meta");
+/// lz4-compressed bodies of the articles, see `ArticleBody`
+const ARTICLE_BODY_TABLE: TableDefinition<u64, &[u8]> = TableDefinition::new("article_bodies");
+/// Index of the articles by feed: `(feed_id, timestamp, article_id)`, kept in sync with
+/// `article_meta` so that the articles of a feed are read without scanning the whole table
+const FEED_INDEX_TABLE: TableDefinition<(u64, i64, u64), ()> =
+    TableDefinition::new("articles_by_feed");
+const STATE_TABLE: TableDefinition<u64, &[u8]> = TableDefinition::new("feed_states");
+/// Unversioned states written before the `feed_states` table, upgraded on open
+const LEGACY_STATE_TABLE: TableDefinition<u64, &[u8]> = TableDefinition::new("states");
+const REJECTED_TABLE: TableDefinition<u64, &[u8]> = TableDefinition::new("rejected_articles");
+
+/// Layout version of `FeedState`, stored as the first byte of each state value.
+/// Bump it when the struct changes and decode the previous layout in `decode_state`.
+const STATE_VERSION: u8 = 1;
+
+/// `FeedState` layout of the legacy `states` table, written without a version
+#[derive(Archive, Deserialize, Serialize)]
+struct LegacyFeedState {
+    last_etag: Option<String>,
+    last_check_ts: Option<i64>,
+    last_modified_ts: Option<i64>,
+    last_http_status: Option<u16>,
+}
+
+impl From<LegacyFeedState> for FeedState {
+    fn from(legacy: LegacyFeedState) -> Self {
+        Self {
+            last_etag: legacy.last_etag,
+            last_check_ts: legacy.last_check_ts,
+            last_modified_ts: legacy.last_modified_ts,
+            last_http_status: legacy.last_http_status,
+            ..Self::default()
+        }
+    }
+}
 
-const ARTICLES_TABLE: TableDefinition<u64, &[u8]> = TableDefinition::new("articles");
-const STATE_TABLE: TableDefinition<u64, &[u8]> = TableDefinition::new("states");
+fn encode_state(state: &FeedState) -> Result<Vec<u8>, FrustError> {
+    let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(state)?;
+    let mut value = Vec::with_capacity(bytes.len() + 1);
+    value.push(STATE_VERSION);
+    value.extend_from_slice(&bytes);
+    Ok(value)
+}
+
+fn decode_state(value: &[u8]) -> Result<FeedState, FrustError> {
+    match value.split_first() {
+        Some((&STATE_VERSION, bytes)) => {
+            let aligned = aligned_bytes(bytes);
+            let archived =
+                rkyv::access::<rkyv::Archived<FeedState>, rkyv::rancor::Error>(&aligned)?;
+            Ok(rkyv::deserialize::<FeedState, rkyv::rancor::Error>(
+                archived,
+            )?)
+        }
+        Some((version, _)) => Err(FrustError::Serialization(format!(
+            "unsupported feed state version {}",
+            version
+        ))),
+        None => Err(FrustError::Serialization("empty feed state".to_string())),
+    }
+}
+
+/// Copy a stored value to a buffer aligned for `rkyv::access`, as redb does not
+/// guarantee the alignment of the slices it returns.
+fn aligned_bytes(bytes: &[u8]) -> rkyv::util::AlignedVec {
+    let mut aligned = rkyv::util::AlignedVec::with_capacity(bytes.len());
+    aligned.extend_from_slice(bytes);
+    aligned
+}
+
+/// Local media file referenced by an article, e.g. `media/abc123def456789a.jpg`
+static MEDIA_REF: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"media/([0-9a-f]{16}\.[a-zA-Z0-9]{1,5})").unwrap());
+
+/// Part of an article read by the retention, the media purge and the feed index,
+/// accessed in its archived form without decompressing or deserializing the body
+#[derive(Archive, Deserialize, Serialize)]
+struct ArticleMeta {
+    id: u64,
+    feed_id: u64,
+    timestamp: i64,
+    added_at: i64,
+    title: String,
+    url: String,
+    /// File names of the local media referenced by the enclosures and the content
+    media: Vec<String>,
+}
+
+/// Part of an article only read to export it, stored compressed
+#[derive(Archive, Deserialize, Serialize)]
+struct ArticleBody {
+    content: String,
+    summary: Option<String>,
+    is_full_content: bool,
+    enclosures: Vec<Enclosure>,
+}
+
+/// Split an article into its serialized metadata and compressed body
+fn encode_article(article: Article) -> Result<(Vec<u8>, Vec<u8>), FrustError> {
+    let mut media: Vec<String> = Vec::new();
+    let texts = article.enclosures.iter().map(|e| e.url.as_str());
+    for text in texts.chain([article.content.as_str()]) {
+        for cap in MEDIA_REF.captures_iter(text) {
+            if !media.iter().any(|m| m == &cap[1]) {
+                media.push(cap[1].to_string());
+            }
+        }
+    }
+    let meta = ArticleMeta {
+        id: article.id,
+        feed_id: article.feed_id,
+        timestamp: article.timestamp,
+        added_at: article.added_at,
+        title: article.title,
+        url: article.url,
+        media,
+    };
+    let body = ArticleBody {
+        content: article.content,
+        summary: article.summary,
+        is_full_content: article.is_full_content,
+        enclosures: article.enclosures,
+    };
+    let meta_bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&meta)?.to_vec();
+    let body_bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&body)?;
+    Ok((
+        meta_bytes,
+        lz4_flex::compress_prepend_size(body_bytes.as_slice()),
+    ))
+}
+
+/// Article rebuilt from its stored metadata and body
+fn decode_article(meta: &[u8], body: &[u8]) -> Result<Article, FrustError> {
+    let aligned = aligned_bytes(meta);
+    let archived = rkyv::access::<rkyv::Archived<ArticleMeta>, rkyv::rancor::Error>(&aligned)?;
+    let meta = rkyv::deserialize::<ArticleMeta, rkyv::rancor::Error>(archived)?;
+    let decompressed = lz4_flex::decompress_size_prepended(body)
+        .map_err(|e| FrustError::Serialization(e.to_string()))?;
+    let archived = rkyv::access::<rkyv::Archived<ArticleBody>, rkyv::rancor::Error>(&decompressed)?;
+    let body = rkyv::deserialize::<ArticleBody, rkyv::rancor::Error>(archived)?;
+    Ok(Article {
+        id: meta.id,
+        feed_id: meta.feed_id,
+        title: meta.title,
+        url: meta.url,
+        content: body.content,
+        summary: body.summary,
+        timestamp: meta.timestamp,
+        added_at: meta.added_at,
+        is_full_content: body.is_full_content,
+        enclosures: body.enclosures,
+    })
+}
+
+/// Feed id and timestamp of stored metadata, its key in the feed index
+fn meta_sort_key(meta: &[u8]) -> Result<(u64, i64), FrustError> {
+    let aligned = aligned_bytes(meta);
+    let archived = rkyv::access::<rkyv::Archived<ArticleMeta>, rkyv::rancor::Error>(&aligned)?;
+    Ok((archived.feed_id.to_native(), archived.timestamp.to_native()))
+}
+
+/// Article of the legacy `articles` table: compressed whole
+fn decode_legacy_article(value: &[u8]) -> Result<Article, FrustError> {
+    let decompressed = lz4_flex::decompress_size_prepended(value)
+        .map_err(|e| FrustError::Serialization(e.to_string()))?;
+    let archived = rkyv::access::<rkyv::Archived<Article>, rkyv::rancor::Error>(&decompressed)?;
+    Ok(rkyv::deserialize::<Article, rkyv::rancor::Error>(archived)?)
+}
+
+/// Store articles with their feed index entries in `write_txn`, replacing the stored
+/// articles with the same ids
+fn write_articles(
+    write_txn: &redb::WriteTransaction,
+    articles: Vec<Article>,
+) -> Result<(), FrustError> {
+    let mut metas = write_txn.open_table(ARTICLE_META_TABLE)?;
+    let mut bodies = write_txn.open_table(ARTICLE_BODY_TABLE)?;
+    let mut index = write_txn.open_table(FEED_INDEX_TABLE)?;
+    for article in articles {
+        let (feed_id, timestamp, id) = (article.feed_id, article.timestamp, article.id);
+        let (meta, body) = encode_article(article)?;
+        let previous = metas
+            .insert(id, meta.as_slice())?
+            .map(|old| meta_sort_key(old.value()))
+            .transpose()?;
+        // The feed or the date of a replaced article may have changed
+        if let Some((old_feed_id, old_timestamp)) = previous {
+            index.remove((old_feed_id, old_timestamp, id))?;
+        }
+        bodies.insert(id, body.as_slice())?;
+        index.insert((feed_id, timestamp, id), ())?;
+    }
+    Ok(())
+}
 
 pub struct Storage {
     articles_db: Database,
@@ -17,57 +215,218 @@ impl Storage {
         tracing::info!("Creating database files");
         let articles_db = Database::builder().create(articles_path)?;
         let states_db = Database::builder().create(states_path)?;
-        Ok(Self {
+        let storage = Self {
             articles_db,
             states_db,
-        })
+        };
+        storage.upgrade_legacy_states()?;
+        storage.split_legacy_articles()?;
+        storage.build_feed_index()?;
+        Ok(storage)
     }
 
-    /// Save a FeedState using rkyv 0.8
+    /// Move the articles of the legacy `articles` table to the metadata and body tables
+    fn split_legacy_articles(&self) -> Result<(), FrustError> {
+        let read_txn = self.articles_db.begin_read()?;
+        let mut articles = Vec::new();
+        match read_txn.open_table(LEGACY_ARTICLES_TABLE) {
+            Ok(table) => {
+                for item in table.iter()? {
+                    let (_, bytes) = item?;
+                    articles.push(decode_legacy_article(bytes.value())?);
+                }
+            }
+            Err(redb::TableError::TableDoesNotExist(_)) => return Ok(()),
+            Err(e) => return Err(e.into()),
+        }
+        drop(read_txn);
+
+        let count = articles.len();
+        let write_txn = self.articles_db.begin_write()?;
+        write_articles(&write_txn, articles)?;
+        write_txn.delete_table(LEGACY_ARTICLES_TABLE)?;
+        write_txn.commit()?;
+        tracing::info!("Split {} stored article(s) into metadata and body", count);
+        Ok(())
+    }
+
+    /// Index the articles of a database written before the feed index existed
+    fn build_feed_index(&self) -> Result<(), FrustError> {
+        let read_txn = self.articles_db.begin_read()?;
+        match read_txn.open_table(FEED_INDEX_TABLE) {
+            Ok(_) => return Ok(()),
+            Err(redb::TableError::TableDoesNotExist(_)) => {}
+            Err(e) => return Err(e.into()),
+        }
+        let mut keys = Vec::new();
+        match read_txn.open_table(ARTICLE_META_TABLE) {
+            Ok(table) => {
+                for item in table.iter()? {
+                    let (id, bytes) = item?;
+                    let (feed_id, timestamp) = meta_sort_key(bytes.value())?;
+                    keys.push((feed_id, timestamp, id.value()));
+                }
+            }
+            Err(redb::TableError::TableDoesNotExist(_)) => {}
+            Err(e) => return Err(e.into()),
+        }
+        drop(read_txn);
+
+        let write_txn = self.articles_db.begin_write()?;
+        {
+            // Creates the article tables of a new database along with the index
+            write_txn.open_table(ARTICLE_META_TABLE)?;
+            write_txn.open_table(ARTICLE_BODY_TABLE)?;
+            let mut index = write_txn.open_table(FEED_INDEX_TABLE)?;
+            for key in &keys {
+                index.insert(key, ())?;
+            }
+        }
+        write_txn.commit()?;
+        if !keys.is_empty() {
+            tracing::info!("Indexed {} article(s) by feed", keys.len());
+        }
+        Ok(())
+    }
+
+    /// Move the states of the legacy `states` table to the versioned `feed_states` table
+    fn upgrade_legacy_states(&self) -> Result<(), FrustError> {
+        let read_txn = self.states_db.begin_read()?;
+        let mut upgraded = Vec::new();
+        match read_txn.open_table(LEGACY_STATE_TABLE) {
+            Ok(table) => {
+                for item in table.iter()? {
+                    let (id, bytes) = item?;
+                    let aligned = aligned_bytes(bytes.value());
+                    let archived = rkyv::access::<
+                        rkyv::Archived<LegacyFeedState>,
+                        rkyv::rancor::Error,
+                    >(&aligned)?;
+                    let legacy =
+                        rkyv::deserialize::<LegacyFeedState, rkyv::rancor::Error>(archived)?;
+                    upgraded.push((id.value(), FeedState::from(legacy)));
+                }
+            }
+            Err(redb::TableError::TableDoesNotExist(_)) => return Ok(()),
+            Err(e) => return Err(e.into()),
+        }
+        drop(read_txn);
+
+        let write_txn = self.states_db.begin_write()?;
+        {
+            let mut table = write_txn.open_table(STATE_TABLE)?;
+            for (id, state) in &upgraded {
+                // A state saved by a newer version wins over the legacy one
+                if table.get(id)?.is_none() {
+                    table.insert(id, encode_state(state)?.as_slice())?;
+                }
+            }
+        }
+        write_txn.delete_table(LEGACY_STATE_TABLE)?;
+        write_txn.commit()?;
+        tracing::info!("Upgraded {} legacy feed state(s)", upgraded.len());
+        Ok(())
+    }
+
+    /// Save a FeedState using rkyv 0.8, prefixed with its layout version
     pub fn save_feed_state(&self, feed_id: u64, state: &FeedState) -> Result<(), FrustError> {
         tracing::info!("Saving feed state");
         let write_txn = self.states_db.begin_write()?;
         {
             let mut table = write_txn.open_table(STATE_TABLE)?;
-
-            // rkyv 0.8: to_bytes returns a Pooled<AlignedVec>
-            // We use the default API which requires specifying an error type (rancor::Error)
-            let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(state)?;
-
-            table.insert(feed_id, bytes.as_slice())?;
+            table.insert(feed_id, encode_state(state)?.as_slice())?;
         }
         write_txn.commit()?;
         Ok(())
     }
 
-    /// Load all states using rkyv 0.8 access API
+    /// Load all states, skipping (with a warning) the ones that cannot be decoded
     pub fn load_all_states(&self) -> Result<HashMap<u64, FeedState>, FrustError> {
         tracing::info!("Loading feed state");
         let read_txn = self.states_db.begin_read()?;
-        let table = read_txn.open_table(STATE_TABLE)?;
+        let table = match read_txn.open_table(STATE_TABLE) {
+            Ok(table) => table,
+            Err(redb::TableError::TableDoesNotExist(_)) => return Ok(HashMap::new()),
+            Err(e) => return Err(e.into()),
+        };
         let mut states = HashMap::new();
 
         for item in table.iter()? {
             let (id, bytes) = item?;
+            // A state that cannot be read only costs its feed the cache headers
+            match decode_state(bytes.value()) {
+                Ok(state) => {
+                    states.insert(id.value(), state);
+                }
+                Err(e) => tracing::warn!("Ignoring the state of feed {}: {}", id.value(), e),
+            }
+        }
+        Ok(states)
+    }
 
-            // rkyv 0.8: access provides a zero-copy view of the bytes
-            // It requires the Archived version of the struct
-            let bytes_slice = bytes.value();
-            let archived =
-                rkyv::access::<rkyv::Archived<FeedState>, rkyv::rancor::Error>(bytes_slice)?;
+    /// Rekey feed states and articles stored under legacy feed ids.
+    ///
+    /// `legacy_ids` maps a legacy feed id to the current feed id(s) derived from it.
+    /// When exactly one feed matches, its state and articles are moved to the new id.
+    /// When several feeds shared the legacy id, the state is dropped so each feed is
+    /// fetched again, and the articles are left to expire with the default retention.
+    /// Articles are only read when a legacy state was found, so this is cheap once
+    /// the databases have been migrated. Returns the number of rekeyed articles.
+    pub fn migrate_feed_ids(
+        &self,
+        legacy_ids: &HashMap<u64, Vec<u64>>,
+    ) -> Result<usize, FrustError> {
+        let current: HashSet<u64> = legacy_ids.values().flatten().copied().collect();
+        let mut renames: HashMap<u64, u64> = HashMap::new();
+
+        let write_txn = self.states_db.begin_write()?;
+        {
+            let mut table = write_txn.open_table(STATE_TABLE)?;
+            for (old_id, new_ids) in legacy_ids {
+                if current.contains(old_id) {
+                    continue;
+                }
+                let Some(bytes) = table.remove(old_id)?.map(|b| b.value().to_vec()) else {
+                    continue;
+                };
+                if let [new_id] = new_ids.as_slice() {
+                    table.insert(new_id, bytes.as_slice())?;
+                    renames.insert(*old_id, *new_id);
+                } else {
+                    tracing::warn!(
+                        "Feed state {} was shared by {} feeds, it has been reset",
+                        old_id,
+                        new_ids.len()
+                    );
+                }
+            }
+        }
+        write_txn.commit()?;
+
+        if renames.is_empty() {
+            return Ok(0);
+        }
+        tracing::info!("Migrating articles of {} feed(s) to new ids", renames.len());
 
-            // To get an owned FeedState back from the archived view
-            let state: FeedState = rkyv::deserialize::<FeedState, rkyv::rancor::Error>(archived)?;
+        let mut migrated = Vec::new();
+        for (old_id, new_id) in &renames {
+            for mut article in self.load_articles_for_feed(*old_id)? {
+                article.feed_id = *new_id;
+                migrated.push(article);
+            }
+        }
 
-            states.insert(id.value(), state);
+        let count = migrated.len();
+        if count > 0 {
+            self.upsert_articles(migrated)?;
         }
-        Ok(states)
+        Ok(count)
     }
 
     /// Return the set of all article IDs currently stored. Used to skip already-seen entries.
     pub fn load_article_ids(&self) -> Result<HashSet<u64>, FrustError> {
         let read_txn = self.articles_db.begin_read()?;
-        match read_txn.open_table(ARTICLES_TABLE) {
+        match read_txn.open_table(ARTICLE_META_TABLE) {
             Ok(table) => {
                 let ids = table
                     .iter()?
@@ -80,22 +439,93 @@ impl Storage {
         }
     }
 
-    pub fn upsert_articles(&self, articles: Vec<Article>) -> Result<(), FrustError> {
+    /// Remember entries dropped by the retention or the filters, so that they are not
+    /// processed (and possibly scraped) again on the next runs.
+    pub fn save_rejected(&self, rejected: &[RejectedArticle]) -> Result<(), FrustError> {
         let write_txn = self.articles_db.begin_write()?;
         {
-            let mut table = write_txn.open_table(ARTICLES_TABLE)?;
-            for article in articles {
-                // Serialize -> Compress -> Store
-                let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&article)?;
-                let compressed = lz4_flex::compress_prepend_size(bytes.as_slice());
-                table.insert(article.id, compressed.as_slice())?;
+            let mut table = write_txn.open_table(REJECTED_TABLE)?;
+            for r in rejected {
+                let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(r)?;
+                table.insert(r.id, bytes.as_slice())?;
+            }
+        }
+        write_txn.commit()?;
+        Ok(())
+    }
+
+    /// Load all rejected entries, e.g. to list them on the index page.
+    pub fn load_rejected(&self) -> Result<Vec<RejectedArticle>, FrustError> {
+        let read_txn = self.articles_db.begin_read()?;
+        let table = match read_txn.open_table(REJECTED_TABLE) {
+            Ok(t) => t,
+            Err(redb::TableError::TableDoesNotExist(_)) => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut rejected = Vec::new();
+        for item in table.iter()? {
+            let (_, bytes) = item?;
+            let aligned = aligned_bytes(bytes.value());
+            let archived =
+                rkyv::access::<rkyv::Archived<RejectedArticle>, rkyv::rancor::Error>(&aligned)?;
+            rejected.push(rkyv::deserialize::<RejectedArticle, rkyv::rancor::Error>(
+                archived,
+            )?);
+        }
+        Ok(rejected)
+    }
+
+    /// Return the IDs of the entries rejected with the current filters of their feed,
+    /// given as `feed_id -> fingerprint`. Used with [`Self::load_article_ids`] to skip
+    /// already-seen entries; the others are evaluated again.
+    pub fn load_rejected_ids(
+        &self,
+        fingerprints: &HashMap<u64, u64>,
+    ) -> Result<HashSet<u64>, FrustError> {
+        let read_txn = self.articles_db.begin_read()?;
+        let table = match read_txn.open_table(REJECTED_TABLE) {
+            Ok(t) => t,
+            Err(redb::TableError::TableDoesNotExist(_)) => return Ok(HashSet::new()),
+            Err(e) => return Err(e.into()),
+        };
+        let mut ids = HashSet::new();
+        for item in table.iter()? {
+            let (key, bytes) = item?;
+            let aligned = aligned_bytes(bytes.value());
+            let archived =
+                rkyv::access::<rkyv::Archived<RejectedArticle>, rkyv::rancor::Error>(&aligned)?;
+            if fingerprints.get(&archived.feed_id.to_native())
+                == Some(&archived.filters_hash.to_native())
+            {
+                ids.insert(key.value());
             }
         }
+        Ok(ids)
+    }
+
+    /// Forget the entries rejected at or before `rejected_before`, or with filters that
+    /// are not the current ones of their feed (see [`Self::load_rejected_ids`]), so that
+    /// the table does not grow forever. Returns the number of forgotten entries.
+    pub fn delete_stale_rejected(
+        &self,
+        rejected_before: i64,
+        fingerprints: &HashMap<u64, u64>,
+    ) -> Result<usize, FrustError> {
+        self.delete_rejected_where(&|r| {
+            r.rejected_at.to_native() <= rejected_before
+                || fingerprints.get(&r.feed_id.to_native()) != Some(&r.filters_hash.to_native())
+        })
+    }
+
+    pub fn upsert_articles(&self, articles: Vec<Article>) -> Result<(), FrustError> {
+        let write_txn = self.articles_db.begin_write()?;
+        write_articles(&write_txn, articles)?;
         write_txn.commit()?;
         Ok(())
     }
 
-    /// Delete articles that have exceeded their retention window.
+    /// Delete articles that have exceeded their retention window, along with the
+    /// rejected entries of the same age.
     ///
     /// Returns the number of deleted articles.
     /// `now_ts` is the current UNIX timestamp in seconds.
@@ -107,78 +537,108 @@ impl Storage {
         feed_retentions: &HashMap<u64, u16>,
         default_retention: u16,
     ) -> Result<usize, FrustError> {
+        let is_expired = |feed_id: u64, timestamp: i64| {
+            let retention = feed_retentions
+                .get(&feed_id)
+                .copied()
+                .unwrap_or(default_retention);
+            retention != 0 && timestamp <= now_ts - retention as i64 * 86_400
+        };
+        self.delete_expired_rejected(&is_expired)?;
+
+        // The index holds the feed and the date of every article: no article is read
         let read_txn = self.articles_db.begin_read()?;
-        let ids_to_delete: Vec<u64> = match read_txn.open_table(ARTICLES_TABLE) {
-            Ok(table) => {
-                let mut ids = Vec::new();
-                for item in table.iter()? {
-                    let (key, bytes) = item?;
-                    let decompressed = lz4_flex::decompress_size_prepended(bytes.value())
-                        .map_err(|e| FrustError::Serialization(e.to_string()))?;
-                    let archived = rkyv::access::<rkyv::Archived<Article>, rkyv::rancor::Error>(
-                        &decompressed,
-                    )?;
-                    let article: Article =
-                        rkyv::deserialize::<Article, rkyv::rancor::Error>(archived)?;
-                    let retention = feed_retentions
-                        .get(&article.feed_id)
-                        .copied()
-                        .unwrap_or(default_retention);
-                    if retention == 0 {
-                        continue;
-                    }
-                    let cutoff = now_ts - retention as i64 * 86_400;
-                    if article.timestamp <= cutoff {
-                        ids.push(key.value());
+        let keys_to_delete: Vec<(u64, i64, u64)> = match read_txn.open_table(FEED_INDEX_TABLE) {
+            Ok(index) => {
+                let mut keys = Vec::new();
+                for item in index.iter()? {
+                    let (key, _) = item?;
+                    let (feed_id, timestamp, id) = key.value();
+                    if is_expired(feed_id, timestamp) {
+                        keys.push((feed_id, timestamp, id));
                     }
                 }
-                ids
+                keys
             }
             Err(redb::TableError::TableDoesNotExist(_)) => return Ok(0),
             Err(e) => return Err(e.into()),
         };
         drop(read_txn);
 
-        if ids_to_delete.is_empty() {
+        if keys_to_delete.is_empty() {
             return Ok(0);
         }
 
         let write_txn = self.articles_db.begin_write()?;
         {
-            let mut table = write_txn.open_table(ARTICLES_TABLE)?;
-            for id in &ids_to_delete {
-                table.remove(id)?;
+            let mut metas = write_txn.open_table(ARTICLE_META_TABLE)?;
+            let mut bodies = write_txn.open_table(ARTICLE_BODY_TABLE)?;
+            let mut index = write_txn.open_table(FEED_INDEX_TABLE)?;
+            for key in &keys_to_delete {
+                metas.remove(key.2)?;
+                bodies.remove(key.2)?;
+                index.remove(key)?;
             }
         }
         write_txn.commit()?;
-        Ok(ids_to_delete.len())
+        Ok(keys_to_delete.len())
+    }
+
+    /// Forget the rejected entries for which `is_expired(feed_id, timestamp)` holds: they
+    /// are dropped by the retention anyway if they show up again.
+    fn delete_expired_rejected(
+        &self,
+        is_expired: &dyn Fn(u64, i64) -> bool,
+    ) -> Result<(), FrustError> {
+        let deleted = self.delete_rejected_where(&|r| {
+            is_expired(r.feed_id.to_native(), r.timestamp.to_native())
+        })?;
+        if deleted > 0 {
+            tracing::info!("Forgot {} expired rejected article(s)", deleted);
+        }
+        Ok(())
+    }
+
+    fn delete_rejected_where(
+        &self,
+        predicate: &dyn Fn(&rkyv::Archived<RejectedArticle>) -> bool,
+    ) -> Result<usize, FrustError> {
+        let write_txn = self.articles_db.begin_write()?;
+        let deleted = {
+            let mut table = write_txn.open_table(REJECTED_TABLE)?;
+            let mut ids = Vec::new();
+            for item in table.iter()? {
+                let (key, bytes) = item?;
+                let aligned = aligned_bytes(bytes.value());
+                let archived =
+                    rkyv::access::<rkyv::Archived<RejectedArticle>, rkyv::rancor::Error>(&aligned)?;
+                if predicate(archived) {
+                    ids.push(key.value());
+                }
+            }
+            for id in &ids {
+                table.remove(id)?;
+            }
+            ids.len()
+        };
+        write_txn.commit()?;
+        Ok(deleted)
     }
 
     /// Collect bare filenames (e.g. `"abc123def456789a.jpg"`) of every media asset
     /// referenced by stored articles — either in enclosure URLs or inline in content.
+    /// They are read from the metadata, the bodies are not decompressed.
     pub fn collect_media_refs(&self) -> Result<HashSet<String>, FrustError> {
-        let re = Regex::new(r"media/([0-9a-f]{16}\.[a-zA-Z0-9]{1,5})").unwrap();
         let read_txn = self.articles_db.begin_read()?;
         let mut refs = HashSet::new();
-        match read_txn.open_table(ARTICLES_TABLE) {
+        match read_txn.open_table(ARTICLE_META_TABLE) {
             Ok(table) => {
                 for item in table.iter()? {
                     let (_, bytes) = item?;
-                    let decompressed = lz4_flex::decompress_size_prepended(bytes.value())
-                        .map_err(|e| FrustError::Serialization(e.to_string()))?;
-                    let archived = rkyv::access::<rkyv::Archived<Article>, rkyv::rancor::Error>(
-                        &decompressed,
-                    )?;
-                    let article: Article =
-                        rkyv::deserialize::<Article, rkyv::rancor::Error>(archived)?;
-                    for enc in &article.enclosures {
-                        for cap in re.captures_iter(&enc.url) {
-                            refs.insert(cap[1].to_string());
-                        }
-                    }
-                    for cap in re.captures_iter(&article.content) {
-                        refs.insert(cap[1].to_string());
-                    }
+                    let aligned = aligned_bytes(bytes.value());
+                    let archived =
+                        rkyv::access::<rkyv::Archived<ArticleMeta>, rkyv::rancor::Error>(&aligned)?;
+                    refs.extend(archived.media.iter().map(|name| name.as_str().to_string()));
                 }
             }
             Err(redb::TableError::TableDoesNotExist(_)) => {}
@@ -215,35 +675,44 @@ impl Storage {
 
     /// Load all articles for a specific feed (e.g., to regenerate the RSS XML)
     pub fn load_articles_for_feed(&self, feed_id: u64) -> Result<Vec<Article>, FrustError> {
-        tracing::info!("Loading articles for feed");
+        self.load_feed_articles(feed_id, None, None)
+    }
+
+    /// Load the articles of a feed from the feed index, newest first: only the ones
+    /// published at or after `since` when given, and at most `limit` when given.
+    pub fn load_feed_articles(
+        &self,
+        feed_id: u64,
+        since: Option<i64>,
+        limit: Option<usize>,
+    ) -> Result<Vec<Article>, FrustError> {
+        tracing::debug!("Loading articles for feed {}", feed_id);
         let read_txn = self.articles_db.begin_read()?;
-        let table = match read_txn.open_table(ARTICLES_TABLE) {
-            Ok(t) => t,
+        let index = match read_txn.open_table(FEED_INDEX_TABLE) {
+            Ok(index) => index,
             Err(redb::TableError::TableDoesNotExist(_)) => return Ok(Vec::new()),
             Err(e) => return Err(e.into()),
         };
-        let mut articles = Vec::new();
-
-        for item in table.iter()? {
-            let (_, bytes) = item?;
-
-            // 1. Decompress (since articles ARE compressed)
-            let decompressed = lz4_flex::decompress_size_prepended(bytes.value())
-                .map_err(|e| FrustError::Serialization(e.to_string()))?;
+        // Created with the index
+        let metas = read_txn.open_table(ARTICLE_META_TABLE)?;
+        let bodies = read_txn.open_table(ARTICLE_BODY_TABLE)?;
 
-            // 2. Deserialize
-            let archived =
-                rkyv::access::<rkyv::Archived<Article>, rkyv::rancor::Error>(&decompressed)?;
-            let article: Article = rkyv::deserialize::<Article, rkyv::rancor::Error>(archived)?;
-
-            // 3. Filter by feed_id
-            if article.feed_id == feed_id {
-                articles.push(article);
+        let from = (feed_id, since.unwrap_or(i64::MIN), 0);
+        let to = (feed_id, i64::MAX, u64::MAX);
+        let mut articles = Vec::new();
+        for item in index.range(from..=to)?.rev() {
+            if limit.is_some_and(|limit| articles.len() >= limit) {
+                break;
+            }
+            let (key, _) = item?;
+            let (_, _, id) = key.value();
+            match (metas.get(id)?, bodies.get(id)?) {
+                (Some(meta), Some(body)) => {
+                    articles.push(decode_article(meta.value(), body.value())?)
+                }
+                _ => tracing::warn!("Indexed article {} of feed {} is missing", id, feed_id),
             }
         }
-
-        // Sort by date (descending) to have newest articles first in the RSS
-        articles.sort_by_key(|a| std::cmp::Reverse(a.timestamp));
         Ok(articles)
     }
 }
@@ -280,6 +749,114 @@ mod tests {
         }
     }
 
+    fn make_rejected(id: u64, feed_id: u64, timestamp: i64) -> RejectedArticle {
+        RejectedArticle {
+            id,
+            feed_id,
+            title: String::from("Rejected"),
+            url: String::from("http://example.com/rejected"),
+            timestamp,
+            rejected_at: timestamp,
+            reason: String::from("excluded"),
+            filter: Some(String::from("ads")),
+            filters_hash: 99,
+        }
+    }
+
+    /// Fingerprints of feeds 42 and 7 matching [`make_rejected`]
+    fn fingerprints() -> HashMap<u64, u64> {
+        HashMap::from([(42, 99), (7, 99)])
+    }
+
+    #[test]
+    fn test_rejected_ids_roundtrip() {
+        let storage = make_storage();
+        assert!(
+            storage
+                .load_rejected_ids(&fingerprints())
+                .unwrap()
+                .is_empty()
+        );
+        storage
+            .save_rejected(&[make_rejected(1, 42, 1_000), make_rejected(2, 42, 1_000)])
+            .unwrap();
+        storage
+            .save_rejected(&[make_rejected(2, 42, 2_000)])
+            .unwrap();
+        assert_eq!(
+            storage.load_rejected_ids(&fingerprints()).unwrap(),
+            HashSet::from([1, 2])
+        );
+        let mut rejected = storage.load_rejected().unwrap();
+        rejected.sort_by_key(|r| r.id);
+        assert_eq!(
+            rejected,
+            vec![make_rejected(1, 42, 1_000), make_rejected(2, 42, 2_000)]
+        );
+        // rejected entries are not articles
+        assert!(storage.load_article_ids().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_cleanup_expires_rejected_entries() {
+        let storage = make_storage();
+        let now = 1_000_000_i64;
+        storage
+            .save_rejected(&[
+                make_rejected(1, 42, now - 10 * 86_400),
+                make_rejected(2, 42, now - 3 * 86_400),
+                make_rejected(3, 7, now - 9_999 * 86_400),
+            ])
+            .unwrap();
+
+        let mut retentions = HashMap::new();
+        retentions.insert(42u64, 7u16);
+        retentions.insert(7u64, 0u16); // 0 = keep forever
+        let deleted = storage
+            .delete_expired_articles(now, &retentions, 0)
+            .unwrap();
+        assert_eq!(deleted, 0); // only articles are counted
+        assert_eq!(
+            storage.load_rejected_ids(&fingerprints()).unwrap(),
+            HashSet::from([2, 3])
+        );
+    }
+
+    #[test]
+    fn test_rejected_entries_with_other_filters_are_evaluated_again() {
+        let storage = make_storage();
+        let other_filters = RejectedArticle {
+            filters_hash: 1,
+            ..make_rejected(3, 42, 5_000)
+        };
+        let unknown_feed = make_rejected(4, 1234, 5_000);
+        storage
+            .save_rejected(&[
+                make_rejected(1, 42, 1_000),
+                make_rejected(2, 7, 5_000),
+                other_filters,
+                unknown_feed,
+            ])
+            .unwrap();
+        assert_eq!(
+            storage.load_rejected_ids(&fingerprints()).unwrap(),
+            HashSet::from([1, 2])
+        );
+
+        // rejected too long ago, with other filters, or of a removed feed
+        let deleted = storage
+            .delete_stale_rejected(1_000, &fingerprints())
+            .unwrap();
+        assert_eq!(deleted, 3);
+        let remaining: Vec<u64> = storage
+            .load_rejected()
+            .unwrap()
+            .iter()
+            .map(|r| r.id)
+            .collect();
+        assert_eq!(remaining, vec![2]);
+    }
+
     #[test]
     fn test_cleanup_empty_db_returns_zero() {
         let storage = make_storage();
@@ -532,4 +1109,250 @@ mod tests {
 
         let _ = std::fs::remove_dir_all(&dir);
     }
+
+    // ---- migrate_feed_ids ----
+
+    fn make_state(status: u16) -> FeedState {
+        FeedState {
+            last_check_ts: Some(1_000_000),
+            last_http_status: Some(status),
+            ..FeedState::default()
+        }
+    }
+
+    // ---- feed states ----
+
+    #[test]
+    fn test_feed_state_roundtrip() {
+        let storage = make_storage();
+        let state = FeedState {
+            last_etag: Some("\"abc\"".to_string()),
+            consecutive_failures: 2,
+            last_error: Some("HTTP status 500".to_string()),
+            last_success_ts: Some(900_000),
+            next_check_ts: Some(1_003_600),
+            retries: 2,
+            discovered_url: Some("https://example.com/feed.xml".to_string()),
+            title: Some("Example".to_string()),
+            page_url: Some("https://example.com/".to_string()),
+            ..make_state(500)
+        };
+        storage.save_feed_state(1, &state).unwrap();
+        assert_eq!(storage.load_all_states().unwrap()[&1], state);
+    }
+
+    #[test]
+    fn test_undecodable_state_is_skipped() {
+        let storage = make_storage();
+        storage.save_feed_state(1, &make_state(200)).unwrap();
+        let write_txn = storage.states_db.begin_write().unwrap();
+        {
+            let mut table = write_txn.open_table(STATE_TABLE).unwrap();
+            table.insert(2, [STATE_VERSION + 1, 0].as_slice()).unwrap();
+        }
+        write_txn.commit().unwrap();
+
+        let states = storage.load_all_states().unwrap();
+        assert_eq!(states.len(), 1);
+        assert_eq!(states[&1], make_state(200));
+    }
+
+    #[test]
+    fn test_unknown_state_version_is_an_error() {
+        assert!(decode_state(&[STATE_VERSION + 1, 0, 0, 0]).is_err());
+        assert!(decode_state(&[]).is_err());
+    }
+
+    #[test]
+    fn test_legacy_states_are_upgraded() {
+        let articles_path = unique_path("articles");
+        let states_path = unique_path("states");
+        {
+            let db = Database::builder().create(&states_path).unwrap();
+            let write_txn = db.begin_write().unwrap();
+            {
+                let mut table = write_txn.open_table(LEGACY_STATE_TABLE).unwrap();
+                let legacy = LegacyFeedState {
+                    last_etag: Some("etag".to_string()),
+                    last_check_ts: Some(1_000_000),
+                    last_modified_ts: Some(999_000),
+                    last_http_status: Some(200),
+                };
+                let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&legacy).unwrap();
+                table.insert(1, bytes.as_slice()).unwrap();
+            }
+            write_txn.commit().unwrap();
+        }
+
+        let storage = Storage::new(&articles_path, &states_path).unwrap();
+        let states = storage.load_all_states().unwrap();
+        assert_eq!(
+            states[&1],
+            FeedState {
+                last_etag: Some("etag".to_string()),
+                last_modified_ts: Some(999_000),
+                ..make_state(200)
+            }
+        );
+        drop(storage);
+
+        // The legacy table is gone, reopening keeps the upgraded state
+        let storage = Storage::new(&articles_path, &states_path).unwrap();
+        assert_eq!(storage.load_all_states().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_migrate_feed_ids_rekeys_state_and_articles() {
+        let storage = make_storage();
+        storage.save_feed_state(1, &make_state(200)).unwrap();
+        storage
+            .upsert_articles(vec![
+                make_article(10, 1, 1_000_000),
+                make_article(11, 7, 1_000_000),
+            ])
+            .unwrap();
+
+        let legacy = HashMap::from([(1u64, vec![100u64])]);
+        assert_eq!(storage.migrate_feed_ids(&legacy).unwrap(), 1);
+
+        let states = storage.load_all_states().unwrap();
+        assert!(!states.contains_key(&1));
+        assert_eq!(states[&100].last_http_status, Some(200));
+        assert_eq!(storage.load_articles_for_feed(100).unwrap().len(), 1);
+        assert!(storage.load_articles_for_feed(1).unwrap().is_empty());
+        assert_eq!(storage.load_articles_for_feed(7).unwrap().len(), 1);
+
+        // Second run is a no-op
+        assert_eq!(storage.migrate_feed_ids(&legacy).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_migrate_feed_ids_shared_state_is_reset() {
+        let storage = make_storage();
+        storage.save_feed_state(1, &make_state(200)).unwrap();
+        storage
+            .upsert_articles(vec![make_article(10, 1, 1_000_000)])
+            .unwrap();
+
+        let legacy = HashMap::from([(1u64, vec![100u64, 200u64])]);
+        assert_eq!(storage.migrate_feed_ids(&legacy).unwrap(), 0);
+        assert!(storage.load_all_states().unwrap().is_empty());
+        assert_eq!(storage.load_articles_for_feed(1).unwrap().len(), 1);
+    }
+
+    fn ids(articles: &[Article]) -> Vec<u64> {
+        articles.iter().map(|a| a.id).collect()
+    }
+
+    #[test]
+    fn test_feed_articles_newest_first_with_range_queries() {
+        let storage = make_storage();
+        storage
+            .upsert_articles(vec![
+                make_article(1, 42, 1_000),
+                make_article(2, 42, 3_000),
+                make_article(3, 7, 2_000),
+                make_article(4, 42, 2_000),
+            ])
+            .unwrap();
+
+        assert_eq!(ids(&storage.load_articles_for_feed(42).unwrap()), [2, 4, 1]);
+        assert_eq!(ids(&storage.load_articles_for_feed(7).unwrap()), [3]);
+        assert!(storage.load_articles_for_feed(99).unwrap().is_empty());
+        // newest N
+        let newest = storage.load_feed_articles(42, None, Some(2)).unwrap();
+        assert_eq!(ids(&newest), [2, 4]);
+        // since T, bound included
+        let since = storage.load_feed_articles(42, Some(2_000), None).unwrap();
+        assert_eq!(ids(&since), [2, 4]);
+    }
+
+    #[test]
+    fn test_feed_index_follows_updates_and_expiry() {
+        let storage = make_storage();
+        storage
+            .upsert_articles(vec![make_article(1, 42, 1_000)])
+            .unwrap();
+        // Same article moved to another feed with another date
+        storage
+            .upsert_articles(vec![make_article(1, 7, 2_000)])
+            .unwrap();
+        assert!(storage.load_articles_for_feed(42).unwrap().is_empty());
+        assert_eq!(ids(&storage.load_articles_for_feed(7).unwrap()), [1]);
+
+        let now = 2_000 + 10 * 86_400;
+        let deleted = storage
+            .delete_expired_articles(now, &HashMap::from([(7, 7)]), 0)
+            .unwrap();
+        assert_eq!(deleted, 1);
+        assert!(storage.load_articles_for_feed(7).unwrap().is_empty());
+        assert!(storage.load_article_ids().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_article_split_roundtrip() {
+        let storage = make_storage();
+        let mut article = make_article(1, 42, 1_000);
+        article.summary = Some("Summary".to_string());
+        article.is_full_content = true;
+        article.content = "![](media/0123456789abcdef.png)".to_string();
+        article.enclosures.push(Enclosure {
+            url: "media/fedcba9876543210.mp3".to_string(),
+            mime_type: "audio/mpeg".to_string(),
+            length: Some(10),
+        });
+        storage.upsert_articles(vec![article]).unwrap();
+
+        let loaded = storage.load_articles_for_feed(42).unwrap().remove(0);
+        assert_eq!(loaded.title, "Test");
+        assert_eq!(loaded.url, "http://example.com");
+        assert_eq!(loaded.content, "![](media/0123456789abcdef.png)");
+        assert_eq!(loaded.summary.as_deref(), Some("Summary"));
+        assert_eq!(loaded.added_at, 1_000);
+        assert!(loaded.is_full_content);
+        assert_eq!(loaded.enclosures[0].length, Some(10));
+        assert_eq!(
+            storage.collect_media_refs().unwrap(),
+            HashSet::from([
+                "0123456789abcdef.png".to_string(),
+                "fedcba9876543210.mp3".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn test_legacy_articles_are_split_and_indexed() {
+        let articles_path = unique_path("articles");
+        let states_path = unique_path("states");
+        {
+            // Articles stored whole, before the metadata/body split and the feed index
+            let db = Database::builder().create(&articles_path).unwrap();
+            let write_txn = db.begin_write().unwrap();
+            {
+                let mut table = write_txn.open_table(LEGACY_ARTICLES_TABLE).unwrap();
+                let mut with_media = make_article(2, 42, 2_000);
+                with_media.content = "![](media/0123456789abcdef.png)".to_string();
+                for article in [make_article(1, 42, 1_000), with_media] {
+                    let bytes = rkyv::to_bytes::<rkyv::rancor::Error>(&article).unwrap();
+                    let compressed = lz4_flex::compress_prepend_size(bytes.as_slice());
+                    table.insert(article.id, compressed.as_slice()).unwrap();
+                }
+            }
+            write_txn.commit().unwrap();
+        }
+
+        let storage = Storage::new(&articles_path, &states_path).unwrap();
+        let articles = storage.load_articles_for_feed(42).unwrap();
+        assert_eq!(ids(&articles), [2, 1]);
+        assert_eq!(articles[0].content, "![](media/0123456789abcdef.png)");
+        assert_eq!(
+            storage.collect_media_refs().unwrap(),
+            HashSet::from(["0123456789abcdef.png".to_string()])
+        );
+        drop(storage);
+
+        // The legacy table is gone, reopening keeps the split articles
+        let storage = Storage::new(&articles_path, &states_path).unwrap();
+        assert_eq!(storage.load_article_ids().unwrap(), HashSet::from([1, 2]));
+    }
 }
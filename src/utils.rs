@@ -12,6 +12,16 @@ pub(crate) fn is_refresh_required(
     }
 }
 
+/// Delay in seconds before checking a failing feed again: `base` doubled for each
+/// consecutive failure after the first one, capped to `max`
+pub(crate) fn backoff_delay(failures: u32, base: i64, max: i64) -> i64 {
+    if failures == 0 {
+        return 0;
+    }
+    base.saturating_mul(2i64.saturating_pow(failures - 1))
+        .min(max)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -67,4 +77,15 @@ mod tests {
         // Scenario 6: same timestamps
         assert!(is_refresh_required(Some(now), now, 0));
     }
+
+    #[test]
+    fn test_backoff_delay() {
+        assert_eq!(backoff_delay(0, 1800, 86_400), 0);
+        assert_eq!(backoff_delay(1, 1800, 86_400), 1800);
+        assert_eq!(backoff_delay(2, 1800, 86_400), 3600);
+        assert_eq!(backoff_delay(4, 1800, 86_400), 14_400);
+        // Capped, even when the doubling overflows
+        assert_eq!(backoff_delay(7, 1800, 86_400), 86_400);
+        assert_eq!(backoff_delay(u32::MAX, 1800, 86_400), 86_400);
+    }
 }